@@ -0,0 +1,386 @@
+//! Threshold alerts over polled device statistics and states.
+//!
+//! An `AlertEngine` holds `AlertRule`s such as "CPU above 90% for 5 minutes" and is fed
+//! observations, either one device at a time or for a whole site with
+//! `AlertEngine::evaluate_site`. It returns an `Alert` whenever a rule starts firing for a
+//! device, after its condition has held for the rule's duration, and again when the
+//! condition clears. Alerts can be posted to webhooks with `Notifier::notify_alert`.
+//!
+//! ```rust,no_run
+//! # async fn example(client: &unifi_rs::UnifiClient, site_id: unifi_rs::models::id::SiteId) -> Result<(), unifi_rs::UnifiError> {
+//! use std::time::Duration;
+//! use unifi_rs::alerts::{AlertEngine, AlertRule, Metric};
+//!
+//! let mut engine = AlertEngine::new()
+//!     .rule(AlertRule::above("high-cpu", Metric::CpuUtilizationPct, 90.0)
+//!         .for_duration(Duration::from_secs(300)))
+//!     .rule(AlertRule::above("wifi-retries", Metric::TxRetriesPct, 40.0))
+//!     .rule(AlertRule::device_offline("offline"));
+//! loop {
+//!     for alert in engine.evaluate_site(client, site_id).await? {
+//!         println!("{}", alert.summary());
+//!     }
+//!     tokio::time::sleep(Duration::from_secs(60)).await;
+//! }
+//! # }
+//! ```
+
+use crate::client::UnifiClient;
+use crate::errors::UnifiError;
+use crate::models::device::DeviceState;
+use crate::models::id::{DeviceId, SiteId};
+use crate::models::statistics::DeviceStatistics;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// A value of `DeviceStatistics` that rules can compare against a threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    CpuUtilizationPct,
+    MemoryUtilizationPct,
+    LoadAverage1Min,
+    LoadAverage5Min,
+    LoadAverage15Min,
+    /// The highest transmit retry percentage of the device's radios.
+    TxRetriesPct,
+    UplinkTxRateBps,
+    UplinkRxRateBps,
+}
+
+impl Metric {
+    /// Returns the snake_case name of the metric, e.g. `cpu_utilization_pct`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::CpuUtilizationPct => "cpu_utilization_pct",
+            Metric::MemoryUtilizationPct => "memory_utilization_pct",
+            Metric::LoadAverage1Min => "load_average_1min",
+            Metric::LoadAverage5Min => "load_average_5min",
+            Metric::LoadAverage15Min => "load_average_15min",
+            Metric::TxRetriesPct => "tx_retries_pct",
+            Metric::UplinkTxRateBps => "uplink_tx_rate_bps",
+            Metric::UplinkRxRateBps => "uplink_rx_rate_bps",
+        }
+    }
+
+    /// Returns the metric's value in `statistics`, or `None` if the device does not
+    /// report it.
+    pub fn value(&self, statistics: &DeviceStatistics) -> Option<f64> {
+        match self {
+            Metric::CpuUtilizationPct => statistics.cpu_utilization_pct,
+            Metric::MemoryUtilizationPct => statistics.memory_utilization_pct,
+            Metric::LoadAverage1Min => statistics.load_average_1min,
+            Metric::LoadAverage5Min => statistics.load_average_5min,
+            Metric::LoadAverage15Min => statistics.load_average_15min,
+            Metric::TxRetriesPct => statistics
+                .interfaces
+                .as_ref()?
+                .radios
+                .iter()
+                .filter_map(|radio| radio.tx_retries_pct)
+                .reduce(f64::max),
+            Metric::UplinkTxRateBps => statistics.uplink.as_ref().map(|u| u.tx_rate_bps as f64),
+            Metric::UplinkRxRateBps => statistics.uplink.as_ref().map(|u| u.rx_rate_bps as f64),
+        }
+    }
+}
+
+/// What an `AlertRule` checks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The metric is greater than the threshold.
+    Above { metric: Metric, threshold: f64 },
+    /// The metric is less than the threshold.
+    Below { metric: Metric, threshold: f64 },
+    /// The device is in the `Offline` state.
+    DeviceOffline,
+}
+
+/// A named condition that fires once it has held for a duration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    name: String,
+    condition: Condition,
+    for_duration: Duration,
+}
+
+impl AlertRule {
+    pub fn new(name: impl Into<String>, condition: Condition) -> Self {
+        Self {
+            name: name.into(),
+            condition,
+            for_duration: Duration::ZERO,
+        }
+    }
+
+    /// A rule firing when `metric` is greater than `threshold`.
+    pub fn above(name: impl Into<String>, metric: Metric, threshold: f64) -> Self {
+        Self::new(name, Condition::Above { metric, threshold })
+    }
+
+    /// A rule firing when `metric` is less than `threshold`.
+    pub fn below(name: impl Into<String>, metric: Metric, threshold: f64) -> Self {
+        Self::new(name, Condition::Below { metric, threshold })
+    }
+
+    /// A rule firing when a device is offline.
+    pub fn device_offline(name: impl Into<String>) -> Self {
+        Self::new(name, Condition::DeviceOffline)
+    }
+
+    /// Fires only once the condition has held for `duration`, across observations. The
+    /// default of zero fires on the first observation that meets the condition.
+    pub fn for_duration(mut self, duration: Duration) -> Self {
+        self.for_duration = duration;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn condition(&self) -> &Condition {
+        &self.condition
+    }
+}
+
+/// Whether an `Alert` started or stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+/// A rule starting or stopping to fire for a device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// The name of the rule.
+    pub rule: String,
+    pub device_id: DeviceId,
+    pub status: AlertStatus,
+    /// The observed metric value, for threshold rules.
+    pub value: Option<f64>,
+    /// When the condition was first observed.
+    pub since: DateTime<Utc>,
+    /// When the alert fired or resolved.
+    pub at: DateTime<Utc>,
+}
+
+impl Alert {
+    /// Returns a one-line, human-readable description of the alert.
+    pub fn summary(&self) -> String {
+        let status = match self.status {
+            AlertStatus::Firing => "is firing",
+            AlertStatus::Resolved => "resolved",
+        };
+        match self.value {
+            Some(value) => format!(
+                "{} {} for device {} (value {})",
+                self.rule, status, self.device_id, value
+            ),
+            None => format!("{} {} for device {}", self.rule, status, self.device_id),
+        }
+    }
+}
+
+/// Where a rule stands for one device.
+#[derive(Debug, Clone)]
+struct Pending {
+    since: DateTime<Utc>,
+    firing: bool,
+}
+
+/// Evaluates `AlertRule`s against observations and tracks which alerts are firing.
+#[derive(Debug, Clone, Default)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    pending: HashMap<(usize, DeviceId), Pending>,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule.
+    pub fn rule(mut self, rule: AlertRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Evaluates the threshold rules against a device's statistics observed at `at`.
+    ///
+    /// A metric the statistics do not report leaves its rules as they were.
+    ///
+    /// # Returns
+    ///
+    /// The alerts that started or stopped firing.
+    pub fn observe_statistics(
+        &mut self,
+        device_id: DeviceId,
+        statistics: &DeviceStatistics,
+        at: DateTime<Utc>,
+    ) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for index in 0..self.rules.len() {
+            let (metric, threshold, above) = match self.rules[index].condition {
+                Condition::Above { metric, threshold } => (metric, threshold, true),
+                Condition::Below { metric, threshold } => (metric, threshold, false),
+                Condition::DeviceOffline => continue,
+            };
+            if let Some(value) = metric.value(statistics) {
+                let active = if above {
+                    value > threshold
+                } else {
+                    value < threshold
+                };
+                alerts.extend(self.update(index, device_id, active, Some(value), at));
+            }
+        }
+        alerts
+    }
+
+    /// Evaluates the `DeviceOffline` rules against a device's state observed at `at`.
+    ///
+    /// # Returns
+    ///
+    /// The alerts that started or stopped firing.
+    pub fn observe_state(
+        &mut self,
+        device_id: DeviceId,
+        state: &DeviceState,
+        at: DateTime<Utc>,
+    ) -> Vec<Alert> {
+        let offline = *state == DeviceState::Offline;
+        let mut alerts = Vec::new();
+        for index in 0..self.rules.len() {
+            if self.rules[index].condition == Condition::DeviceOffline {
+                alerts.extend(self.update(index, device_id, offline, None, at));
+            }
+        }
+        alerts
+    }
+
+    /// Stops tracking a device, e.g. one removed from the site.
+    ///
+    /// # Returns
+    ///
+    /// A resolved alert for every alert that was firing for the device.
+    pub fn forget_device(&mut self, device_id: DeviceId, at: DateTime<Utc>) -> Vec<Alert> {
+        let keys: Vec<_> = self
+            .pending
+            .keys()
+            .filter(|(_, id)| *id == device_id)
+            .copied()
+            .collect();
+        keys.into_iter()
+            .filter_map(|(index, id)| self.update(index, id, false, None, at))
+            .collect()
+    }
+
+    /// Returns the alerts that are currently firing, as of when each started.
+    pub fn firing(&self) -> Vec<Alert> {
+        let mut firing: Vec<Alert> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.firing)
+            .map(|((index, device_id), pending)| Alert {
+                rule: self.rules[*index].name.clone(),
+                device_id: *device_id,
+                status: AlertStatus::Firing,
+                value: None,
+                since: pending.since,
+                at: pending.since,
+            })
+            .collect();
+        firing.sort_by(|a, b| (&a.rule, a.since).cmp(&(&b.rule, b.since)));
+        firing
+    }
+
+    /// Lists the devices of a site and their latest statistics and evaluates every rule
+    /// against them. Devices no longer in the site are forgotten.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to poll with.
+    /// * `site_id` - The id of the site to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the alerts that started or stopped firing, or a `UnifiError`
+    /// if polling failed, in which case no rule is evaluated.
+    pub async fn evaluate_site(
+        &mut self,
+        client: &UnifiClient,
+        site_id: SiteId,
+    ) -> Result<Vec<Alert>, UnifiError> {
+        let devices = client.list_all_devices(site_id).await?;
+        let statistics = client.get_site_statistics(site_id).await?;
+        let at = Utc::now();
+
+        let mut alerts = Vec::new();
+        for device in &devices {
+            alerts.extend(self.observe_state(device.id, &device.state, at));
+            if let Some(statistics) = statistics.get(&device.id) {
+                alerts.extend(self.observe_statistics(device.id, statistics, at));
+            }
+        }
+        let gone: Vec<DeviceId> = self
+            .pending
+            .keys()
+            .map(|(_, device_id)| *device_id)
+            .filter(|device_id| devices.iter().all(|device| device.id != *device_id))
+            .collect();
+        for device_id in gone {
+            alerts.extend(self.forget_device(device_id, at));
+        }
+        Ok(alerts)
+    }
+
+    /// Records whether rule `index` is met for a device, returning the alert if it started
+    /// or stopped firing.
+    fn update(
+        &mut self,
+        index: usize,
+        device_id: DeviceId,
+        active: bool,
+        value: Option<f64>,
+        at: DateTime<Utc>,
+    ) -> Option<Alert> {
+        let key = (index, device_id);
+        let rule = &self.rules[index];
+        if !active {
+            let pending = self.pending.remove(&key)?;
+            return pending.firing.then(|| Alert {
+                rule: rule.name.clone(),
+                device_id,
+                status: AlertStatus::Resolved,
+                value,
+                since: pending.since,
+                at,
+            });
+        }
+
+        let pending = self.pending.entry(key).or_insert(Pending {
+            since: at,
+            firing: false,
+        });
+        let held = (at - pending.since).to_std().unwrap_or_default();
+        if pending.firing || held < rule.for_duration {
+            return None;
+        }
+        pending.firing = true;
+        Some(Alert {
+            rule: rule.name.clone(),
+            device_id,
+            status: AlertStatus::Firing,
+            value,
+            since: pending.since,
+            at,
+        })
+    }
+}
@@ -0,0 +1,599 @@
+//! The `UnifiApi` trait, abstracting `UnifiClient` so code that uses it can be tested
+//! without a controller.
+//!
+//! Accept `impl UnifiApi` (or `&dyn UnifiApi`) instead of `UnifiClient` and pass a
+//! `MockUnifiApi` in tests. The mock is generated by `mockall` and only available with
+//! the `test-util` feature.
+
+use crate::capabilities::Capabilities;
+use crate::client::UnifiClient;
+use crate::errors::UnifiError;
+use crate::models::client::{
+    AuthorizeGuestRequest, AuthorizeGuestResponse, ClientDetails, ClientOverview,
+};
+use crate::models::common::{
+    ActionAck, ApplicationInfo, FrequencyBand, ListParams, Page, Timestamped,
+};
+use crate::models::device::{DeviceAction, DeviceDetails, DeviceOverview};
+use crate::models::health::{ControllerHealth, HealthReport};
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use crate::models::search::SearchResults;
+use crate::models::site::SiteOverview;
+use crate::models::statistics::{DeviceStatistics, DeviceStatisticsMetrics, SiteStatistics};
+use crate::models::voucher::{Voucher, VoucherCreateRequest};
+use crate::snapshot::{Inventory, SiteSnapshot};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The operations of the UniFi Network API offered by `UnifiClient`.
+///
+/// Methods that return borrowing handles or streams, such as `upgrade_device_firmware`
+/// and `list_devices_stream`, are only available on `UnifiClient` itself.
+#[cfg_attr(feature = "test-util", mockall::automock)]
+#[async_trait]
+pub trait UnifiApi: Send + Sync {
+    /// See `UnifiClient::capabilities`.
+    async fn capabilities(&self) -> Result<Capabilities, UnifiError>;
+
+    /// See `UnifiClient::list_sites`.
+    async fn list_sites(&self, params: &ListParams) -> Result<Page<SiteOverview>, UnifiError>;
+
+    /// See `UnifiClient::default_site_id`.
+    async fn default_site_id(&self) -> Result<SiteId, UnifiError>;
+
+    /// See `UnifiClient::sites_cached`.
+    async fn sites_cached(&self) -> Result<Vec<SiteOverview>, UnifiError>;
+
+    /// See `UnifiClient::refresh_sites`.
+    async fn refresh_sites(&self) -> Result<Vec<SiteOverview>, UnifiError>;
+
+    /// See `UnifiClient::list_devices`.
+    async fn list_devices(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<DeviceOverview>, UnifiError>;
+
+    /// See `UnifiClient::model_counts`.
+    async fn model_counts(&self, site_id: SiteId) -> Result<HashMap<String, usize>, UnifiError>;
+
+    /// See `UnifiClient::get_device_details`.
+    async fn get_device_details(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceDetails, UnifiError>;
+
+    /// See `UnifiClient::get_device_statistics`.
+    async fn get_device_statistics(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceStatistics, UnifiError>;
+
+    /// See `UnifiClient::get_device_statistics_timestamped`.
+    async fn get_device_statistics_timestamped(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<Timestamped<DeviceStatistics>, UnifiError>;
+
+    /// See `UnifiClient::get_device_statistics_metrics`.
+    async fn get_device_statistics_metrics(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceStatisticsMetrics, UnifiError>;
+
+    /// See `UnifiClient::get_site_statistics`.
+    async fn get_site_statistics(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<DeviceId, DeviceStatistics>, UnifiError>;
+
+    /// See `UnifiClient::site_statistics`.
+    async fn site_statistics(&self, site_id: SiteId) -> Result<SiteStatistics, UnifiError>;
+
+    /// See `UnifiClient::execute_device_action`.
+    async fn execute_device_action(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        action: DeviceAction,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::restart_device`.
+    async fn restart_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::list_pending_devices`.
+    async fn list_pending_devices(
+        &self,
+        site_id: SiteId,
+    ) -> Result<Vec<DeviceOverview>, UnifiError>;
+
+    /// See `UnifiClient::adopt_device`.
+    async fn adopt_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::start_locating_device`.
+    async fn start_locating_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::stop_locating_device`.
+    async fn stop_locating_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::power_cycle_port`.
+    async fn power_cycle_port(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        port_idx: i32,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::wait_for_updates_complete`.
+    async fn wait_for_updates_complete(
+        &self,
+        site_id: SiteId,
+        device_ids: &[DeviceId],
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<Vec<(DeviceId, bool)>, UnifiError>;
+
+    /// See `UnifiClient::devices_with_uplink_names`.
+    async fn devices_with_uplink_names(
+        &self,
+        site_id: SiteId,
+    ) -> Result<Vec<(DeviceOverview, Option<String>)>, UnifiError>;
+
+    /// See `UnifiClient::set_device_name`.
+    async fn set_device_name(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        name: &str,
+    ) -> Result<(), UnifiError>;
+
+    /// See `UnifiClient::get_info`.
+    async fn get_info(&self) -> Result<ApplicationInfo, UnifiError>;
+
+    /// See `UnifiClient::wireless_band_distribution`.
+    async fn wireless_band_distribution(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<FrequencyBand, usize>, UnifiError>;
+
+    /// See `UnifiClient::health_check`.
+    async fn health_check(&self) -> HealthReport;
+
+    /// See `UnifiClient::get_controller_health`.
+    async fn get_controller_health(&self) -> Result<ControllerHealth, UnifiError>;
+
+    /// See `UnifiClient::clients_per_wlan`.
+    async fn clients_per_wlan(&self, site_id: SiteId) -> Result<HashMap<Uuid, usize>, UnifiError>;
+
+    /// See `UnifiClient::get_with_accept`.
+    async fn get_with_accept(&self, path: &str, accept: &str) -> Result<Bytes, UnifiError>;
+
+    /// See `UnifiClient::list_clients`.
+    async fn list_clients(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<ClientOverview>, UnifiError>;
+
+    /// See `UnifiClient::get_client_details`.
+    async fn get_client_details(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ClientDetails, UnifiError>;
+
+    /// See `UnifiClient::block_client`.
+    async fn block_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::unblock_client`.
+    async fn unblock_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::reconnect_client`.
+    async fn reconnect_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError>;
+
+    /// See `UnifiClient::authorize_guest_access`.
+    async fn authorize_guest_access(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+        request: &AuthorizeGuestRequest,
+    ) -> Result<AuthorizeGuestResponse, UnifiError>;
+
+    /// See `UnifiClient::list_vouchers`.
+    async fn list_vouchers(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<Voucher>, UnifiError>;
+
+    /// See `UnifiClient::get_voucher_details`.
+    async fn get_voucher_details(
+        &self,
+        site_id: SiteId,
+        voucher_id: Uuid,
+    ) -> Result<Voucher, UnifiError>;
+
+    /// See `UnifiClient::generate_vouchers`.
+    async fn generate_vouchers(
+        &self,
+        site_id: SiteId,
+        request: &VoucherCreateRequest,
+    ) -> Result<Vec<Voucher>, UnifiError>;
+
+    /// See `UnifiClient::delete_voucher`.
+    async fn delete_voucher(&self, site_id: SiteId, voucher_id: Uuid) -> Result<(), UnifiError>;
+
+    /// See `UnifiClient::list_recent_clients`.
+    async fn list_recent_clients(
+        &self,
+        site_id: SiteId,
+        within: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ClientOverview>, UnifiError>;
+
+    /// See `UnifiClient::find_duplicate_ips`.
+    async fn find_duplicate_ips(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<IpAddr, Vec<Uuid>>, UnifiError>;
+
+    /// See `UnifiClient::list_all_sites`.
+    async fn list_all_sites(&self) -> Result<Vec<SiteOverview>, UnifiError>;
+
+    /// See `UnifiClient::list_all_devices`.
+    async fn list_all_devices(&self, site_id: SiteId) -> Result<Vec<DeviceOverview>, UnifiError>;
+
+    /// See `UnifiClient::list_all_clients`.
+    async fn list_all_clients(&self, site_id: SiteId) -> Result<Vec<ClientOverview>, UnifiError>;
+
+    /// See `UnifiClient::site_snapshot`.
+    async fn site_snapshot(&self, site_id: SiteId) -> Result<SiteSnapshot, UnifiError>;
+
+    /// See `UnifiClient::snapshot`.
+    async fn snapshot(&self) -> Result<Inventory, UnifiError>;
+
+    /// See `UnifiClient::search`.
+    async fn search(&self, query: &str) -> Result<SearchResults, UnifiError>;
+}
+
+#[async_trait]
+impl UnifiApi for UnifiClient {
+    async fn capabilities(&self) -> Result<Capabilities, UnifiError> {
+        UnifiClient::capabilities(self).await
+    }
+
+    async fn list_sites(&self, params: &ListParams) -> Result<Page<SiteOverview>, UnifiError> {
+        UnifiClient::list_sites(self, params).await
+    }
+
+    async fn default_site_id(&self) -> Result<SiteId, UnifiError> {
+        UnifiClient::default_site_id(self).await
+    }
+
+    async fn sites_cached(&self) -> Result<Vec<SiteOverview>, UnifiError> {
+        UnifiClient::sites_cached(self).await
+    }
+
+    async fn refresh_sites(&self) -> Result<Vec<SiteOverview>, UnifiError> {
+        UnifiClient::refresh_sites(self).await
+    }
+
+    async fn list_devices(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<DeviceOverview>, UnifiError> {
+        UnifiClient::list_devices(self, site_id, params).await
+    }
+
+    async fn model_counts(&self, site_id: SiteId) -> Result<HashMap<String, usize>, UnifiError> {
+        UnifiClient::model_counts(self, site_id).await
+    }
+
+    async fn get_device_details(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceDetails, UnifiError> {
+        UnifiClient::get_device_details(self, site_id, device_id).await
+    }
+
+    async fn get_device_statistics(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceStatistics, UnifiError> {
+        UnifiClient::get_device_statistics(self, site_id, device_id).await
+    }
+
+    async fn get_device_statistics_timestamped(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<Timestamped<DeviceStatistics>, UnifiError> {
+        UnifiClient::get_device_statistics_timestamped(self, site_id, device_id).await
+    }
+
+    async fn get_device_statistics_metrics(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceStatisticsMetrics, UnifiError> {
+        UnifiClient::get_device_statistics_metrics(self, site_id, device_id).await
+    }
+
+    async fn get_site_statistics(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<DeviceId, DeviceStatistics>, UnifiError> {
+        UnifiClient::get_site_statistics(self, site_id).await
+    }
+
+    async fn site_statistics(&self, site_id: SiteId) -> Result<SiteStatistics, UnifiError> {
+        UnifiClient::site_statistics(self, site_id).await
+    }
+
+    async fn execute_device_action(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        action: DeviceAction,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::execute_device_action(self, site_id, device_id, action).await
+    }
+
+    async fn restart_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::restart_device(self, site_id, device_id).await
+    }
+
+    async fn list_pending_devices(
+        &self,
+        site_id: SiteId,
+    ) -> Result<Vec<DeviceOverview>, UnifiError> {
+        UnifiClient::list_pending_devices(self, site_id).await
+    }
+
+    async fn adopt_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::adopt_device(self, site_id, device_id).await
+    }
+
+    async fn start_locating_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::start_locating_device(self, site_id, device_id).await
+    }
+
+    async fn stop_locating_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::stop_locating_device(self, site_id, device_id).await
+    }
+
+    async fn power_cycle_port(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        port_idx: i32,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::power_cycle_port(self, site_id, device_id, port_idx).await
+    }
+
+    async fn wait_for_updates_complete(
+        &self,
+        site_id: SiteId,
+        device_ids: &[DeviceId],
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<Vec<(DeviceId, bool)>, UnifiError> {
+        UnifiClient::wait_for_updates_complete(self, site_id, device_ids, timeout, interval).await
+    }
+
+    async fn devices_with_uplink_names(
+        &self,
+        site_id: SiteId,
+    ) -> Result<Vec<(DeviceOverview, Option<String>)>, UnifiError> {
+        UnifiClient::devices_with_uplink_names(self, site_id).await
+    }
+
+    async fn set_device_name(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        name: &str,
+    ) -> Result<(), UnifiError> {
+        UnifiClient::set_device_name(self, site_id, device_id, name).await
+    }
+
+    async fn get_info(&self) -> Result<ApplicationInfo, UnifiError> {
+        UnifiClient::get_info(self).await
+    }
+
+    async fn wireless_band_distribution(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<FrequencyBand, usize>, UnifiError> {
+        UnifiClient::wireless_band_distribution(self, site_id).await
+    }
+
+    async fn health_check(&self) -> HealthReport {
+        UnifiClient::health_check(self).await
+    }
+
+    async fn get_controller_health(&self) -> Result<ControllerHealth, UnifiError> {
+        UnifiClient::get_controller_health(self).await
+    }
+
+    async fn clients_per_wlan(&self, site_id: SiteId) -> Result<HashMap<Uuid, usize>, UnifiError> {
+        UnifiClient::clients_per_wlan(self, site_id).await
+    }
+
+    async fn get_with_accept(&self, path: &str, accept: &str) -> Result<Bytes, UnifiError> {
+        UnifiClient::get_with_accept(self, path, accept).await
+    }
+
+    async fn list_clients(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<ClientOverview>, UnifiError> {
+        UnifiClient::list_clients(self, site_id, params).await
+    }
+
+    async fn get_client_details(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ClientDetails, UnifiError> {
+        UnifiClient::get_client_details(self, site_id, client_id).await
+    }
+
+    async fn block_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::block_client(self, site_id, client_id).await
+    }
+
+    async fn unblock_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::unblock_client(self, site_id, client_id).await
+    }
+
+    async fn reconnect_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError> {
+        UnifiClient::reconnect_client(self, site_id, client_id).await
+    }
+
+    async fn authorize_guest_access(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+        request: &AuthorizeGuestRequest,
+    ) -> Result<AuthorizeGuestResponse, UnifiError> {
+        UnifiClient::authorize_guest_access(self, site_id, client_id, request).await
+    }
+
+    async fn list_vouchers(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<Voucher>, UnifiError> {
+        UnifiClient::list_vouchers(self, site_id, params).await
+    }
+
+    async fn get_voucher_details(
+        &self,
+        site_id: SiteId,
+        voucher_id: Uuid,
+    ) -> Result<Voucher, UnifiError> {
+        UnifiClient::get_voucher_details(self, site_id, voucher_id).await
+    }
+
+    async fn generate_vouchers(
+        &self,
+        site_id: SiteId,
+        request: &VoucherCreateRequest,
+    ) -> Result<Vec<Voucher>, UnifiError> {
+        UnifiClient::generate_vouchers(self, site_id, request).await
+    }
+
+    async fn delete_voucher(&self, site_id: SiteId, voucher_id: Uuid) -> Result<(), UnifiError> {
+        UnifiClient::delete_voucher(self, site_id, voucher_id).await
+    }
+
+    async fn list_recent_clients(
+        &self,
+        site_id: SiteId,
+        within: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ClientOverview>, UnifiError> {
+        UnifiClient::list_recent_clients(self, site_id, within, now).await
+    }
+
+    async fn find_duplicate_ips(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<IpAddr, Vec<Uuid>>, UnifiError> {
+        UnifiClient::find_duplicate_ips(self, site_id).await
+    }
+
+    async fn list_all_sites(&self) -> Result<Vec<SiteOverview>, UnifiError> {
+        UnifiClient::list_all_sites(self).await
+    }
+
+    async fn list_all_devices(&self, site_id: SiteId) -> Result<Vec<DeviceOverview>, UnifiError> {
+        UnifiClient::list_all_devices(self, site_id).await
+    }
+
+    async fn list_all_clients(&self, site_id: SiteId) -> Result<Vec<ClientOverview>, UnifiError> {
+        UnifiClient::list_all_clients(self, site_id).await
+    }
+
+    async fn site_snapshot(&self, site_id: SiteId) -> Result<SiteSnapshot, UnifiError> {
+        UnifiClient::site_snapshot(self, site_id).await
+    }
+
+    async fn snapshot(&self) -> Result<Inventory, UnifiError> {
+        UnifiClient::snapshot(self).await
+    }
+
+    async fn search(&self, query: &str) -> Result<SearchResults, UnifiError> {
+        UnifiClient::search(self, query).await
+    }
+}
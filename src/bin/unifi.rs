@@ -0,0 +1,345 @@
+//! `unifi`, a command-line client for the UniFi Network API built on `unifi-rs`.
+//!
+//! The controller is taken from `--url` and `--api-key` (or `UNIFI_BASE_URL` and
+//! `UNIFI_API_KEY`), or from a controller of a `--config` file. Commands act on the
+//! controller's default site unless `--site` is given.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use unifi_rs::config::UnifiConfig;
+use unifi_rs::models::client::ClientOverview;
+use unifi_rs::models::id::{DeviceId, SiteId};
+use unifi_rs::{UnifiClient, UnifiClientBuilder, UnifiError};
+
+#[derive(Parser)]
+#[command(
+    name = "unifi",
+    version,
+    about = "Query and manage a UniFi Network controller"
+)]
+struct Cli {
+    /// The base URL of the controller's integration API.
+    #[arg(long, env = "UNIFI_BASE_URL", global = true)]
+    url: Option<String>,
+    /// The API key.
+    #[arg(long, env = "UNIFI_API_KEY", hide_env_values = true, global = true)]
+    api_key: Option<String>,
+    /// Skips verification of the controller's TLS certificate.
+    #[arg(long, global = true)]
+    insecure: bool,
+    /// A TOML file of controllers, see the `config` module of `unifi-rs`.
+    #[arg(long, env = "UNIFI_CONFIG", global = true)]
+    config: Option<PathBuf>,
+    /// The controller of the config file to use, instead of its default.
+    #[arg(long, global = true)]
+    controller: Option<String>,
+    /// The site to act on, instead of the default site.
+    #[arg(long, env = "UNIFI_SITE", global = true)]
+    site: Option<SiteId>,
+    /// How to print results.
+    #[arg(short, long, value_enum, default_value_t = Output::Table, global = true)]
+    output: Output,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Sites of the controller.
+    #[command(subcommand)]
+    Sites(SitesCommand),
+    /// Adopted devices.
+    #[command(subcommand)]
+    Devices(DevicesCommand),
+    /// Connected clients.
+    #[command(subcommand)]
+    Clients(ClientsCommand),
+    /// Device statistics.
+    #[command(subcommand)]
+    Stats(StatsCommand),
+}
+
+#[derive(Subcommand)]
+enum SitesCommand {
+    /// Lists every site.
+    List,
+}
+
+#[derive(Subcommand)]
+enum DevicesCommand {
+    /// Lists every device in the site.
+    List,
+    /// Shows the details of a device.
+    Show { device_id: DeviceId },
+    /// Restarts a device.
+    Restart { device_id: DeviceId },
+}
+
+#[derive(Subcommand)]
+enum ClientsCommand {
+    /// Lists every connected client in the site.
+    List,
+}
+
+#[derive(Subcommand)]
+enum StatsCommand {
+    /// Shows the latest statistics of a device.
+    Show { device_id: DeviceId },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: &Cli) -> Result<(), UnifiError> {
+    let client = connect(cli)?;
+    match &cli.command {
+        Command::Sites(SitesCommand::List) => {
+            let sites = client.list_all_sites().await?;
+            print(cli.output, &sites, || {
+                table(
+                    &["ID", "NAME"],
+                    sites
+                        .iter()
+                        .map(|site| vec![site.id.to_string(), optional(&site.name)]),
+                )
+            })
+        }
+        Command::Devices(DevicesCommand::List) => {
+            let devices = client.list_all_devices(site(cli, &client).await?).await?;
+            print(cli.output, &devices, || {
+                table(
+                    &["ID", "NAME", "MODEL", "IP", "STATE"],
+                    devices.iter().map(|device| {
+                        vec![
+                            device.id.to_string(),
+                            device.name.clone(),
+                            device.model.clone(),
+                            optional(&device.ip_address),
+                            format!("{:?}", device.state),
+                        ]
+                    }),
+                )
+            })
+        }
+        Command::Devices(DevicesCommand::Show { device_id }) => {
+            let site_id = site(cli, &client).await?;
+            let device = client.get_device_details(site_id, *device_id).await?;
+            print(cli.output, &device, || {
+                fields([
+                    ("ID", device.id.to_string()),
+                    ("Name", device.name.clone()),
+                    ("Model", device.model.clone()),
+                    ("MAC", device.mac_address.clone()),
+                    ("IP", optional(&device.ip_address)),
+                    ("State", format!("{:?}", device.state)),
+                    ("Firmware", device.firmware_version.clone()),
+                    ("Updatable", device.firmware_updatable.to_string()),
+                    ("Adopted", optional(&device.adopted_at)),
+                ])
+            })
+        }
+        Command::Devices(DevicesCommand::Restart { device_id }) => {
+            let site_id = site(cli, &client).await?;
+            let ack = client.restart_device(site_id, *device_id).await?;
+            let status = if ack.completed {
+                "restarted"
+            } else {
+                "restart queued"
+            };
+            let result = serde_json::json!({
+                "deviceId": device_id,
+                "statusCode": ack.status_code,
+                "completed": ack.completed,
+            });
+            print(cli.output, &result, || {
+                format!("{}: {}\n", device_id, status)
+            })
+        }
+        Command::Clients(ClientsCommand::List) => {
+            let clients = client.list_all_clients(site(cli, &client).await?).await?;
+            print(cli.output, &clients, || {
+                table(
+                    &["ID", "NAME", "TYPE", "IP", "MAC", "CONNECTED"],
+                    clients.iter().map(|client| {
+                        let base = client.base();
+                        vec![
+                            base.id.to_string(),
+                            optional(&base.name),
+                            client_type(client).to_string(),
+                            optional(&base.ip_address),
+                            client.mac_address().unwrap_or("-").to_string(),
+                            base.connected_at.to_rfc3339(),
+                        ]
+                    }),
+                )
+            })
+        }
+        Command::Stats(StatsCommand::Show { device_id }) => {
+            let site_id = site(cli, &client).await?;
+            let stats = client.get_device_statistics(site_id, *device_id).await?;
+            print(cli.output, &stats, || {
+                fields([
+                    ("Uptime", format!("{}s", stats.uptime_sec)),
+                    ("Last heartbeat", stats.last_heartbeat_at.to_rfc3339()),
+                    ("CPU", percentage(stats.cpu_utilization_pct)),
+                    ("Memory", percentage(stats.memory_utilization_pct)),
+                    ("Load (1m)", optional(&stats.load_average_1min)),
+                    ("Load (5m)", optional(&stats.load_average_5min)),
+                    ("Load (15m)", optional(&stats.load_average_15min)),
+                    (
+                        "Uplink tx/rx",
+                        stats
+                            .uplink
+                            .as_ref()
+                            .map(|uplink| {
+                                format!("{} / {} bps", uplink.tx_rate_bps, uplink.rx_rate_bps)
+                            })
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                ])
+            })
+        }
+    }
+}
+
+/// Builds the client from the command-line options or the config file.
+fn connect(cli: &Cli) -> Result<UnifiClient, UnifiError> {
+    let builder = match (&cli.config, &cli.url) {
+        (Some(path), None) => {
+            let config = UnifiConfig::load(path)?;
+            let name = match &cli.controller {
+                Some(name) => name.as_str(),
+                None => config.default_controller().ok_or_else(|| {
+                    UnifiError::Config("pass --controller to choose a controller".to_string())
+                })?,
+            };
+            config.builder(name)?
+        }
+        (_, Some(url)) => {
+            let api_key = cli.api_key.as_deref().ok_or_else(|| {
+                UnifiError::Config("pass --api-key or set UNIFI_API_KEY".to_string())
+            })?;
+            UnifiClientBuilder::new(url).api_key(api_key)
+        }
+        (None, None) => {
+            return Err(UnifiError::Config(
+                "pass --url or --config, or set UNIFI_BASE_URL or UNIFI_CONFIG".to_string(),
+            ))
+        }
+    };
+
+    let builder = builder.user_agent(concat!("unifi-cli/", env!("CARGO_PKG_VERSION")));
+    let builder = if cli.insecure {
+        builder.verify_ssl(false)
+    } else {
+        builder
+    };
+    builder.build()
+}
+
+/// Returns the site given with `--site`, or the controller's default site.
+async fn site(cli: &Cli, client: &UnifiClient) -> Result<SiteId, UnifiError> {
+    match cli.site {
+        Some(site_id) => Ok(site_id),
+        None => client.default_site_id().await,
+    }
+}
+
+/// Prints `value` as JSON, or the output of `render` as a table.
+fn print<T: Serialize>(
+    output: Output,
+    value: &T,
+    render: impl FnOnce() -> String,
+) -> Result<(), UnifiError> {
+    let text = match output {
+        Output::Json => {
+            let json = serde_json::to_string_pretty(value)
+                .map_err(|e| UnifiError::Config(format!("cannot encode output: {}", e)))?;
+            json + "\n"
+        }
+        Output::Table => render(),
+    };
+    // A closed pipe, e.g. into `head`, is not an error.
+    match std::io::stdout().write_all(text.as_bytes()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+            Err(UnifiError::Config(format!("cannot write output: {}", e)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Renders rows as columns aligned under `headers`.
+fn table(headers: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let rows: Vec<Vec<String>> = std::iter::once(headers.iter().map(|h| h.to_string()).collect())
+        .chain(rows)
+        .collect();
+    let mut widths = vec![0; headers.len()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders labelled values, one per line.
+fn fields<const N: usize>(fields: [(&str, String); N]) -> String {
+    let width = fields
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    fields
+        .iter()
+        .map(|(label, value)| format!("{:<width$}  {}\n", label, value, width = width))
+        .collect()
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| "-".to_string(), ToString::to_string)
+}
+
+fn percentage(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |pct| format!("{:.1}%", pct))
+}
+
+fn client_type(client: &ClientOverview) -> &'static str {
+    match client {
+        ClientOverview::Wired(_) => "wired",
+        ClientOverview::Wireless(_) => "wireless",
+        ClientOverview::Vpn(_) => "vpn",
+        ClientOverview::Teleport(_) => "teleport",
+        ClientOverview::Unknown(_) => "unknown",
+    }
+}
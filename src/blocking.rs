@@ -0,0 +1,514 @@
+//! A blocking wrapper around `UnifiClient` for scripts and build tools.
+//!
+//! Each method drives the corresponding async method to completion on a private
+//! single-threaded tokio runtime, so callers need no runtime of their own. Methods panic
+//! if called from within an async context. Only available with the `blocking` feature.
+
+use crate::capabilities::Capabilities;
+use crate::client::{self, UnifiClientBuilder};
+use crate::errors::UnifiError;
+use crate::models::client::{
+    AuthorizeGuestRequest, AuthorizeGuestResponse, ClientDetails, ClientOverview,
+};
+use crate::models::common::{
+    ActionAck, ApplicationInfo, FrequencyBand, ListParams, Page, Timestamped,
+};
+use crate::models::device::{DeviceAction, DeviceDetails, DeviceOverview};
+use crate::models::health::{ControllerHealth, HealthReport};
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use crate::models::search::SearchResults;
+use crate::models::site::SiteOverview;
+use crate::models::statistics::{DeviceStatistics, DeviceStatisticsMetrics, SiteStatistics};
+use crate::models::voucher::{Voucher, VoucherCreateRequest};
+use crate::snapshot::{Inventory, SiteSnapshot};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+impl UnifiClientBuilder {
+    /// Builds a blocking client instead of an async one.
+    pub fn build_blocking(self) -> Result<UnifiClient, UnifiError> {
+        UnifiClient::from_async(self.build()?)
+    }
+}
+
+/// A blocking client for the UniFi Network API, mirroring `crate::UnifiClient`.
+#[derive(Clone)]
+pub struct UnifiClient {
+    inner: client::UnifiClient,
+    runtime: Arc<Runtime>,
+}
+
+/// A firmware upgrade started by `UnifiClient::upgrade_device_firmware`.
+pub struct FirmwareUpgrade<'a> {
+    inner: client::FirmwareUpgrade<'a>,
+    runtime: &'a Runtime,
+}
+
+impl FirmwareUpgrade<'_> {
+    /// Returns the UUID of the device being upgraded.
+    pub fn device_id(&self) -> DeviceId {
+        self.inner.device_id()
+    }
+
+    /// Returns how the controller acknowledged the upgrade request.
+    pub fn ack(&self) -> &ActionAck {
+        self.inner.ack()
+    }
+
+    /// Blocking version of `FirmwareUpgrade::poll`.
+    pub fn poll(&self) -> Result<Option<bool>, UnifiError> {
+        self.runtime.block_on(self.inner.poll())
+    }
+
+    /// Blocking version of `FirmwareUpgrade::wait`.
+    pub fn wait(&self, timeout: Duration, interval: Duration) -> Result<bool, UnifiError> {
+        self.runtime.block_on(self.inner.wait(timeout, interval))
+    }
+}
+
+impl UnifiClient {
+    /// Wraps an async client, starting the runtime that drives it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the blocking client, or `UnifiError::Config` if the runtime
+    /// could not be started.
+    pub fn from_async(inner: client::UnifiClient) -> Result<Self, UnifiError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| UnifiError::Config(format!("cannot start runtime: {}", e)))?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Returns the async client this wraps.
+    pub fn as_async(&self) -> &client::UnifiClient {
+        &self.inner
+    }
+
+    /// Blocking version of `UnifiClient::upgrade_device_firmware`.
+    pub fn upgrade_device_firmware(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<FirmwareUpgrade<'_>, UnifiError> {
+        let inner = self
+            .runtime
+            .block_on(self.inner.upgrade_device_firmware(site_id, device_id))?;
+        Ok(FirmwareUpgrade {
+            inner,
+            runtime: &self.runtime,
+        })
+    }
+
+    /// Blocking version of `UnifiClient::upgrade_device_firmware_to`.
+    pub fn upgrade_device_firmware_to(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        firmware_version: &str,
+    ) -> Result<FirmwareUpgrade<'_>, UnifiError> {
+        let inner = self
+            .runtime
+            .block_on(self.inner.upgrade_device_firmware_to(
+                site_id,
+                device_id,
+                firmware_version,
+            ))?;
+        Ok(FirmwareUpgrade {
+            inner,
+            runtime: &self.runtime,
+        })
+    }
+
+    /// Blocking version of `UnifiClient::capabilities`.
+    pub fn capabilities(&self) -> Result<Capabilities, UnifiError> {
+        self.runtime.block_on(self.inner.capabilities())
+    }
+
+    /// Blocking version of `UnifiClient::list_sites`.
+    pub fn list_sites(&self, params: &ListParams) -> Result<Page<SiteOverview>, UnifiError> {
+        self.runtime.block_on(self.inner.list_sites(params))
+    }
+
+    /// Blocking version of `UnifiClient::default_site_id`.
+    pub fn default_site_id(&self) -> Result<SiteId, UnifiError> {
+        self.runtime.block_on(self.inner.default_site_id())
+    }
+
+    /// Blocking version of `UnifiClient::sites_cached`.
+    pub fn sites_cached(&self) -> Result<Vec<SiteOverview>, UnifiError> {
+        self.runtime.block_on(self.inner.sites_cached())
+    }
+
+    /// Blocking version of `UnifiClient::refresh_sites`.
+    pub fn refresh_sites(&self) -> Result<Vec<SiteOverview>, UnifiError> {
+        self.runtime.block_on(self.inner.refresh_sites())
+    }
+
+    /// Blocking version of `UnifiClient::list_devices`.
+    pub fn list_devices(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<DeviceOverview>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.list_devices(site_id, params))
+    }
+
+    /// Blocking version of `UnifiClient::model_counts`.
+    pub fn model_counts(&self, site_id: SiteId) -> Result<HashMap<String, usize>, UnifiError> {
+        self.runtime.block_on(self.inner.model_counts(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::get_device_details`.
+    pub fn get_device_details(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceDetails, UnifiError> {
+        self.runtime
+            .block_on(self.inner.get_device_details(site_id, device_id))
+    }
+
+    /// Blocking version of `UnifiClient::get_device_statistics`.
+    pub fn get_device_statistics(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceStatistics, UnifiError> {
+        self.runtime
+            .block_on(self.inner.get_device_statistics(site_id, device_id))
+    }
+
+    /// Blocking version of `UnifiClient::get_device_statistics_timestamped`.
+    pub fn get_device_statistics_timestamped(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<Timestamped<DeviceStatistics>, UnifiError> {
+        self.runtime.block_on(
+            self.inner
+                .get_device_statistics_timestamped(site_id, device_id),
+        )
+    }
+
+    /// Blocking version of `UnifiClient::get_device_statistics_metrics`.
+    pub fn get_device_statistics_metrics(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<DeviceStatisticsMetrics, UnifiError> {
+        self.runtime
+            .block_on(self.inner.get_device_statistics_metrics(site_id, device_id))
+    }
+
+    /// Blocking version of `UnifiClient::get_site_statistics`.
+    pub fn get_site_statistics(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<DeviceId, DeviceStatistics>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.get_site_statistics(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::site_statistics`.
+    pub fn site_statistics(&self, site_id: SiteId) -> Result<SiteStatistics, UnifiError> {
+        self.runtime.block_on(self.inner.site_statistics(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::execute_device_action`.
+    pub fn execute_device_action(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        action: DeviceAction,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.execute_device_action(site_id, device_id, action))
+    }
+
+    /// Blocking version of `UnifiClient::restart_device`.
+    pub fn restart_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.restart_device(site_id, device_id))
+    }
+
+    /// Blocking version of `UnifiClient::list_pending_devices`.
+    pub fn list_pending_devices(&self, site_id: SiteId) -> Result<Vec<DeviceOverview>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.list_pending_devices(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::adopt_device`.
+    pub fn adopt_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.adopt_device(site_id, device_id))
+    }
+
+    /// Blocking version of `UnifiClient::start_locating_device`.
+    pub fn start_locating_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.start_locating_device(site_id, device_id))
+    }
+
+    /// Blocking version of `UnifiClient::stop_locating_device`.
+    pub fn stop_locating_device(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.stop_locating_device(site_id, device_id))
+    }
+
+    /// Blocking version of `UnifiClient::power_cycle_port`.
+    pub fn power_cycle_port(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        port_idx: i32,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.power_cycle_port(site_id, device_id, port_idx))
+    }
+
+    /// Blocking version of `UnifiClient::wait_for_updates_complete`.
+    pub fn wait_for_updates_complete(
+        &self,
+        site_id: SiteId,
+        device_ids: &[DeviceId],
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<Vec<(DeviceId, bool)>, UnifiError> {
+        self.runtime.block_on(
+            self.inner
+                .wait_for_updates_complete(site_id, device_ids, timeout, interval),
+        )
+    }
+
+    /// Blocking version of `UnifiClient::devices_with_uplink_names`.
+    pub fn devices_with_uplink_names(
+        &self,
+        site_id: SiteId,
+    ) -> Result<Vec<(DeviceOverview, Option<String>)>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.devices_with_uplink_names(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::set_device_name`.
+    pub fn set_device_name(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        name: &str,
+    ) -> Result<(), UnifiError> {
+        self.runtime
+            .block_on(self.inner.set_device_name(site_id, device_id, name))
+    }
+
+    /// Blocking version of `UnifiClient::get_info`.
+    pub fn get_info(&self) -> Result<ApplicationInfo, UnifiError> {
+        self.runtime.block_on(self.inner.get_info())
+    }
+
+    /// Blocking version of `UnifiClient::wireless_band_distribution`.
+    pub fn wireless_band_distribution(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<FrequencyBand, usize>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.wireless_band_distribution(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::health_check`.
+    pub fn health_check(&self) -> HealthReport {
+        self.runtime.block_on(self.inner.health_check())
+    }
+
+    /// Blocking version of `UnifiClient::get_controller_health`.
+    pub fn get_controller_health(&self) -> Result<ControllerHealth, UnifiError> {
+        self.runtime.block_on(self.inner.get_controller_health())
+    }
+
+    /// Blocking version of `UnifiClient::clients_per_wlan`.
+    pub fn clients_per_wlan(&self, site_id: SiteId) -> Result<HashMap<Uuid, usize>, UnifiError> {
+        self.runtime.block_on(self.inner.clients_per_wlan(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::get_with_accept`.
+    pub fn get_with_accept(&self, path: &str, accept: &str) -> Result<Bytes, UnifiError> {
+        self.runtime
+            .block_on(self.inner.get_with_accept(path, accept))
+    }
+
+    /// Blocking version of `UnifiClient::list_clients`.
+    pub fn list_clients(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<ClientOverview>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.list_clients(site_id, params))
+    }
+
+    /// Blocking version of `UnifiClient::get_client_details`.
+    pub fn get_client_details(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ClientDetails, UnifiError> {
+        self.runtime
+            .block_on(self.inner.get_client_details(site_id, client_id))
+    }
+
+    /// Blocking version of `UnifiClient::block_client`.
+    pub fn block_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.block_client(site_id, client_id))
+    }
+
+    /// Blocking version of `UnifiClient::unblock_client`.
+    pub fn unblock_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.unblock_client(site_id, client_id))
+    }
+
+    /// Blocking version of `UnifiClient::reconnect_client`.
+    pub fn reconnect_client(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+    ) -> Result<ActionAck, UnifiError> {
+        self.runtime
+            .block_on(self.inner.reconnect_client(site_id, client_id))
+    }
+
+    /// Blocking version of `UnifiClient::authorize_guest_access`.
+    pub fn authorize_guest_access(
+        &self,
+        site_id: SiteId,
+        client_id: ClientId,
+        request: &AuthorizeGuestRequest,
+    ) -> Result<AuthorizeGuestResponse, UnifiError> {
+        self.runtime.block_on(
+            self.inner
+                .authorize_guest_access(site_id, client_id, request),
+        )
+    }
+
+    /// Blocking version of `UnifiClient::list_vouchers`.
+    pub fn list_vouchers(
+        &self,
+        site_id: SiteId,
+        params: &ListParams,
+    ) -> Result<Page<Voucher>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.list_vouchers(site_id, params))
+    }
+
+    /// Blocking version of `UnifiClient::get_voucher_details`.
+    pub fn get_voucher_details(
+        &self,
+        site_id: SiteId,
+        voucher_id: Uuid,
+    ) -> Result<Voucher, UnifiError> {
+        self.runtime
+            .block_on(self.inner.get_voucher_details(site_id, voucher_id))
+    }
+
+    /// Blocking version of `UnifiClient::generate_vouchers`.
+    pub fn generate_vouchers(
+        &self,
+        site_id: SiteId,
+        request: &VoucherCreateRequest,
+    ) -> Result<Vec<Voucher>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.generate_vouchers(site_id, request))
+    }
+
+    /// Blocking version of `UnifiClient::delete_voucher`.
+    pub fn delete_voucher(&self, site_id: SiteId, voucher_id: Uuid) -> Result<(), UnifiError> {
+        self.runtime
+            .block_on(self.inner.delete_voucher(site_id, voucher_id))
+    }
+
+    /// Blocking version of `UnifiClient::list_recent_clients`.
+    pub fn list_recent_clients(
+        &self,
+        site_id: SiteId,
+        within: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ClientOverview>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.list_recent_clients(site_id, within, now))
+    }
+
+    /// Blocking version of `UnifiClient::find_duplicate_ips`.
+    pub fn find_duplicate_ips(
+        &self,
+        site_id: SiteId,
+    ) -> Result<HashMap<IpAddr, Vec<Uuid>>, UnifiError> {
+        self.runtime
+            .block_on(self.inner.find_duplicate_ips(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::list_all_sites`.
+    pub fn list_all_sites(&self) -> Result<Vec<SiteOverview>, UnifiError> {
+        self.runtime.block_on(self.inner.list_all_sites())
+    }
+
+    /// Blocking version of `UnifiClient::list_all_devices`.
+    pub fn list_all_devices(&self, site_id: SiteId) -> Result<Vec<DeviceOverview>, UnifiError> {
+        self.runtime.block_on(self.inner.list_all_devices(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::list_all_clients`.
+    pub fn list_all_clients(&self, site_id: SiteId) -> Result<Vec<ClientOverview>, UnifiError> {
+        self.runtime.block_on(self.inner.list_all_clients(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::site_snapshot`.
+    pub fn site_snapshot(&self, site_id: SiteId) -> Result<SiteSnapshot, UnifiError> {
+        self.runtime.block_on(self.inner.site_snapshot(site_id))
+    }
+
+    /// Blocking version of `UnifiClient::snapshot`.
+    pub fn snapshot(&self) -> Result<Inventory, UnifiError> {
+        self.runtime.block_on(self.inner.snapshot())
+    }
+
+    /// Blocking version of `UnifiClient::search`.
+    pub fn search(&self, query: &str) -> Result<SearchResults, UnifiError> {
+        self.runtime.block_on(self.inner.search(query))
+    }
+}
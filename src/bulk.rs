@@ -0,0 +1,159 @@
+//! Running an operation on many items with bounded concurrency.
+//!
+//! `for_each_device` and `for_each_client` list a site and run an operation on each device
+//! or client, at most `concurrency` at a time. A failing item does not stop the others; its
+//! error is collected in the returned `BulkReport`.
+
+use crate::client::UnifiClient;
+use crate::errors::UnifiError;
+use crate::models::client::ClientOverview;
+use crate::models::device::DeviceOverview;
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use futures::stream::{self, StreamExt};
+use std::future::Future;
+
+/// The per-item outcomes of a bulk operation, in completion order.
+#[derive(Debug)]
+pub struct BulkReport<K, T> {
+    /// The items the operation succeeded on, with its output.
+    pub succeeded: Vec<(K, T)>,
+    /// The items the operation failed on, with its error.
+    pub failed: Vec<(K, UnifiError)>,
+}
+
+impl<K, T> BulkReport<K, T> {
+    /// Returns whether the operation succeeded on every item.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Returns the number of items the operation ran on.
+    pub fn len(&self) -> usize {
+        self.succeeded.len() + self.failed.len()
+    }
+
+    /// Returns whether there were no items.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, T> Default for BulkReport<K, T> {
+    fn default() -> Self {
+        Self {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+/// Runs `op` on every item, at most `concurrency` at a time.
+///
+/// # Arguments
+///
+/// * `items` - The items to run the operation on; each is also its key in the report.
+/// * `concurrency` - The maximum number of operations in flight; 0 is treated as 1.
+/// * `op` - The operation to run on each item.
+///
+/// # Returns
+///
+/// A `BulkReport` of every item's outcome.
+pub async fn for_each<I, K, T, F, Fut>(items: I, concurrency: usize, op: F) -> BulkReport<K, T>
+where
+    I: IntoIterator<Item = K>,
+    K: Clone,
+    F: Fn(K) -> Fut,
+    Fut: Future<Output = Result<T, UnifiError>>,
+{
+    let outcomes: Vec<(K, Result<T, UnifiError>)> = stream::iter(items)
+        .map(|item| {
+            let result = op(item.clone());
+            async move { (item, result.await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut report = BulkReport::default();
+    for (item, result) in outcomes {
+        match result {
+            Ok(output) => report.succeeded.push((item, output)),
+            Err(error) => report.failed.push((item, error)),
+        }
+    }
+    report
+}
+
+/// Lists every device in a site and runs `op` on each, at most `concurrency` at a time.
+///
+/// # Arguments
+///
+/// * `client` - The client to list the devices with.
+/// * `site_id` - The id of the site whose devices to run the operation on.
+/// * `concurrency` - The maximum number of operations in flight; 0 is treated as 1.
+/// * `op` - The operation to run on each device.
+///
+/// # Returns
+///
+/// A `Result` containing a `BulkReport` keyed by device id, or a `UnifiError` if the
+/// devices could not be listed.
+pub async fn for_each_device<T, F, Fut>(
+    client: &UnifiClient,
+    site_id: SiteId,
+    concurrency: usize,
+    op: F,
+) -> Result<BulkReport<DeviceId, T>, UnifiError>
+where
+    F: Fn(DeviceOverview) -> Fut,
+    Fut: Future<Output = Result<T, UnifiError>>,
+{
+    let devices = client.list_all_devices(site_id).await?;
+    Ok(keyed(for_each(devices, concurrency, op).await, |device| {
+        device.id
+    }))
+}
+
+/// Lists every client in a site and runs `op` on each, at most `concurrency` at a time.
+///
+/// # Arguments
+///
+/// * `client` - The client to list the site's clients with.
+/// * `site_id` - The id of the site whose clients to run the operation on.
+/// * `concurrency` - The maximum number of operations in flight; 0 is treated as 1.
+/// * `op` - The operation to run on each client.
+///
+/// # Returns
+///
+/// A `Result` containing a `BulkReport` keyed by client id, or a `UnifiError` if the
+/// clients could not be listed.
+pub async fn for_each_client<T, F, Fut>(
+    client: &UnifiClient,
+    site_id: SiteId,
+    concurrency: usize,
+    op: F,
+) -> Result<BulkReport<ClientId, T>, UnifiError>
+where
+    F: Fn(ClientOverview) -> Fut,
+    Fut: Future<Output = Result<T, UnifiError>>,
+{
+    let clients = client.list_all_clients(site_id).await?;
+    Ok(keyed(for_each(clients, concurrency, op).await, |client| {
+        client.base().id
+    }))
+}
+
+/// Replaces the items of a report with their ids.
+fn keyed<I, K, T>(report: BulkReport<I, T>, key: impl Fn(&I) -> K) -> BulkReport<K, T> {
+    BulkReport {
+        succeeded: report
+            .succeeded
+            .into_iter()
+            .map(|(item, output)| (key(&item), output))
+            .collect(),
+        failed: report
+            .failed
+            .into_iter()
+            .map(|(item, error)| (key(&item), error))
+            .collect(),
+    }
+}
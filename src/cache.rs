@@ -0,0 +1,273 @@
+//! An in-memory cache of `GET` responses, installed with `UnifiClientBuilder::cache`.
+//!
+//! Successful `GET` responses are kept for a time-to-live chosen per endpoint by a
+//! `CachePolicy`. Within that time, repeated requests are answered from memory. After it,
+//! a response may still be served for the policy's stale-while-revalidate window while a
+//! fresh one is fetched in the background. Responses carrying an `ETag` are kept after
+//! that and revalidated with `If-None-Match`, so that a `304 Not Modified` renews the
+//! cached body instead of transferring it again. Any other request, such as an action or
+//! an update, clears the cache so that later reads see its effect. `/v1/info` is never
+//! cached, so `health_check`, `capabilities` and `build_detected` always reach the
+//! controller. Only available with the `cache` feature.
+
+use crate::time::Instant;
+use bytes::Bytes;
+use reqwest::{header, Method, Request, Response, ResponseBuilderExt, StatusCode, Url};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The endpoints never cached, whatever the policy: the probes of controller reachability.
+pub const UNCACHED_ENDPOINTS: [&str; 1] = ["/v1/info"];
+
+/// How long responses are cached, per endpoint.
+///
+/// Every `GET` endpoint except those in `UNCACHED_ENDPOINTS` is cached. Endpoints are
+/// matched on their path relative to the base URL, where `*` matches one path segment,
+/// e.g. `/v1/sites/*/devices/*`. The first matching rule wins; other endpoints use the
+/// default time-to-live. A time-to-live of zero disables caching.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachePolicy {
+    default_ttl: Duration,
+    endpoint_ttls: Vec<(String, Duration)>,
+    stale_while_revalidate: Duration,
+    max_entries: usize,
+}
+
+impl CachePolicy {
+    /// A policy caching every endpoint for `default_ttl`, without serving stale responses.
+    pub fn new(default_ttl: Duration) -> Self {
+        Self {
+            default_ttl,
+            endpoint_ttls: Vec::new(),
+            stale_while_revalidate: Duration::ZERO,
+            max_entries: 1024,
+        }
+    }
+
+    /// Caches the endpoints matching `pattern` for `ttl` instead of the default.
+    pub fn endpoint_ttl(mut self, pattern: impl Into<String>, ttl: Duration) -> Self {
+        let pattern = pattern.into();
+        self.endpoint_ttls
+            .push((pattern.trim_end_matches('/').to_string(), ttl));
+        self
+    }
+
+    /// Serves an expired response for up to `window` longer while it is refreshed.
+    pub fn stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = window;
+        self
+    }
+
+    /// Sets the number of responses kept, evicting the oldest beyond it; the default is 1024.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Returns the time-to-live for the endpoint at `path`, relative to the base URL.
+    ///
+    /// Always zero for `UNCACHED_ENDPOINTS`.
+    pub fn ttl(&self, path: &str) -> Duration {
+        let path = path.trim_end_matches('/');
+        if UNCACHED_ENDPOINTS.contains(&path) {
+            return Duration::ZERO;
+        }
+        self.endpoint_ttls
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, path))
+            .map_or(self.default_ttl, |(_, ttl)| *ttl)
+    }
+}
+
+/// Returns whether `path` matches `pattern`, segment by segment.
+fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let (mut pattern, mut path) = (pattern.split('/'), path.split('/'));
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some("*"), Some(_)) => {}
+            (Some(expected), Some(actual)) if expected == actual => {}
+            _ => return false,
+        }
+    }
+}
+
+/// What the cache holds for a request.
+pub(crate) enum Lookup {
+    /// A response within its time-to-live.
+    Fresh(Response),
+    /// An expired response that may be served while the caller refreshes it.
+    Stale(Response),
+    /// Nothing usable; the request must be sent, with `If-None-Match` if an `ETag` is known.
+    Miss,
+}
+
+struct Entry {
+    status: StatusCode,
+    headers: header::HeaderMap,
+    body: Bytes,
+    url: Url,
+    stored_at: Instant,
+    ttl: Duration,
+    refreshing: bool,
+}
+
+impl Entry {
+    fn response(&self) -> Response {
+        let mut response = http::Response::builder()
+            .status(self.status)
+            .url(self.url.clone())
+            .body(self.body.clone())
+            .expect("a cached response is valid");
+        *response.headers_mut() = self.headers.clone();
+        let mut response = Response::from(response);
+        response.extensions_mut().insert(Method::GET);
+        response
+    }
+}
+
+/// The cache shared by a client and its clones.
+pub(crate) struct ResponseCache {
+    policy: CachePolicy,
+    base_path: String,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(policy: CachePolicy, base_url: &str) -> Self {
+        let base_path = Url::parse(base_url)
+            .map(|url| url.path().trim_end_matches('/').to_string())
+            .unwrap_or_default();
+        Self {
+            policy,
+            base_path,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the key of a cacheable request, or `None` if the request is not cacheable.
+    pub(crate) fn key(&self, request: &Request) -> Option<String> {
+        if request.method() != Method::GET || self.ttl(request.url()).is_zero() {
+            return None;
+        }
+        let accept = request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        Some(format!("{} {}", request.url(), accept))
+    }
+
+    /// Looks up a request, marking a stale entry as being refreshed so that only one
+    /// caller refreshes it.
+    pub(crate) fn lookup(&self, key: &str) -> Lookup {
+        let mut entries = self.lock();
+        let Some(entry) = entries.get_mut(key) else {
+            return Lookup::Miss;
+        };
+        let age = entry.stored_at.elapsed();
+        if age < entry.ttl {
+            Lookup::Fresh(entry.response())
+        } else if age < entry.ttl + self.policy.stale_while_revalidate {
+            if entry.refreshing {
+                Lookup::Fresh(entry.response())
+            } else {
+                entry.refreshing = true;
+                Lookup::Stale(entry.response())
+            }
+        } else {
+            // An entry with an `ETag` is kept for the caller to revalidate.
+            if !entry.headers.contains_key(header::ETAG) {
+                entries.remove(key);
+            }
+            Lookup::Miss
+        }
+    }
+
+    /// Adds `If-None-Match` to a request if a cached response to it has an `ETag`.
+    pub(crate) fn make_conditional(&self, key: &str, request: &mut Request) {
+        let etag = self
+            .lock()
+            .get(key)
+            .and_then(|entry| entry.headers.get(header::ETAG).cloned());
+        if let Some(etag) = etag {
+            request.headers_mut().insert(header::IF_NONE_MATCH, etag);
+        }
+    }
+
+    /// Stores a response if it is successful, returning an equivalent response.
+    ///
+    /// A `304 Not Modified` renews the cached response and returns it instead.
+    pub(crate) async fn store(
+        &self,
+        key: String,
+        response: Response,
+    ) -> Result<Response, reqwest::Error> {
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = self.lock().get_mut(&key) {
+                entry.stored_at = Instant::now();
+                entry.refreshing = false;
+                return Ok(entry.response());
+            }
+        }
+        if !response.status().is_success() {
+            self.finish_refresh(&key);
+            return Ok(response);
+        }
+
+        let mut headers = response.headers().clone();
+        // The body is buffered, so its original framing no longer applies.
+        headers.remove(header::CONTENT_LENGTH);
+        headers.remove(header::TRANSFER_ENCODING);
+        let entry = Entry {
+            status: response.status(),
+            headers,
+            url: response.url().clone(),
+            ttl: self.ttl(response.url()),
+            body: response.bytes().await?,
+            stored_at: Instant::now(),
+            refreshing: false,
+        };
+        let rebuilt = entry.response();
+
+        let mut entries = self.lock();
+        if entries.len() >= self.policy.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        if self.policy.max_entries > 0 {
+            entries.insert(key, entry);
+        }
+        Ok(rebuilt)
+    }
+
+    /// Allows a stale entry whose refresh failed to be refreshed again.
+    pub(crate) fn finish_refresh(&self, key: &str) {
+        if let Some(entry) = self.lock().get_mut(key) {
+            entry.refreshing = false;
+        }
+    }
+
+    /// Removes every cached response.
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn ttl(&self, url: &Url) -> Duration {
+        let path = url.path();
+        self.policy
+            .ttl(path.strip_prefix(&self.base_path).unwrap_or(path))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
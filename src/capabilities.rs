@@ -0,0 +1,68 @@
+use crate::models::common::ApplicationVersion;
+
+/// An optional part of the UniFi Network API, available from a minimum application version.
+///
+/// With `UnifiClientBuilder::require_version` enabled, the methods listed for a feature
+/// return `UnifiError::Config` on controllers older than its `min_version`, exactly when
+/// `Capabilities::supports` returns `false` for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// Per-device statistics: `UnifiClient::get_device_statistics` and its variants.
+    DeviceStatistics,
+    /// Hotspot vouchers: `UnifiClient::list_vouchers`, `get_voucher_details`,
+    /// `generate_vouchers` and `delete_voucher`.
+    Vouchers,
+    /// Port actions: `UnifiClient::power_cycle_port`.
+    PortActions,
+    /// Adopting pending devices: `UnifiClient::list_pending_devices` and `adopt_device`.
+    DeviceAdoption,
+}
+
+impl Feature {
+    /// Returns the earliest Network application version known to serve the feature.
+    pub const fn min_version(self) -> ApplicationVersion {
+        match self {
+            Feature::DeviceStatistics => ApplicationVersion::new(9, 0, 0),
+            Feature::Vouchers | Feature::PortActions => ApplicationVersion::new(9, 1, 0),
+            Feature::DeviceAdoption => ApplicationVersion::new(9, 3, 0),
+        }
+    }
+}
+
+impl std::fmt::Display for Feature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Feature::DeviceStatistics => "device statistics",
+            Feature::Vouchers => "vouchers",
+            Feature::PortActions => "port actions",
+            Feature::DeviceAdoption => "device adoption",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The features served by a controller, derived from its Network application version.
+///
+/// Obtained from `UnifiClient::capabilities`, so callers can skip or degrade calls the
+/// controller cannot serve instead of handling the resulting errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    version: ApplicationVersion,
+}
+
+impl Capabilities {
+    pub const fn new(version: ApplicationVersion) -> Self {
+        Self { version }
+    }
+
+    /// Returns the controller's Network application version.
+    pub const fn version(&self) -> ApplicationVersion {
+        self.version
+    }
+
+    /// Returns whether the controller is recent enough to serve `feature`.
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.version >= feature.min_version()
+    }
+}
@@ -0,0 +1,249 @@
+//! Recording and replaying controller responses ("cassettes") for deterministic tests.
+//!
+//! In record mode a `Cassette` captures every response the client receives, together
+//! with the method, path and query of its request, and writes them to a JSON file. Paths
+//! are stored relative to the client's base URL, so a cassette recorded through a prefix
+//! such as `/proxy/network/integrations` replays against any base URL. In
+//! replay mode requests are answered from that file without any network access, so tests
+//! recorded once against real hardware can run anywhere. Headers, including the API key,
+//! are never recorded. Only available with the `test-util` feature.
+
+use crate::errors::UnifiError;
+use bytes::Bytes;
+use reqwest::{header, Request, Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The environment variable read by `Cassette::from_env`.
+pub const CASSETTE_ENV: &str = "UNIFI_CASSETTE";
+
+/// Whether a `Cassette` captures real responses or serves recorded ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// A single recorded request and its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interaction {
+    pub method: String,
+    /// The request path relative to the client's base URL, e.g. `/v1/sites`.
+    pub path: String,
+    #[serde(default)]
+    pub query: Option<String>,
+    pub status: u16,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+/// A file of recorded interactions, installed with `UnifiClientBuilder::cassette`.
+///
+/// A recording cassette writes its file when `save` is called and again when it is
+/// dropped. A replaying cassette serves each recorded interaction once, matching on
+/// method, path and query in recording order; a request with no remaining match fails
+/// with `UnifiError::Config`.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl Cassette {
+    /// Creates an empty cassette that records to `path`, replacing any existing file.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Loads the cassette at `path` for replay.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Cassette`, or `UnifiError::Config` if the file cannot
+    /// be read or parsed.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, UnifiError> {
+        let path = path.into();
+        let contents = std::fs::read(&path).map_err(|e| {
+            UnifiError::Config(format!("cannot read cassette {}: {}", path.display(), e))
+        })?;
+        let interactions: Vec<Interaction> = serde_json::from_slice(&contents).map_err(|e| {
+            UnifiError::Config(format!("invalid cassette {}: {}", path.display(), e))
+        })?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            interactions: Mutex::new(interactions.into_iter().map(|i| (i, false)).collect()),
+        })
+    }
+
+    /// Opens a cassette in the mode named by the `UNIFI_CASSETTE` environment variable.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if the variable is unset, a recording or replaying `Cassette` if it is
+    /// `record` or `replay`, or `UnifiError::Config` for any other value or if the
+    /// cassette to replay cannot be loaded.
+    pub fn from_env(path: impl Into<PathBuf>) -> Result<Option<Self>, UnifiError> {
+        match std::env::var(CASSETTE_ENV).as_deref() {
+            Err(_) => Ok(None),
+            Ok("record") => Ok(Some(Self::record(path))),
+            Ok("replay") => Self::replay(path).map(Some),
+            Ok(other) => Err(UnifiError::Config(format!(
+                "{} must be `record` or `replay`, not `{}`",
+                CASSETTE_ENV, other
+            ))),
+        }
+    }
+
+    /// Returns whether the cassette is recording or replaying.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Returns the file the cassette is read from or written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a copy of the interactions recorded or loaded so far.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().iter().map(|(i, _)| i.clone()).collect()
+    }
+
+    /// Writes the recorded interactions to the cassette file, creating parent directories.
+    ///
+    /// Does nothing for a replaying cassette.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or `UnifiError::Config` if the file cannot be written.
+    pub fn save(&self) -> Result<(), UnifiError> {
+        if self.mode == CassetteMode::Replay {
+            return Ok(());
+        }
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let json = serde_json::to_vec_pretty(&self.interactions())?;
+            std::fs::write(&self.path, json)
+        };
+        write().map_err(|e| {
+            UnifiError::Config(format!(
+                "cannot write cassette {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Answers `request`, sent by a client with base URL `base_url`, from the recorded
+    /// interactions.
+    pub(crate) fn play(&self, base_url: &str, request: &Request) -> Result<Response, UnifiError> {
+        let path = relative_path(base_url, request.url());
+        let mut interactions = self.lock();
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(i, used)| {
+                !used
+                    && i.method == request.method().as_str()
+                    && i.path == path
+                    && i.query.as_deref() == request.url().query()
+            })
+            .ok_or_else(|| {
+                UnifiError::Config(format!(
+                    "no recorded interaction in {} for {} {}",
+                    self.path.display(),
+                    request.method(),
+                    request.url()
+                ))
+            })?;
+        *used = true;
+
+        let mut response = http::Response::builder()
+            .status(interaction.status)
+            .url(request.url().clone());
+        if let Some(content_type) = &interaction.content_type {
+            response = response.header(header::CONTENT_TYPE, content_type);
+        }
+        let response = response
+            .body(interaction.body.clone())
+            .map_err(|e| UnifiError::Config(format!("invalid recorded response: {}", e)))?;
+        Ok(Response::from(response))
+    }
+
+    /// Records `response` as the answer to a request sent by a client with base URL
+    /// `base_url`, returning an equivalent response.
+    pub(crate) async fn capture(
+        &self,
+        base_url: &str,
+        method: &reqwest::Method,
+        url: &reqwest::Url,
+        response: Response,
+    ) -> Result<Response, UnifiError> {
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut headers = response.headers().clone();
+        let body: Bytes = response.bytes().await?;
+
+        self.lock().push((
+            Interaction {
+                method: method.to_string(),
+                path: relative_path(base_url, url),
+                query: url.query().map(str::to_string),
+                status: status.as_u16(),
+                content_type,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            },
+            true,
+        ));
+
+        // The body has been buffered, so its original framing no longer applies.
+        headers.remove(header::CONTENT_LENGTH);
+        headers.remove(header::TRANSFER_ENCODING);
+        let mut rebuilt = http::Response::builder()
+            .status(status)
+            .url(url.clone())
+            .body(body)
+            .expect("a captured response is valid");
+        *rebuilt.headers_mut() = headers;
+        Ok(Response::from(rebuilt))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Interaction, bool)>> {
+        self.interactions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        // Errors cannot be reported from here; call `save` to observe them.
+        let _ = self.save();
+    }
+}
+
+/// Returns the path of `url` with the path of `base_url` stripped from its start.
+fn relative_path(base_url: &str, url: &reqwest::Url) -> String {
+    let base_path = reqwest::Url::parse(base_url)
+        .map(|base| base.path().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    match url.path().strip_prefix(&base_path) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.to_string(),
+        _ => url.path().to_string(),
+    }
+}
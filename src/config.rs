@@ -0,0 +1,237 @@
+//! Loading client settings for one or more controllers from a TOML file.
+//!
+//! Each controller is a table under `controllers`, keyed by the name used to look it up:
+//!
+//! ```toml
+//! default = "home"
+//!
+//! [controllers.home]
+//! base_url = "https://192.168.1.1/proxy/network/integrations"
+//! api_key_env = "UNIFI_HOME_API_KEY"
+//! verify_ssl = false
+//!
+//! [controllers.office]
+//! base_url = "https://unifi.example.com/proxy/network/integrations"
+//! api_key = "..."
+//! request_timeout_secs = 30
+//! headers = { "X-Proxy-Auth" = "..." }
+//! ```
+//!
+//! `UnifiConfig::client` then builds a ready `UnifiClient`:
+//!
+//! ```rust,no_run
+//! # fn example() -> Result<(), unifi_rs::UnifiError> {
+//! let client = unifi_rs::config::UnifiConfig::load("unifi.toml")?.client("home")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only available with the `config` feature.
+
+use crate::client::{UnifiClient, UnifiClientBuilder};
+use crate::errors::UnifiError;
+use crate::fleet::FleetClient;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The settings of every configured controller.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnifiConfig {
+    /// The controller returned by `UnifiConfig::default_client`.
+    #[serde(default)]
+    pub default: Option<String>,
+    /// The controllers, by name.
+    #[serde(default)]
+    pub controllers: BTreeMap<String, ControllerConfig>,
+    /// The directory relative file paths are resolved against.
+    #[serde(skip)]
+    base_dir: Option<PathBuf>,
+}
+
+/// The settings of one controller.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ControllerConfig {
+    /// The base URL of the controller's integration API.
+    pub base_url: String,
+    /// The API key.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// The environment variable to read the API key from, if `api_key` is not set.
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    /// Whether to verify the controller's TLS certificate; defaults to `true`.
+    #[serde(default = "default_verify_ssl")]
+    pub verify_ssl: bool,
+    /// A PEM file holding a client certificate and its private key, for mutual TLS.
+    #[serde(default)]
+    pub identity_pem: Option<PathBuf>,
+    /// The proxy to send requests through.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Hosts to reach without the proxy.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// The connect timeout, in seconds.
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// The timeout of a whole request, in seconds.
+    #[serde(default)]
+    pub request_timeout_secs: Option<u64>,
+    /// The `User-Agent` to send.
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Headers to send with every request, e.g. for a reverse proxy.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+fn default_verify_ssl() -> bool {
+    true
+}
+
+impl UnifiConfig {
+    /// Loads the configuration file at `path`.
+    ///
+    /// Relative `identity_pem` paths are resolved against the directory of the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `UnifiConfig`, or `UnifiError::Config` if the file cannot
+    /// be read or parsed.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, UnifiError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            UnifiError::Config(format!("cannot read config {}: {}", path.display(), e))
+        })?;
+        let mut config: Self = contents.parse().map_err(|e| match e {
+            UnifiError::Config(message) => {
+                UnifiError::Config(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })?;
+        config.base_dir = path.parent().map(Path::to_path_buf);
+        Ok(config)
+    }
+
+    /// Returns the settings of the controller named `name`.
+    pub fn controller(&self, name: &str) -> Option<&ControllerConfig> {
+        self.controllers.get(name)
+    }
+
+    /// Builds a client for the controller named `name`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `UnifiClient`, or `UnifiError::Config` if there is no
+    /// such controller or its settings are invalid.
+    pub fn client(&self, name: &str) -> Result<UnifiClient, UnifiError> {
+        self.builder(name)?.build()
+    }
+
+    /// Returns a builder configured for the controller named `name`, for adding settings
+    /// the file does not cover.
+    pub fn builder(&self, name: &str) -> Result<UnifiClientBuilder, UnifiError> {
+        let controller = self.controller(name).ok_or_else(|| {
+            UnifiError::Config(format!("no controller named `{}` is configured", name))
+        })?;
+        controller
+            .builder(self.base_dir.as_deref())
+            .map_err(|e| match e {
+                UnifiError::Config(message) => {
+                    UnifiError::Config(format!("controller `{}`: {}", name, message))
+                }
+                other => other,
+            })
+    }
+
+    /// Returns the name of the default controller: the one named by `default`, or the only
+    /// controller if there is just one.
+    pub fn default_controller(&self) -> Option<&str> {
+        match (&self.default, self.controllers.len()) {
+            (Some(name), _) => Some(name),
+            (None, 1) => self.controllers.keys().next().map(String::as_str),
+            (None, _) => None,
+        }
+    }
+
+    /// Builds a client for the default controller, see `UnifiConfig::default_controller`.
+    pub fn default_client(&self) -> Result<UnifiClient, UnifiError> {
+        let name = self.default_controller().ok_or_else(|| {
+            UnifiError::Config(
+                "set `default` to choose between the configured controllers".to_string(),
+            )
+        })?;
+        self.client(name)
+    }
+
+    /// Builds a `FleetClient` of every configured controller.
+    pub fn fleet(&self) -> Result<FleetClient, UnifiError> {
+        self.controllers
+            .keys()
+            .map(|name| Ok((name.clone(), self.client(name)?)))
+            .collect()
+    }
+}
+
+impl FromStr for UnifiConfig {
+    type Err = UnifiError;
+
+    /// Parses a configuration from TOML; relative paths are resolved against the current
+    /// directory.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|e| UnifiError::Config(format!("invalid config: {}", e)))
+    }
+}
+
+impl ControllerConfig {
+    /// Returns a builder with these settings, resolving relative paths against `base_dir`.
+    fn builder(&self, base_dir: Option<&Path>) -> Result<UnifiClientBuilder, UnifiError> {
+        let api_key = match (&self.api_key, &self.api_key_env) {
+            (Some(key), _) => key.clone(),
+            (None, Some(var)) => std::env::var(var).map_err(|_| {
+                UnifiError::Config(format!("environment variable {} is not set", var))
+            })?,
+            (None, None) => {
+                return Err(UnifiError::Config(
+                    "`api_key` or `api_key_env` is required".to_string(),
+                ))
+            }
+        };
+
+        let mut builder = UnifiClientBuilder::new(&self.base_url)
+            .api_key(api_key)
+            .verify_ssl(self.verify_ssl)
+            .no_proxy(self.no_proxy.iter().cloned());
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(secs) = self.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.request_timeout_secs {
+            builder = builder.request_timeout(Duration::from_secs(secs));
+        }
+        if let Some(path) = &self.identity_pem {
+            let path = match base_dir {
+                Some(dir) if path.is_relative() => dir.join(path),
+                _ => path.clone(),
+            };
+            let pem = std::fs::read(&path).map_err(|e| {
+                UnifiError::Config(format!("cannot read {}: {}", path.display(), e))
+            })?;
+            builder = builder.identity_pem(&pem);
+        }
+        Ok(builder)
+    }
+}
@@ -0,0 +1,79 @@
+//! Supplying the API key at runtime, so that rotated keys are picked up without a restart.
+
+use crate::errors::UnifiError;
+use futures::future::BoxFuture;
+use reqwest::header::HeaderValue;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// A source of the current API key, e.g. a secret manager, installed with
+/// `UnifiClientBuilder::api_key_provider`.
+///
+/// The client asks for a key before its first request and reuses it until a request is
+/// rejected with HTTP 401, when it asks again and, if the key has changed, retries the
+/// request once. Any async closure returning `Result<String, UnifiError>` is a provider.
+pub trait ApiKeyProvider: Send + Sync {
+    /// Returns the current API key.
+    fn api_key(&self) -> BoxFuture<'_, Result<String, UnifiError>>;
+}
+
+impl<F, Fut> ApiKeyProvider for F
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<String, UnifiError>> + Send + 'static,
+{
+    fn api_key(&self) -> BoxFuture<'_, Result<String, UnifiError>> {
+        Box::pin(self())
+    }
+}
+
+/// The key last read from a provider, shared by a client and its clones.
+pub(crate) struct ApiKeyCache {
+    provider: Arc<dyn ApiKeyProvider>,
+    current: RwLock<Option<HeaderValue>>,
+}
+
+impl ApiKeyCache {
+    pub(crate) fn new(provider: Arc<dyn ApiKeyProvider>) -> Self {
+        Self {
+            provider,
+            current: RwLock::new(None),
+        }
+    }
+
+    /// Returns the current key, reading it from the provider the first time.
+    pub(crate) async fn current(&self) -> Result<HeaderValue, UnifiError> {
+        if let Some(key) = self.current.read().await.as_ref() {
+            return Ok(key.clone());
+        }
+        let mut current = self.current.write().await;
+        if let Some(key) = current.as_ref() {
+            return Ok(key.clone());
+        }
+        let key = self.read().await?;
+        *current = Some(key.clone());
+        Ok(key)
+    }
+
+    /// Re-reads the key after `rejected` was refused, returning whether a different key is
+    /// now current. A key already replaced by a concurrent refresh counts as different.
+    pub(crate) async fn refresh(&self, rejected: &HeaderValue) -> Result<bool, UnifiError> {
+        let mut current = self.current.write().await;
+        if current.as_ref().is_some_and(|key| key != rejected) {
+            return Ok(true);
+        }
+        let key = self.read().await?;
+        let changed = key != rejected;
+        *current = Some(key);
+        Ok(changed)
+    }
+
+    async fn read(&self) -> Result<HeaderValue, UnifiError> {
+        let key = self.provider.api_key().await?;
+        let mut key = HeaderValue::from_str(&key)
+            .map_err(|e| UnifiError::Config(format!("invalid API key: {}", e)))?;
+        key.set_sensitive(true);
+        Ok(key)
+    }
+}
@@ -7,7 +7,22 @@ pub enum UnifiError {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
-    /// Represents an API error, containing the status code and error message.
+    /// Represents a failed API call, naming the request that produced the underlying error.
+    ///
+    /// Errors raised while sending a request or handling its response are wrapped in this
+    /// variant; use `UnifiError::inner` to match on the underlying error.
+    #[error("{method} {path}: {source}")]
+    Request {
+        /// The HTTP method of the request.
+        method: reqwest::Method,
+        /// The URL path of the request, e.g. `/v1/sites`.
+        path: String,
+        /// The error the request failed with.
+        #[source]
+        source: Box<UnifiError>,
+    },
+
+    /// Represents an API error response not covered by a more specific variant.
     #[error("API error: {status_code} - {message}")]
     Api {
         /// The HTTP status code returned by the API.
@@ -23,4 +38,213 @@ pub enum UnifiError {
     /// Represents a configuration error, containing a descriptive error message.
     #[error("Configuration error: {0}")]
     Config(String),
+
+    /// Represents a response body that could not be deserialized into the expected model.
+    #[error("Failed to deserialize `{path}` (value: {value}): {message}")]
+    Deserialization {
+        /// The path of the offending field, e.g. `data[0].id`.
+        path: String,
+        /// The raw JSON value found at `path`, or an empty string if unavailable.
+        value: String,
+        /// The underlying deserialization error message.
+        message: String,
+    },
+
+    /// Represents a successful response whose body is not valid JSON.
+    #[error("Failed to decode {status} response body: {source}")]
+    Decode {
+        /// The HTTP status code of the response.
+        status: u16,
+        /// The response body, decoded lossily as UTF-8.
+        body: String,
+        /// The underlying JSON error.
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// Represents a list endpoint that kept reporting more pages than the configured cap.
+    #[error("Page limit exceeded: stopped after {max_pages} pages")]
+    PageLimitExceeded {
+        /// The configured maximum number of pages.
+        max_pages: usize,
+    },
+
+    /// Represents an HTTP 429 response from a controller rate-limiting the API key.
+    #[error("Rate limited{}: {message}", retry_after.map(|d| format!(", retry after {:?}", d)).unwrap_or_default())]
+    RateLimited {
+        /// How long the controller asked to wait, from the `Retry-After` header.
+        retry_after: Option<std::time::Duration>,
+        /// The error message returned by the API.
+        message: String,
+    },
+
+    /// Represents an argument rejected before any request was sent, containing a descriptive error message.
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    /// Represents a failure on the WebSocket event feed, containing a descriptive error message.
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
+
+    /// Represents a 404 response, or a required resource missing from a successful response.
+    #[error("Not found: {resource} - {message}")]
+    NotFound {
+        /// The endpoint path or kind of item that was not found, e.g. `sites`.
+        resource: String,
+        /// The error message returned by the API, or a description of what was missing.
+        message: String,
+    },
+
+    /// Represents a 401 response, usually a missing, invalid or revoked API key.
+    #[error("Unauthorized: {status_code} - {message}")]
+    Unauthorized {
+        /// The HTTP status code returned by the API.
+        status_code: u16,
+        /// The error message returned by the API.
+        message: String,
+    },
+
+    /// Represents a 403 response from an API key lacking permission for the request.
+    #[error("Forbidden: {status_code} - {message}")]
+    Forbidden {
+        /// The HTTP status code returned by the API.
+        status_code: u16,
+        /// The error message returned by the API.
+        message: String,
+    },
+
+    /// Represents a 5xx response from the controller.
+    #[error("Server error: {status_code} - {message}")]
+    ServerError {
+        /// The HTTP status code returned by the API.
+        status_code: u16,
+        /// The error message returned by the API.
+        message: String,
+    },
+
+    /// Represents a failure raised by a `reqwest-middleware` layer, containing its message.
+    #[error("Middleware error: {0}")]
+    Middleware(String),
+
+    /// Represents a failure reading or writing the statistics history database, containing
+    /// a descriptive error message.
+    #[error("Storage error: {0}")]
+    Storage(String),
+}
+
+impl UnifiError {
+    /// Builds the variant matching an unsuccessful response status.
+    ///
+    /// # Arguments
+    ///
+    /// * `status_code` - The HTTP status code of the response.
+    /// * `resource` - The path of the request, reported by `NotFound`.
+    /// * `message` - The error message returned by the API.
+    ///
+    /// `RateLimited` is built without a `retry_after`, which the caller may fill in from the
+    /// response headers.
+    pub(crate) fn from_status(status_code: u16, resource: &str, message: String) -> Self {
+        match status_code {
+            401 => UnifiError::Unauthorized {
+                status_code,
+                message,
+            },
+            403 => UnifiError::Forbidden {
+                status_code,
+                message,
+            },
+            404 => UnifiError::NotFound {
+                resource: resource.to_string(),
+                message,
+            },
+            429 => UnifiError::RateLimited {
+                retry_after: None,
+                message,
+            },
+            500..=599 => UnifiError::ServerError {
+                status_code,
+                message,
+            },
+            _ => UnifiError::Api {
+                status_code,
+                message,
+            },
+        }
+    }
+
+    /// Attaches the method and path of the request that produced the error.
+    ///
+    /// Errors that already carry request context are returned unchanged.
+    pub(crate) fn with_request(self, method: reqwest::Method, path: &str) -> Self {
+        match self {
+            UnifiError::Request { .. } => self,
+            error => UnifiError::Request {
+                method,
+                path: path.to_string(),
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Returns the underlying error, looking through any `UnifiError::Request` context.
+    pub fn inner(&self) -> &UnifiError {
+        match self {
+            UnifiError::Request { source, .. } => source.inner(),
+            error => error,
+        }
+    }
+
+    /// Consumes the error, returning it without any `UnifiError::Request` context.
+    pub fn into_inner(self) -> UnifiError {
+        match self {
+            UnifiError::Request { source, .. } => source.into_inner(),
+            error => error,
+        }
+    }
+
+    /// Returns the HTTP status code behind the error, if it came from an API response.
+    ///
+    /// `NotFound` always reports 404 and `RateLimited` 429.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            UnifiError::Api { status_code, .. }
+            | UnifiError::Unauthorized { status_code, .. }
+            | UnifiError::Forbidden { status_code, .. }
+            | UnifiError::ServerError { status_code, .. } => Some(*status_code),
+            UnifiError::Decode { status, .. } => Some(*status),
+            UnifiError::Request { source, .. } => source.status_code(),
+            UnifiError::NotFound { .. } => Some(404),
+            UnifiError::RateLimited { .. } => Some(429),
+            UnifiError::Http(error) => error.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the same request may succeed if sent again later.
+    ///
+    /// This holds for rate limiting, 502, 503 and 504 responses, and connection failures
+    /// or timeouts, matching the statuses retried by the default `RetryPolicy`.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            UnifiError::RateLimited { .. } => true,
+            UnifiError::ServerError { status_code, .. } => {
+                matches!(status_code, 502..=504)
+            }
+            UnifiError::Http(error) => crate::client::is_transient(error),
+            UnifiError::Request { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "middleware")]
+impl From<reqwest_middleware::Error> for UnifiError {
+    fn from(error: reqwest_middleware::Error) -> Self {
+        match error {
+            reqwest_middleware::Error::Reqwest(error) => UnifiError::Http(error),
+            reqwest_middleware::Error::Middleware(error) => {
+                UnifiError::Middleware(format!("{:#}", error))
+            }
+        }
+    }
 }
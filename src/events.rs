@@ -0,0 +1,175 @@
+//! The controller's WebSocket event feed. Experimental.
+//!
+//! The integration API does not document an event feed. `UnifiClient::subscribe_events`
+//! connects to `v1/sites/{site_id}/events` below the base URL, which controllers may not
+//! serve, and the shape of the events it yields may change in any release.
+
+use crate::client::{deserialize_body, TlsBackend};
+use crate::errors::UnifiError;
+use crate::models::device::DeviceState;
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use futures::{Stream, StreamExt};
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+use tokio_tungstenite::Connector;
+
+/// An event pushed by the controller over its WebSocket feed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "SCREAMING_SNAKE_CASE",
+    rename_all_fields = "camelCase"
+)]
+pub enum UnifiEvent {
+    DeviceStateChanged {
+        device_id: DeviceId,
+        state: DeviceState,
+    },
+    ClientConnected {
+        client_id: ClientId,
+        #[serde(default)]
+        uplink_device_id: Option<DeviceId>,
+    },
+    ClientDisconnected {
+        client_id: ClientId,
+    },
+    /// An event type not modelled by this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Builds the WebSocket URL of a site's event feed from the REST base URL.
+pub(crate) fn events_url(base_url: &str, site_id: SiteId) -> Result<String, UnifiError> {
+    let mut url = url::Url::parse(&format!("{}/v1/sites/{}/events", base_url, site_id))?;
+    let scheme = match url.scheme() {
+        "https" => "wss",
+        "http" => "ws",
+        other => {
+            return Err(UnifiError::Config(format!(
+                "unsupported base URL scheme `{}` for events",
+                other
+            )))
+        }
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| UnifiError::Config("cannot build events URL".to_string()))?;
+    Ok(url.into())
+}
+
+/// Connects to an event feed and yields its decoded events.
+pub(crate) async fn subscribe(
+    url: &str,
+    api_key: &HeaderValue,
+    verify_ssl: bool,
+    backend: TlsBackend,
+) -> Result<impl Stream<Item = Result<UnifiEvent, UnifiError>>, UnifiError> {
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| UnifiError::WebSocket(e.to_string()))?;
+    request.headers_mut().insert("X-API-KEY", api_key.clone());
+
+    let connector = match backend {
+        #[cfg(feature = "rustls")]
+        TlsBackend::Rustls if verify_ssl => None,
+        #[cfg(feature = "rustls")]
+        TlsBackend::Rustls => Some(Connector::Rustls(insecure::client_config()?)),
+        #[cfg(feature = "native-tls")]
+        TlsBackend::NativeTls => {
+            let tls = native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(!verify_ssl)
+                .build()
+                .map_err(|e| UnifiError::Config(e.to_string()))?;
+            Some(Connector::NativeTls(tls))
+        }
+    };
+
+    let (socket, _) =
+        tokio_tungstenite::connect_async_tls_with_config(request, None, false, connector)
+            .await
+            .map_err(|e| UnifiError::WebSocket(e.to_string()))?;
+
+    Ok(socket.filter_map(|message| async move {
+        match message {
+            Ok(Message::Text(text)) => Some(deserialize_body(text.as_bytes())),
+            Ok(_) => None,
+            Err(e) => Some(Err(UnifiError::WebSocket(e.to_string()))),
+        }
+    }))
+}
+
+/// A rustls configuration that accepts any server certificate, for `verify_ssl(false)`.
+#[cfg(feature = "rustls")]
+mod insecure {
+    use crate::errors::UnifiError;
+    use rustls_crate::client::danger::{
+        HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+    };
+    use rustls_crate::crypto::{self, CryptoProvider};
+    use rustls_crate::pki_types::{CertificateDer, ServerName, UnixTime};
+    use rustls_crate::{ClientConfig, DigitallySignedStruct, Error, SignatureScheme};
+    use std::sync::Arc;
+
+    /// Trusts every certificate chain, while still checking that the server holds the
+    /// private key of the certificate it presents.
+    #[derive(Debug)]
+    struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+    impl ServerCertVerifier for AcceptAnyCertificate {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            crypto::verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            crypto::verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+
+    pub(super) fn client_config() -> Result<Arc<ClientConfig>, UnifiError> {
+        let provider = Arc::new(crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| UnifiError::Config(e.to_string()))?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+            .with_no_client_auth();
+        Ok(Arc::new(config))
+    }
+}
@@ -0,0 +1,254 @@
+//! Rendering statistics as InfluxDB line protocol, also accepted by VictoriaMetrics.
+//!
+//! `device_statistics` renders one `unifi_device` line per statistics sample and
+//! `client_counts` one `unifi_clients` line per site, tagged with the site and, for
+//! devices, the device's name, id and model. The lines can be joined with newlines and
+//! written to the database's write endpoint as they are. `Line` builds custom points
+//! with the same escaping. Only available with the `influx` feature.
+
+use crate::models::client::ClientOverview;
+use crate::models::device::{DeviceDetails, DeviceOverview};
+use crate::models::id::DeviceId;
+use crate::models::site::SiteOverview;
+use crate::models::statistics::DeviceStatistics;
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// The measurement of `device_statistics` lines.
+pub const DEVICE_MEASUREMENT: &str = "unifi_device";
+/// The measurement of `client_counts` lines.
+pub const CLIENTS_MEASUREMENT: &str = "unifi_clients";
+
+/// The device a statistics sample belongs to, used for its tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceTags {
+    pub id: DeviceId,
+    pub name: String,
+    pub model: String,
+}
+
+impl From<&DeviceOverview> for DeviceTags {
+    fn from(device: &DeviceOverview) -> Self {
+        Self {
+            id: device.id,
+            name: device.name.clone(),
+            model: device.model.clone(),
+        }
+    }
+}
+
+impl From<&DeviceDetails> for DeviceTags {
+    fn from(device: &DeviceDetails) -> Self {
+        Self {
+            id: device.id,
+            name: device.name.clone(),
+            model: device.model.clone(),
+        }
+    }
+}
+
+/// A point in line protocol: a measurement, tags, fields and an optional timestamp.
+///
+/// Names and values are escaped when the line is displayed. A point needs at least one
+/// field to be valid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    measurement: String,
+    tags: Vec<(String, String)>,
+    fields: Vec<(String, FieldValue)>,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldValue {
+    Float(f64),
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+impl Line {
+    pub fn new(measurement: impl Into<String>) -> Self {
+        Self {
+            measurement: measurement.into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
+            timestamp: None,
+        }
+    }
+
+    /// Adds a tag. Tags with an empty value are left out, as line protocol cannot carry them.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.is_empty() {
+            self.tags.push((key.into(), value));
+        }
+        self
+    }
+
+    /// Adds a float field; non-finite values are left out.
+    pub fn float(mut self, key: impl Into<String>, value: f64) -> Self {
+        if value.is_finite() {
+            self.fields.push((key.into(), FieldValue::Float(value)));
+        }
+        self
+    }
+
+    /// Adds an integer field.
+    pub fn integer(mut self, key: impl Into<String>, value: i64) -> Self {
+        self.fields.push((key.into(), FieldValue::Integer(value)));
+        self
+    }
+
+    /// Adds a boolean field.
+    pub fn boolean(mut self, key: impl Into<String>, value: bool) -> Self {
+        self.fields.push((key.into(), FieldValue::Boolean(value)));
+        self
+    }
+
+    /// Adds a string field.
+    pub fn string(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields
+            .push((key.into(), FieldValue::String(value.into())));
+        self
+    }
+
+    /// Sets the timestamp, written in nanoseconds. Without one the database uses the time
+    /// it receives the point.
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&escape(&self.measurement, &[',', ' ']))?;
+        // Tags sorted by key are what the database stores, so it need not sort them.
+        let mut tags: Vec<_> = self.tags.iter().collect();
+        tags.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, value) in tags {
+            write!(
+                f,
+                ",{}={}",
+                escape(key, KEY_SPECIAL),
+                escape(value, KEY_SPECIAL)
+            )?;
+        }
+        for (i, (key, value)) in self.fields.iter().enumerate() {
+            f.write_str(if i == 0 { " " } else { "," })?;
+            write!(f, "{}=", escape(key, KEY_SPECIAL))?;
+            match value {
+                FieldValue::Float(value) => write!(f, "{}", value)?,
+                FieldValue::Integer(value) => write!(f, "{}i", value)?,
+                FieldValue::Boolean(value) => write!(f, "{}", value)?,
+                FieldValue::String(value) => write!(f, "\"{}\"", escape(value, &['"', '\\']))?,
+            }
+        }
+        if let Some(nanos) = self.timestamp.and_then(|t| t.timestamp_nanos_opt()) {
+            write!(f, " {}", nanos)?;
+        }
+        Ok(())
+    }
+}
+
+/// The characters escaped in tag keys, tag values and field keys.
+const KEY_SPECIAL: &[char] = &[',', '=', ' '];
+
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        // Line breaks would end the line, so they are replaced.
+        escaped.push(if c == '\n' || c == '\r' { ' ' } else { c });
+    }
+    escaped
+}
+
+/// Adds the `site` (name, or id for unnamed sites) and `site_id` tags.
+fn site_tags(line: Line, site: &SiteOverview) -> Line {
+    let name = site.name.clone().unwrap_or_else(|| site.id.to_string());
+    line.tag("site", name).tag("site_id", site.id.to_string())
+}
+
+/// Renders a device's statistics sample, timestamped with its last heartbeat.
+///
+/// # Arguments
+///
+/// * `site` - The site of the device.
+/// * `device` - The device, as a `DeviceOverview`, `DeviceDetails` or `DeviceTags`.
+/// * `statistics` - The statistics sample.
+///
+/// # Returns
+///
+/// A `unifi_device` line with the uptime, CPU, memory, load averages and uplink rates
+/// the controller reported.
+pub fn device_statistics(
+    site: &SiteOverview,
+    device: impl Into<DeviceTags>,
+    statistics: &DeviceStatistics,
+) -> String {
+    let device = device.into();
+    let mut line = site_tags(Line::new(DEVICE_MEASUREMENT), site)
+        .tag("device", device.name)
+        .tag("device_id", device.id.to_string())
+        .tag("model", device.model)
+        .integer("uptime_sec", statistics.uptime_sec)
+        .timestamp(statistics.last_heartbeat_at);
+
+    let gauges = [
+        ("cpu_utilization_pct", statistics.cpu_utilization_pct),
+        ("memory_utilization_pct", statistics.memory_utilization_pct),
+        ("load_average_1min", statistics.load_average_1min),
+        ("load_average_5min", statistics.load_average_5min),
+        ("load_average_15min", statistics.load_average_15min),
+    ];
+    for (key, value) in gauges {
+        if let Some(value) = value {
+            line = line.float(key, value);
+        }
+    }
+    if let Some(uplink) = &statistics.uplink {
+        line = line
+            .integer("uplink_tx_rate_bps", uplink.tx_rate_bps)
+            .integer("uplink_rx_rate_bps", uplink.rx_rate_bps);
+    }
+    line.to_string()
+}
+
+/// Renders how many clients are connected to a site, in total and by connection type.
+///
+/// # Arguments
+///
+/// * `site` - The site the clients are connected to.
+/// * `clients` - The site's connected clients.
+/// * `timestamp` - When the clients were listed.
+///
+/// # Returns
+///
+/// A `unifi_clients` line with `total`, `wired`, `wireless`, `vpn` and `teleport` fields.
+pub fn client_counts(
+    site: &SiteOverview,
+    clients: &[ClientOverview],
+    timestamp: DateTime<Utc>,
+) -> String {
+    let count = |matches: fn(&ClientOverview) -> bool| {
+        clients.iter().filter(|client| matches(client)).count() as i64
+    };
+    site_tags(Line::new(CLIENTS_MEASUREMENT), site)
+        .integer("total", clients.len() as i64)
+        .integer("wired", count(|c| matches!(c, ClientOverview::Wired(_))))
+        .integer(
+            "wireless",
+            count(|c| matches!(c, ClientOverview::Wireless(_))),
+        )
+        .integer("vpn", count(|c| matches!(c, ClientOverview::Vpn(_))))
+        .integer(
+            "teleport",
+            count(|c| matches!(c, ClientOverview::Teleport(_))),
+        )
+        .timestamp(timestamp)
+        .to_string()
+}
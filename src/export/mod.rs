@@ -0,0 +1,200 @@
+//! Exporting an `Inventory` as flat rows, for asset-management tools and spreadsheets.
+//!
+//! Every device and client becomes one `InventoryRow` carrying its site, so the rows of a
+//! whole controller fit one table. `to_csv` writes them with a header line and `to_json`
+//! as an array of objects with the same camelCase keys. With the `influx` feature,
+//! `influx` renders statistics as InfluxDB line protocol. `ndjson` streams a whole
+//! `FleetSnapshot` one record per line, and reads it back.
+
+#[cfg(feature = "influx")]
+pub mod influx;
+pub mod ndjson;
+
+use crate::models::client::ClientOverview;
+use crate::models::device::{DeviceDetails, DeviceState};
+use crate::models::id::SiteId;
+use crate::models::site::SiteOverview;
+use crate::snapshot::Inventory;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// The columns written by `to_csv`, in order.
+pub const CSV_COLUMNS: [&str; 11] = [
+    "kind",
+    "siteId",
+    "siteName",
+    "id",
+    "name",
+    "model",
+    "macAddress",
+    "ipAddress",
+    "state",
+    "firmwareVersion",
+    "connectionType",
+];
+
+/// Whether an `InventoryRow` describes a device or a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RowKind {
+    Device,
+    Client,
+}
+
+impl RowKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            RowKind::Device => "device",
+            RowKind::Client => "client",
+        }
+    }
+}
+
+/// One device or client of an inventory. Fields that do not apply to the kind are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryRow {
+    pub kind: RowKind,
+    pub site_id: SiteId,
+    pub site_name: Option<String>,
+    pub id: Uuid,
+    pub name: Option<String>,
+    /// The model of a device.
+    pub model: Option<String>,
+    pub mac_address: Option<String>,
+    pub ip_address: Option<IpAddr>,
+    /// The state of a device, e.g. `ONLINE`.
+    pub state: Option<String>,
+    /// The firmware version of a device.
+    pub firmware_version: Option<String>,
+    /// How a client is connected, e.g. `WIRELESS`.
+    pub connection_type: Option<String>,
+}
+
+impl InventoryRow {
+    fn device(site: &SiteOverview, device: &DeviceDetails) -> Self {
+        Self {
+            kind: RowKind::Device,
+            site_id: site.id,
+            site_name: site.name.clone(),
+            id: device.id.into(),
+            name: Some(device.name.clone()),
+            model: Some(device.model.clone()),
+            mac_address: Some(device.mac_address.clone()),
+            ip_address: device.ip_address,
+            state: Some(state_name(&device.state)),
+            firmware_version: Some(device.firmware_version.clone()),
+            connection_type: None,
+        }
+    }
+
+    fn client(site: &SiteOverview, client: &ClientOverview) -> Self {
+        let base = client.base();
+        let connection_type = match client {
+            ClientOverview::Wired(_) => Some("WIRED"),
+            ClientOverview::Wireless(_) => Some("WIRELESS"),
+            ClientOverview::Vpn(_) => Some("VPN"),
+            ClientOverview::Teleport(_) => Some("TELEPORT"),
+            ClientOverview::Unknown(_) => None,
+        };
+        Self {
+            kind: RowKind::Client,
+            site_id: site.id,
+            site_name: site.name.clone(),
+            id: base.id.into(),
+            name: base.name.clone(),
+            model: None,
+            mac_address: client.mac_address().map(str::to_string),
+            ip_address: base.ip_address,
+            state: None,
+            firmware_version: None,
+            connection_type: connection_type.map(str::to_string),
+        }
+    }
+
+    /// Returns the row's values in the order of `CSV_COLUMNS`, empty where `None`.
+    pub fn values(&self) -> [String; 11] {
+        fn text<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+        [
+            self.kind.as_str().to_string(),
+            self.site_id.to_string(),
+            text(&self.site_name),
+            self.id.to_string(),
+            text(&self.name),
+            text(&self.model),
+            text(&self.mac_address),
+            text(&self.ip_address),
+            text(&self.state),
+            text(&self.firmware_version),
+            text(&self.connection_type),
+        ]
+    }
+}
+
+/// Returns the name the controller uses for a device state, e.g. `ONLINE`.
+fn state_name(state: &DeviceState) -> String {
+    match serde_json::to_value(state) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{:?}", state),
+    }
+}
+
+/// Flattens an inventory into one row per device, then one row per client, site by site.
+pub fn rows(inventory: &Inventory) -> Vec<InventoryRow> {
+    inventory
+        .sites
+        .iter()
+        .flat_map(|site| {
+            let devices = site
+                .snapshot
+                .devices
+                .iter()
+                .map(|device| InventoryRow::device(&site.site, device));
+            let clients = site
+                .snapshot
+                .clients
+                .iter()
+                .map(|client| InventoryRow::client(&site.site, client));
+            devices.chain(clients)
+        })
+        .collect()
+}
+
+/// Writes an inventory as CSV: a header line of `CSV_COLUMNS`, then one line per row.
+///
+/// Values containing a comma, quote or line break are quoted as described in RFC 4180.
+pub fn to_csv<W: Write>(mut writer: W, inventory: &Inventory) -> io::Result<()> {
+    write_csv_line(&mut writer, CSV_COLUMNS.iter().copied())?;
+    for row in rows(inventory) {
+        write_csv_line(&mut writer, row.values().iter().map(String::as_str))?;
+    }
+    writer.flush()
+}
+
+/// Writes an inventory as a JSON array of rows.
+pub fn to_json<W: Write>(writer: W, inventory: &Inventory) -> io::Result<()> {
+    serde_json::to_writer_pretty(writer, &rows(inventory))?;
+    Ok(())
+}
+
+fn write_csv_line<'a, W: Write>(
+    writer: &mut W,
+    values: impl Iterator<Item = &'a str>,
+) -> io::Result<()> {
+    let line = values
+        .map(|value| {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\r\n")
+}
@@ -0,0 +1,321 @@
+//! Streaming a `FleetSnapshot` as newline-delimited JSON.
+//!
+//! The first line is a `header` record carrying `SCHEMA_VERSION` and the time the snapshot
+//! was taken. Every controller, site, device and client then gets one line of its own,
+//! each naming the controller (and site) it belongs to, and a final `end` record counts
+//! them so a truncated file is detected. Snapshots of large fleets can thus be written and
+//! read back without holding the serialized form in memory.
+
+use crate::models::client::ClientOverview;
+use crate::models::device::DeviceDetails;
+use crate::models::id::SiteId;
+use crate::models::site::SiteOverview;
+use crate::snapshot::{FleetSnapshot, Inventory, SiteInventory, SiteSnapshot};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use thiserror::Error;
+
+/// The schema version written in the header record, and the only one read back.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// An error reading a snapshot written by `write_fleet_snapshot_ndjson`.
+#[derive(Debug, Error)]
+pub enum NdjsonError {
+    /// Reading from the underlying reader failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// A line is partial, corrupt or out of place.
+    #[error("line {line}: {message}")]
+    InvalidLine {
+        /// The 1-based number of the offending line.
+        line: usize,
+        /// What is wrong with the line.
+        message: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum Record<'a> {
+    #[serde(rename_all = "camelCase")]
+    Header {
+        schema_version: u32,
+        taken_at: DateTime<Utc>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Controller {
+        name: Cow<'a, str>,
+        taken_at: DateTime<Utc>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Site {
+        controller: Cow<'a, str>,
+        taken_at: DateTime<Utc>,
+        site: Cow<'a, SiteOverview>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Device {
+        controller: Cow<'a, str>,
+        site_id: SiteId,
+        device: Cow<'a, DeviceDetails>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Client {
+        controller: Cow<'a, str>,
+        site_id: SiteId,
+        client: Cow<'a, ClientOverview>,
+    },
+    #[serde(rename_all = "camelCase")]
+    End { records: usize },
+}
+
+/// Writes a fleet snapshot as newline-delimited JSON, one record per line.
+///
+/// # Arguments
+///
+/// * `snapshot` - The snapshot to write.
+/// * `writer` - Where to write it. Wrap unbuffered writers such as files in a `BufWriter`.
+pub fn write_fleet_snapshot_ndjson<W: Write>(
+    snapshot: &FleetSnapshot,
+    mut writer: W,
+) -> io::Result<()> {
+    write_record(
+        &mut writer,
+        &Record::Header {
+            schema_version: SCHEMA_VERSION,
+            taken_at: snapshot.taken_at,
+        },
+    )?;
+
+    let mut records = 0;
+    for (name, inventory) in &snapshot.controllers {
+        let controller = Cow::Borrowed(name.as_str());
+        write_record(
+            &mut writer,
+            &Record::Controller {
+                name: controller.clone(),
+                taken_at: inventory.taken_at,
+            },
+        )?;
+        records += 1;
+
+        for site in &inventory.sites {
+            let site_id = site.site.id;
+            write_record(
+                &mut writer,
+                &Record::Site {
+                    controller: controller.clone(),
+                    taken_at: site.snapshot.taken_at,
+                    site: Cow::Borrowed(&site.site),
+                },
+            )?;
+            records += 1;
+
+            for device in &site.snapshot.devices {
+                write_record(
+                    &mut writer,
+                    &Record::Device {
+                        controller: controller.clone(),
+                        site_id,
+                        device: Cow::Borrowed(device),
+                    },
+                )?;
+                records += 1;
+            }
+            for client in &site.snapshot.clients {
+                write_record(
+                    &mut writer,
+                    &Record::Client {
+                        controller: controller.clone(),
+                        site_id,
+                        client: Cow::Borrowed(client),
+                    },
+                )?;
+                records += 1;
+            }
+        }
+    }
+
+    write_record(&mut writer, &Record::End { records })?;
+    writer.flush()
+}
+
+/// Reads back a fleet snapshot written by `write_fleet_snapshot_ndjson`.
+///
+/// Blank lines are skipped. Every other line must be a complete record, ending in a line
+/// break, that belongs to a controller and site already read.
+///
+/// # Arguments
+///
+/// * `reader` - Where to read the snapshot from.
+///
+/// # Returns
+///
+/// A `Result` containing the `FleetSnapshot` on success, or an `NdjsonError` naming the
+/// offending line if the input is partial, corrupt or of another schema version.
+pub fn read_fleet_snapshot_ndjson<R: BufRead>(mut reader: R) -> Result<FleetSnapshot, NdjsonError> {
+    let mut snapshot: Option<FleetSnapshot> = None;
+    let mut records = 0;
+    let mut ended = false;
+    let mut line = 0;
+    let mut buf = String::new();
+
+    loop {
+        buf.clear();
+        let read = reader
+            .read_line(&mut buf)
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::InvalidData => invalid(line + 1, "not valid UTF-8"),
+                _ => NdjsonError::Io(error),
+            })?;
+        if read == 0 {
+            break;
+        }
+        line += 1;
+        if !buf.ends_with('\n') {
+            return Err(invalid(line, "partial record, missing line break"));
+        }
+        let text = buf.trim();
+        if text.is_empty() {
+            continue;
+        }
+        if ended {
+            return Err(invalid(line, "record after the end record"));
+        }
+
+        let record: Record = serde_json::from_str(text)
+            .map_err(|error| invalid(line, format!("corrupt record: {}", error)))?;
+        let Some(fleet) = snapshot.as_mut() else {
+            match record {
+                Record::Header {
+                    schema_version: SCHEMA_VERSION,
+                    taken_at,
+                } => {
+                    snapshot = Some(FleetSnapshot {
+                        taken_at,
+                        controllers: BTreeMap::new(),
+                    });
+                    continue;
+                }
+                Record::Header { schema_version, .. } => {
+                    return Err(invalid(
+                        line,
+                        format!("unsupported schema version {}", schema_version),
+                    ))
+                }
+                _ => return Err(invalid(line, "expected the header record")),
+            }
+        };
+
+        match record {
+            Record::Header { .. } => return Err(invalid(line, "duplicate header record")),
+            Record::Controller { name, taken_at } => {
+                let inventory = Inventory {
+                    taken_at,
+                    sites: Vec::new(),
+                };
+                if fleet
+                    .controllers
+                    .insert(name.to_string(), inventory)
+                    .is_some()
+                {
+                    return Err(invalid(line, format!("duplicate controller `{}`", name)));
+                }
+            }
+            Record::Site {
+                controller,
+                taken_at,
+                site,
+            } => {
+                let inventory = fleet
+                    .controllers
+                    .get_mut(controller.as_ref())
+                    .ok_or_else(|| unknown_controller(line, &controller))?;
+                let site = site.into_owned();
+                inventory.sites.push(SiteInventory {
+                    snapshot: SiteSnapshot {
+                        site_id: site.id,
+                        taken_at,
+                        devices: Vec::new(),
+                        clients: Vec::new(),
+                    },
+                    site,
+                });
+            }
+            Record::Device {
+                controller,
+                site_id,
+                device,
+            } => find_site(fleet, line, &controller, site_id)?
+                .devices
+                .push(device.into_owned()),
+            Record::Client {
+                controller,
+                site_id,
+                client,
+            } => find_site(fleet, line, &controller, site_id)?
+                .clients
+                .push(client.into_owned()),
+            Record::End { records: expected } => {
+                if expected != records {
+                    return Err(invalid(
+                        line,
+                        format!("end record counts {} records, read {}", expected, records),
+                    ));
+                }
+                ended = true;
+                continue;
+            }
+        }
+        records += 1;
+    }
+
+    match snapshot {
+        None => Err(invalid(line + 1, "missing header record")),
+        Some(_) if !ended => Err(invalid(line + 1, "missing end record, input is truncated")),
+        Some(snapshot) => Ok(snapshot),
+    }
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &Record) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")
+}
+
+fn find_site<'a>(
+    fleet: &'a mut FleetSnapshot,
+    line: usize,
+    controller: &str,
+    site_id: SiteId,
+) -> Result<&'a mut SiteSnapshot, NdjsonError> {
+    let inventory = fleet
+        .controllers
+        .get_mut(controller)
+        .ok_or_else(|| unknown_controller(line, controller))?;
+    inventory
+        .sites
+        .iter_mut()
+        .find(|site| site.site.id == site_id)
+        .map(|site| &mut site.snapshot)
+        .ok_or_else(|| {
+            invalid(
+                line,
+                format!("unknown site {} of `{}`", site_id, controller),
+            )
+        })
+}
+
+fn unknown_controller(line: usize, controller: &str) -> NdjsonError {
+    invalid(line, format!("unknown controller `{}`", controller))
+}
+
+fn invalid(line: usize, message: impl Into<String>) -> NdjsonError {
+    NdjsonError::InvalidLine {
+        line,
+        message: message.into(),
+    }
+}
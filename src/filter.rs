@@ -0,0 +1,237 @@
+use std::fmt;
+use uuid::Uuid;
+
+/// A value compared against a property in a `Filter`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    String(String),
+    Integer(i64),
+    Number(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterValue::String(value) => {
+                write!(f, "'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+            }
+            FilterValue::Integer(value) => write!(f, "{}", value),
+            FilterValue::Number(value) => write!(f, "{}", value),
+            FilterValue::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        FilterValue::String(value.to_string())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        FilterValue::String(value)
+    }
+}
+
+impl From<Uuid> for FilterValue {
+    fn from(value: Uuid) -> Self {
+        FilterValue::String(value.to_string())
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(value: i64) -> Self {
+        FilterValue::Integer(value)
+    }
+}
+
+impl From<i32> for FilterValue {
+    fn from(value: i32) -> Self {
+        FilterValue::Integer(value.into())
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(value: f64) -> Self {
+        FilterValue::Number(value)
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(value: bool) -> Self {
+        FilterValue::Bool(value)
+    }
+}
+
+/// A server-side filter expression for the `filter` query parameter of list endpoints.
+///
+/// Renders to the Network API filter syntax, e.g.
+/// `Filter::eq("state", "ONLINE").and(Filter::contains("name", "office"))` becomes
+/// `and(state.eq('ONLINE'),name.like('*office*'))`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// `property.function(values...)`
+    Property {
+        property: String,
+        function: &'static str,
+        values: Vec<FilterValue>,
+    },
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    fn property(
+        property: impl Into<String>,
+        function: &'static str,
+        values: Vec<FilterValue>,
+    ) -> Self {
+        Filter::Property {
+            property: property.into(),
+            function,
+            values,
+        }
+    }
+
+    /// Matches when `property` equals `value`.
+    pub fn eq(property: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::property(property, "eq", vec![value.into()])
+    }
+
+    /// Matches when `property` does not equal `value`.
+    pub fn ne(property: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::property(property, "ne", vec![value.into()])
+    }
+
+    /// Matches when `property` is greater than `value`.
+    pub fn gt(property: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::property(property, "gt", vec![value.into()])
+    }
+
+    /// Matches when `property` is greater than or equal to `value`.
+    pub fn ge(property: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::property(property, "ge", vec![value.into()])
+    }
+
+    /// Matches when `property` is less than `value`.
+    pub fn lt(property: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::property(property, "lt", vec![value.into()])
+    }
+
+    /// Matches when `property` is less than or equal to `value`.
+    pub fn le(property: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::property(property, "le", vec![value.into()])
+    }
+
+    /// Matches when `property` matches `pattern`, where `*` matches any number of
+    /// characters and `.` any single character. `pattern` is passed through as is; escape
+    /// literal wildcards with `\`, or use `Filter::contains` for plain substrings.
+    pub fn like(property: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::property(property, "like", vec![FilterValue::String(pattern.into())])
+    }
+
+    /// Matches when `property` contains `value` as a substring.
+    ///
+    /// Wildcard characters in `value` are escaped, so they only match themselves.
+    pub fn contains(property: impl Into<String>, value: impl AsRef<str>) -> Self {
+        Self::like(property, format!("*{}*", escape_pattern(value.as_ref())))
+    }
+
+    /// Matches when `property` equals any of `values`.
+    pub fn is_in<V: Into<FilterValue>>(
+        property: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Self::property(property, "in", values.into_iter().map(Into::into).collect())
+    }
+
+    /// Matches when `property` is not set.
+    pub fn is_null(property: impl Into<String>) -> Self {
+        Self::property(property, "isNull", Vec::new())
+    }
+
+    /// Matches when `property` is set.
+    pub fn is_not_null(property: impl Into<String>) -> Self {
+        Self::property(property, "isNotNull", Vec::new())
+    }
+
+    /// Combines this filter with `other`, matching when both match.
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            filter => Filter::And(vec![filter, other]),
+        }
+    }
+
+    /// Combines this filter with `other`, matching when either matches.
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            filter => Filter::Or(vec![filter, other]),
+        }
+    }
+
+    /// Negates this filter.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Filter::Not(Box::new(self))
+    }
+}
+
+/// Escapes the wildcard characters of a `like` pattern, and the escape character itself.
+fn escape_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '.' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn write_joined<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(",")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::Property {
+                property,
+                function,
+                values,
+            } => {
+                write!(f, "{}.{}(", property, function)?;
+                write_joined(f, values)?;
+                f.write_str(")")
+            }
+            Filter::And(filters) => {
+                f.write_str("and(")?;
+                write_joined(f, filters)?;
+                f.write_str(")")
+            }
+            Filter::Or(filters) => {
+                f.write_str("or(")?;
+                write_joined(f, filters)?;
+                f.write_str(")")
+            }
+            Filter::Not(filter) => write!(f, "not({})", filter),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Aggregating several controllers behind one client.
+//!
+//! A `FleetClient` holds one named `UnifiClient` per controller, each built with its own
+//! base URL, API key and TLS settings, and fans operations out to all of them. Results are
+//! tagged with the controller and site they came from, and a controller that fails is
+//! reported alongside the results of the others rather than failing the whole call.
+
+use crate::client::UnifiClient;
+use crate::errors::UnifiError;
+use crate::models::client::ClientOverview;
+use crate::models::device::DeviceOverview;
+use crate::models::id::SiteId;
+use crate::models::site::SiteOverview;
+use crate::snapshot::FleetSnapshot;
+use chrono::Utc;
+use futures::future;
+use std::collections::BTreeMap;
+use std::future::Future;
+
+/// An item returned by a fleet-wide operation, tagged with where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FleetItem<T> {
+    /// The name the controller was added to the fleet under.
+    pub controller: String,
+    /// The id of the site the item belongs to.
+    pub site_id: SiteId,
+    /// The item itself.
+    pub item: T,
+}
+
+/// The outcome of a fleet-wide operation.
+#[derive(Debug)]
+pub struct FleetResults<T> {
+    /// The items returned by the controllers that succeeded, in controller name order.
+    pub items: Vec<FleetItem<T>>,
+    /// The controllers that failed, with their error.
+    pub failed: Vec<(String, UnifiError)>,
+}
+
+impl<T> FleetResults<T> {
+    /// Returns whether every controller succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// A set of named `UnifiClient`s, one per controller.
+#[derive(Clone, Default)]
+pub struct FleetClient {
+    controllers: BTreeMap<String, UnifiClient>,
+}
+
+impl FleetClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a controller, returning the client previously added under `name`, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name results from this controller are tagged with.
+    /// * `client` - The client for the controller.
+    pub fn insert(&mut self, name: impl Into<String>, client: UnifiClient) -> Option<UnifiClient> {
+        self.controllers.insert(name.into(), client)
+    }
+
+    /// Adds a controller, see `FleetClient::insert`.
+    pub fn with_controller(mut self, name: impl Into<String>, client: UnifiClient) -> Self {
+        self.insert(name, client);
+        self
+    }
+
+    /// Removes a controller, returning its client.
+    pub fn remove(&mut self, name: &str) -> Option<UnifiClient> {
+        self.controllers.remove(name)
+    }
+
+    /// Returns the client of the controller added under `name`.
+    pub fn controller(&self, name: &str) -> Option<&UnifiClient> {
+        self.controllers.get(name)
+    }
+
+    /// Returns the names of the controllers, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.controllers.keys().map(String::as_str)
+    }
+
+    /// Returns the number of controllers.
+    pub fn len(&self) -> usize {
+        self.controllers.len()
+    }
+
+    /// Returns whether the fleet has no controllers.
+    pub fn is_empty(&self) -> bool {
+        self.controllers.is_empty()
+    }
+
+    /// Lists every site of every controller.
+    ///
+    /// # Returns
+    ///
+    /// The `FleetResults` of all `SiteOverview`s, tagged with their controller.
+    pub async fn list_all_sites(&self) -> FleetResults<SiteOverview> {
+        self.fan_out(|client| async move {
+            let sites = client.list_all_sites().await?;
+            Ok(sites.into_iter().map(|site| (site.id, site)).collect())
+        })
+        .await
+    }
+
+    /// Lists every device of every site of every controller.
+    ///
+    /// # Returns
+    ///
+    /// The `FleetResults` of all `DeviceOverview`s, tagged with their controller and site.
+    pub async fn list_all_devices(&self) -> FleetResults<DeviceOverview> {
+        self.for_each_site(|client, site_id| async move { client.list_all_devices(site_id).await })
+            .await
+    }
+
+    /// Lists every connected client of every site of every controller.
+    ///
+    /// # Returns
+    ///
+    /// The `FleetResults` of all `ClientOverview`s, tagged with their controller and site.
+    pub async fn list_all_clients(&self) -> FleetResults<ClientOverview> {
+        self.for_each_site(|client, site_id| async move { client.list_all_clients(site_id).await })
+            .await
+    }
+
+    /// Takes a snapshot of every controller, each as by `UnifiClient::snapshot`.
+    ///
+    /// # Returns
+    ///
+    /// The `FleetSnapshot` of the controllers that succeeded, and the controllers that
+    /// failed with their error.
+    pub async fn snapshot(&self) -> (FleetSnapshot, Vec<(String, UnifiError)>) {
+        let taken_at = Utc::now();
+        let outcomes = future::join_all(self.controllers.values().map(UnifiClient::snapshot)).await;
+
+        let mut snapshot = FleetSnapshot {
+            taken_at,
+            controllers: BTreeMap::new(),
+        };
+        let mut failed = Vec::new();
+        for (name, outcome) in self.controllers.keys().zip(outcomes) {
+            match outcome {
+                Ok(inventory) => {
+                    snapshot.controllers.insert(name.clone(), inventory);
+                }
+                Err(error) => failed.push((name.clone(), error)),
+            }
+        }
+        (snapshot, failed)
+    }
+
+    /// Runs `op` on every site of every controller, collecting the items it returns.
+    ///
+    /// A controller fails as a whole if listing its sites or `op` on any of them fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - The operation to run with each controller's client, which is cheap to
+    ///   clone, and site id.
+    ///
+    /// # Returns
+    ///
+    /// The `FleetResults` of the items returned by `op`, tagged with their controller and site.
+    pub async fn for_each_site<T, F, Fut>(&self, op: F) -> FleetResults<T>
+    where
+        F: Fn(UnifiClient, SiteId) -> Fut,
+        Fut: Future<Output = Result<Vec<T>, UnifiError>>,
+    {
+        let op = &op;
+        self.fan_out(|client| async move {
+            let mut items = Vec::new();
+            for site in client.list_all_sites().await? {
+                let site_items = op(client.clone(), site.id).await?;
+                items.extend(site_items.into_iter().map(|item| (site.id, item)));
+            }
+            Ok(items)
+        })
+        .await
+    }
+
+    /// Runs `op` on every controller concurrently and tags the items it returns.
+    async fn fan_out<'a, T, F, Fut>(&'a self, op: F) -> FleetResults<T>
+    where
+        F: Fn(&'a UnifiClient) -> Fut,
+        Fut: Future<Output = Result<Vec<(SiteId, T)>, UnifiError>>,
+    {
+        let outcomes = future::join_all(self.controllers.values().map(&op)).await;
+
+        let mut results = FleetResults {
+            items: Vec::new(),
+            failed: Vec::new(),
+        };
+        for (name, outcome) in self.controllers.keys().zip(outcomes) {
+            match outcome {
+                Ok(items) => results
+                    .items
+                    .extend(items.into_iter().map(|(site_id, item)| FleetItem {
+                        controller: name.clone(),
+                        site_id,
+                        item,
+                    })),
+                Err(error) => results.failed.push((name.clone(), error)),
+            }
+        }
+        results
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, UnifiClient)> for FleetClient {
+    fn from_iter<I: IntoIterator<Item = (S, UnifiClient)>>(iter: I) -> Self {
+        Self {
+            controllers: iter
+                .into_iter()
+                .map(|(name, client)| (name.into(), client))
+                .collect(),
+        }
+    }
+}
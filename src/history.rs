@@ -0,0 +1,427 @@
+//! Keeping a history of device statistics in SQLite.
+//!
+//! The API only serves the latest statistics of each device. A `HistoryStore` records
+//! samples in a SQLite database, one row per device and heartbeat, and answers range and
+//! downsampled queries over them. A `HistoryRecorder` samples every device of a site on an
+//! interval in the background:
+//!
+//! ```rust,no_run
+//! # async fn example(client: unifi_rs::UnifiClient, site_id: unifi_rs::models::id::SiteId, device_id: unifi_rs::models::id::DeviceId) -> Result<(), unifi_rs::UnifiError> {
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use unifi_rs::history::{HistoryRecorder, HistoryStore};
+//!
+//! let store = Arc::new(HistoryStore::open("unifi-history.sqlite")?);
+//! let _recorder = HistoryRecorder::spawn(client, site_id, store.clone(), Duration::from_secs(60));
+//!
+//! let now = chrono::Utc::now();
+//! let hourly = store.downsample(device_id, now - chrono::Duration::days(1)..now, Duration::from_secs(3600))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only available with the `history` feature.
+
+use crate::alerts::Metric;
+use crate::client::UnifiClient;
+use crate::errors::UnifiError;
+use crate::models::id::{DeviceId, SiteId};
+use crate::models::statistics::DeviceStatistics;
+use chrono::{DateTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS device_statistics (
+    site_id TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    sampled_at INTEGER NOT NULL,
+    uptime_sec INTEGER NOT NULL,
+    cpu_utilization_pct REAL,
+    memory_utilization_pct REAL,
+    load_average_1min REAL,
+    load_average_5min REAL,
+    load_average_15min REAL,
+    uplink_tx_rate_bps INTEGER,
+    uplink_rx_rate_bps INTEGER,
+    tx_retries_pct REAL,
+    PRIMARY KEY (device_id, sampled_at)
+);
+";
+
+/// One recorded statistics sample of a device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub site_id: SiteId,
+    pub device_id: DeviceId,
+    /// The device's last heartbeat when the sample was taken.
+    pub sampled_at: DateTime<Utc>,
+    pub uptime_sec: i64,
+    pub cpu_utilization_pct: Option<f64>,
+    pub memory_utilization_pct: Option<f64>,
+    pub load_average_1min: Option<f64>,
+    pub load_average_5min: Option<f64>,
+    pub load_average_15min: Option<f64>,
+    pub uplink_tx_rate_bps: Option<i64>,
+    pub uplink_rx_rate_bps: Option<i64>,
+    /// The highest transmit retry percentage of the device's radios.
+    pub tx_retries_pct: Option<f64>,
+}
+
+/// The averages of the samples of a device in one time bucket.
+///
+/// Averages skip samples that lack the value and are `None` if all of them do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    /// The start of the bucket; buckets are aligned to multiples of their width since the
+    /// Unix epoch.
+    pub start: DateTime<Utc>,
+    /// How many samples fell into the bucket.
+    pub samples: u32,
+    pub cpu_utilization_pct: Option<f64>,
+    pub memory_utilization_pct: Option<f64>,
+    pub load_average_1min: Option<f64>,
+    pub uplink_tx_rate_bps: Option<f64>,
+    pub uplink_rx_rate_bps: Option<f64>,
+    pub tx_retries_pct: Option<f64>,
+}
+
+/// A SQLite database of device statistics samples.
+///
+/// Calls block on SQLite; they are quick for the row counts a controller produces, but
+/// can be moved to `tokio::task::spawn_blocking` if needed.
+pub struct HistoryStore {
+    connection: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// Opens or creates the database at `path`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `HistoryStore`, or `UnifiError::Storage` if the database
+    /// cannot be opened or its table created.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, UnifiError> {
+        Self::init(Connection::open(path).map_err(storage_error)?)
+    }
+
+    /// Opens a database held in memory, lost when the store is dropped.
+    pub fn open_in_memory() -> Result<Self, UnifiError> {
+        Self::init(Connection::open_in_memory().map_err(storage_error)?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, UnifiError> {
+        connection.execute_batch(SCHEMA).map_err(storage_error)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Records a statistics sample of a device.
+    ///
+    /// Samples are keyed by device and heartbeat, so recording the same heartbeat twice
+    /// keeps the first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing whether the sample was new, or `UnifiError::Storage` on
+    /// failure.
+    pub fn record(
+        &self,
+        site_id: SiteId,
+        device_id: DeviceId,
+        statistics: &DeviceStatistics,
+    ) -> Result<bool, UnifiError> {
+        let connection = self.lock();
+        insert(&connection, site_id, device_id, statistics)
+    }
+
+    /// Records a sample of every device of a site in one transaction, e.g. the result of
+    /// `UnifiClient::get_site_statistics`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing how many samples were new, or `UnifiError::Storage` on failure.
+    pub fn record_site(
+        &self,
+        site_id: SiteId,
+        statistics: &HashMap<DeviceId, DeviceStatistics>,
+    ) -> Result<usize, UnifiError> {
+        let mut connection = self.lock();
+        let transaction = connection.transaction().map_err(storage_error)?;
+        let mut recorded = 0;
+        for (device_id, statistics) in statistics {
+            if insert(&transaction, site_id, *device_id, statistics)? {
+                recorded += 1;
+            }
+        }
+        transaction.commit().map_err(storage_error)?;
+        Ok(recorded)
+    }
+
+    /// Fetches the latest statistics of every device of a site and records them.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing how many samples were new, or a `UnifiError` if fetching or
+    /// recording failed.
+    pub async fn sample_site(
+        &self,
+        client: &UnifiClient,
+        site_id: SiteId,
+    ) -> Result<usize, UnifiError> {
+        let statistics = client.get_site_statistics(site_id).await?;
+        self.record_site(site_id, &statistics)
+    }
+
+    /// Returns the samples of a device taken within `range`, oldest first.
+    pub fn samples(
+        &self,
+        device_id: DeviceId,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Sample>, UnifiError> {
+        let connection = self.lock();
+        let mut statement = connection
+            .prepare_cached(
+                "SELECT site_id, device_id, sampled_at, uptime_sec, cpu_utilization_pct,
+                        memory_utilization_pct, load_average_1min, load_average_5min,
+                        load_average_15min, uplink_tx_rate_bps, uplink_rx_rate_bps,
+                        tx_retries_pct
+                 FROM device_statistics
+                 WHERE device_id = ?1 AND sampled_at >= ?2 AND sampled_at < ?3
+                 ORDER BY sampled_at",
+            )
+            .map_err(storage_error)?;
+        let rows = statement
+            .query_map(
+                params![
+                    device_id.to_string(),
+                    range.start.timestamp_millis(),
+                    range.end.timestamp_millis()
+                ],
+                sample_from_row,
+            )
+            .map_err(storage_error)?;
+        rows.map(|row| row.map_err(storage_error)).collect()
+    }
+
+    /// Averages the samples of a device within `range` into buckets of `width`.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_id` - The device whose samples to average.
+    /// * `range` - The time range; buckets overlapping its ends only cover samples inside it.
+    /// * `width` - The width of each bucket, at least one millisecond.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the non-empty buckets, oldest first, or a `UnifiError` on failure.
+    pub fn downsample(
+        &self,
+        device_id: DeviceId,
+        range: Range<DateTime<Utc>>,
+        width: Duration,
+    ) -> Result<Vec<Bucket>, UnifiError> {
+        let width_ms = i64::try_from(width.as_millis()).unwrap_or(i64::MAX);
+        if width_ms == 0 {
+            return Err(UnifiError::InvalidInput(
+                "the bucket width must be at least one millisecond".to_string(),
+            ));
+        }
+
+        let connection = self.lock();
+        let mut statement = connection
+            .prepare_cached(
+                "SELECT (sampled_at / ?4) * ?4 AS bucket, COUNT(*), AVG(cpu_utilization_pct),
+                        AVG(memory_utilization_pct), AVG(load_average_1min),
+                        AVG(uplink_tx_rate_bps), AVG(uplink_rx_rate_bps), AVG(tx_retries_pct)
+                 FROM device_statistics
+                 WHERE device_id = ?1 AND sampled_at >= ?2 AND sampled_at < ?3
+                 GROUP BY bucket
+                 ORDER BY bucket",
+            )
+            .map_err(storage_error)?;
+        let rows = statement
+            .query_map(
+                params![
+                    device_id.to_string(),
+                    range.start.timestamp_millis(),
+                    range.end.timestamp_millis(),
+                    width_ms
+                ],
+                |row| {
+                    Ok(Bucket {
+                        start: timestamp(row.get(0)?),
+                        samples: row.get(1)?,
+                        cpu_utilization_pct: row.get(2)?,
+                        memory_utilization_pct: row.get(3)?,
+                        load_average_1min: row.get(4)?,
+                        uplink_tx_rate_bps: row.get(5)?,
+                        uplink_rx_rate_bps: row.get(6)?,
+                        tx_retries_pct: row.get(7)?,
+                    })
+                },
+            )
+            .map_err(storage_error)?;
+        rows.map(|row| row.map_err(storage_error)).collect()
+    }
+
+    /// Returns the most recent sample of a device, if any.
+    pub fn latest(&self, device_id: DeviceId) -> Result<Option<Sample>, UnifiError> {
+        let connection = self.lock();
+        connection
+            .query_row(
+                "SELECT site_id, device_id, sampled_at, uptime_sec, cpu_utilization_pct,
+                        memory_utilization_pct, load_average_1min, load_average_5min,
+                        load_average_15min, uplink_tx_rate_bps, uplink_rx_rate_bps,
+                        tx_retries_pct
+                 FROM device_statistics
+                 WHERE device_id = ?1
+                 ORDER BY sampled_at DESC
+                 LIMIT 1",
+                params![device_id.to_string()],
+                sample_from_row,
+            )
+            .optional()
+            .map_err(storage_error)
+    }
+
+    /// Deletes the samples taken before `cutoff`, returning how many were deleted.
+    pub fn prune(&self, cutoff: DateTime<Utc>) -> Result<usize, UnifiError> {
+        self.lock()
+            .execute(
+                "DELETE FROM device_statistics WHERE sampled_at < ?1",
+                params![cutoff.timestamp_millis()],
+            )
+            .map_err(storage_error)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Records the statistics of every device of a site on an interval, in the background.
+///
+/// A failed poll is skipped and retried on the next tick. The background task is stopped
+/// when the `HistoryRecorder` is dropped.
+pub struct HistoryRecorder {
+    task: JoinHandle<()>,
+}
+
+impl HistoryRecorder {
+    /// Starts sampling `site_id` every `interval`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client used to fetch statistics.
+    /// * `site_id` - The id of the site to sample.
+    /// * `store` - The store to record samples in.
+    /// * `interval` - The delay between samples.
+    ///
+    /// # Returns
+    ///
+    /// The running `HistoryRecorder`.
+    pub fn spawn(
+        client: UnifiClient,
+        site_id: SiteId,
+        store: Arc<HistoryStore>,
+        interval: Duration,
+    ) -> Self {
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let _ = store.sample_site(&client, site_id).await;
+            }
+        });
+        Self { task }
+    }
+}
+
+impl Drop for HistoryRecorder {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn insert(
+    connection: &Connection,
+    site_id: SiteId,
+    device_id: DeviceId,
+    statistics: &DeviceStatistics,
+) -> Result<bool, UnifiError> {
+    let mut statement = connection
+        .prepare_cached(
+            "INSERT OR IGNORE INTO device_statistics (
+                 site_id, device_id, sampled_at, uptime_sec, cpu_utilization_pct,
+                 memory_utilization_pct, load_average_1min, load_average_5min,
+                 load_average_15min, uplink_tx_rate_bps, uplink_rx_rate_bps, tx_retries_pct
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        )
+        .map_err(storage_error)?;
+    let uplink = statistics.uplink.as_ref();
+    let inserted = statement
+        .execute(params![
+            site_id.to_string(),
+            device_id.to_string(),
+            statistics.last_heartbeat_at.timestamp_millis(),
+            statistics.uptime_sec,
+            statistics.cpu_utilization_pct,
+            statistics.memory_utilization_pct,
+            statistics.load_average_1min,
+            statistics.load_average_5min,
+            statistics.load_average_15min,
+            uplink.map(|uplink| uplink.tx_rate_bps),
+            uplink.map(|uplink| uplink.rx_rate_bps),
+            Metric::TxRetriesPct.value(statistics),
+        ])
+        .map_err(storage_error)?;
+    Ok(inserted > 0)
+}
+
+fn sample_from_row(row: &Row<'_>) -> rusqlite::Result<Sample> {
+    Ok(Sample {
+        site_id: id(row, 0)?,
+        device_id: id(row, 1)?,
+        sampled_at: timestamp(row.get(2)?),
+        uptime_sec: row.get(3)?,
+        cpu_utilization_pct: row.get(4)?,
+        memory_utilization_pct: row.get(5)?,
+        load_average_1min: row.get(6)?,
+        load_average_5min: row.get(7)?,
+        load_average_15min: row.get(8)?,
+        uplink_tx_rate_bps: row.get(9)?,
+        uplink_rx_rate_bps: row.get(10)?,
+        tx_retries_pct: row.get(11)?,
+    })
+}
+
+/// Reads an id stored as text.
+fn id<T>(row: &Row<'_>, index: usize) -> rusqlite::Result<T>
+where
+    T: FromStr<Err = uuid::Error>,
+{
+    let text: String = row.get(index)?;
+    text.parse()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+fn timestamp(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+fn storage_error(error: rusqlite::Error) -> UnifiError {
+    UnifiError::Storage(error.to_string())
+}
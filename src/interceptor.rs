@@ -0,0 +1,32 @@
+//! Hooks that observe or modify every request sent by `UnifiClient`.
+
+use crate::errors::UnifiError;
+use futures::future::BoxFuture;
+use reqwest::{Request, Response};
+
+/// A hook run around every request a `UnifiClient` sends, e.g. to add custom auth
+/// headers, write an audit log or record metrics.
+///
+/// Interceptors are registered with `UnifiClientBuilder::interceptor` and run in
+/// registration order. They see each attempt separately, so a retried request passes
+/// through `before_request` and `after_response` once per attempt. Both methods default
+/// to doing nothing.
+pub trait Interceptor: Send + Sync {
+    /// Called with the fully built request just before it is sent, after the headers of
+    /// `RequestOptions` are applied and before the request signer runs.
+    ///
+    /// Returning an error aborts the request with that error.
+    fn before_request<'a>(
+        &'a self,
+        request: &'a mut Request,
+    ) -> BoxFuture<'a, Result<(), UnifiError>> {
+        let _ = request;
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Called with every response received, whatever its status, before it is parsed.
+    fn after_response<'a>(&'a self, response: &'a Response) -> BoxFuture<'a, ()> {
+        let _ = response;
+        Box::pin(async {})
+    }
+}
@@ -0,0 +1,323 @@
+//! Client for the classic controller API (`/proxy/network/api/s/{site}/...`).
+//!
+//! The classic API predates API keys and authenticates with a username and password:
+//! `login` stores the session cookie and the CSRF token that must accompany every
+//! mutating request. Many features that the Network integration API does not expose
+//! yet, such as statistics history, firewall rules and WLAN configuration, are only
+//! available here. Only available with the `legacy` feature.
+
+use crate::client::parse_body;
+use crate::errors::UnifiError;
+use reqwest::{header, Client, ClientBuilder, Method, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+const CSRF_HEADER: &str = "X-CSRF-Token";
+const UPDATED_CSRF_HEADER: &str = "X-Updated-CSRF-Token";
+
+/// A builder for creating a `LegacyClient`.
+pub struct LegacyClientBuilder {
+    base_url: String,
+    username: Option<String>,
+    password: Option<String>,
+    verify_ssl: bool,
+}
+
+impl LegacyClientBuilder {
+    /// Creates a builder for the console at `base_url`, e.g. `https://192.168.1.1`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            username: None,
+            password: None,
+            verify_ssl: true,
+        }
+    }
+
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    pub fn verify_ssl(mut self, verify: bool) -> Self {
+        self.verify_ssl = verify;
+        self
+    }
+
+    pub fn build(self) -> Result<LegacyClient, UnifiError> {
+        let username = self
+            .username
+            .ok_or_else(|| UnifiError::Config("username is required".to_string()))?;
+        let password = self
+            .password
+            .ok_or_else(|| UnifiError::Config("password is required".to_string()))?;
+
+        let client = ClientBuilder::new()
+            .cookie_store(true)
+            .danger_accept_invalid_certs(!self.verify_ssl)
+            .build()?;
+
+        Ok(LegacyClient {
+            client,
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            username,
+            password,
+            session: RwLock::new(None),
+        })
+    }
+}
+
+/// A client for the classic controller API using cookie and CSRF-token authentication.
+///
+/// Requests log in automatically on first use and once more if the session expires.
+pub struct LegacyClient {
+    client: Client,
+    base_url: String,
+    username: String,
+    password: String,
+    session: RwLock<Option<Session>>,
+}
+
+/// The state of a logged-in session; the session cookie itself lives in the cookie store.
+#[derive(Debug, Clone)]
+struct Session {
+    csrf_token: Option<header::HeaderValue>,
+}
+
+#[derive(Debug, Serialize)]
+struct LoginRequest<'a> {
+    username: &'a str,
+    password: &'a str,
+}
+
+/// The envelope wrapping every classic API response.
+#[derive(Debug, Deserialize)]
+struct LegacyResponse<T> {
+    meta: LegacyMeta,
+    #[serde(default = "Vec::new")]
+    data: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyMeta {
+    rc: String,
+    #[serde(default)]
+    msg: Option<String>,
+}
+
+impl LegacyClient {
+    /// Logs in with the configured credentials, storing the session cookie and CSRF token.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or a `UnifiError` if the credentials are rejected.
+    pub async fn login(&self) -> Result<(), UnifiError> {
+        let path = "/api/auth/login";
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .post(&url)
+            .json(&LoginRequest {
+                username: &self.username,
+                password: &self.password,
+            })
+            .send()
+            .await
+            .map_err(|error| UnifiError::from(error).with_request(Method::POST, path))?;
+
+        if !response.status().is_success() {
+            return Err(UnifiError::from_status(
+                response.status().as_u16(),
+                response.url().path(),
+                "login failed".to_string(),
+            )
+            .with_request(Method::POST, path));
+        }
+        *self.session.write().await = Some(Session {
+            csrf_token: response.headers().get(CSRF_HEADER).cloned(),
+        });
+        Ok(())
+    }
+
+    /// Ends the current session.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or a `UnifiError` on failure.
+    pub async fn logout(&self) -> Result<(), UnifiError> {
+        let path = "/api/auth/logout";
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .send(Method::POST, &url, None::<&()>)
+            .await
+            .map_err(|error| error.with_request(Method::POST, path))?;
+        *self.session.write().await = None;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(UnifiError::from_status(
+                response.status().as_u16(),
+                response.url().path(),
+                "logout failed".to_string(),
+            )
+            .with_request(Method::POST, path))
+        }
+    }
+
+    /// Sends a GET request to a classic site endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `site` - The site's short name, e.g. `"default"`.
+    /// * `path` - The endpoint below the site, e.g. `"rest/wlanconf"` or `"stat/health"`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the response's `data` array on success, or a `UnifiError` on failure.
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        site: &str,
+        path: &str,
+    ) -> Result<Vec<T>, UnifiError> {
+        self.request(Method::GET, site, path, None::<&()>).await
+    }
+
+    /// Sends a POST request with a JSON body to a classic site endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `site` - The site's short name, e.g. `"default"`.
+    /// * `path` - The endpoint below the site, e.g. `"cmd/devmgr"`.
+    /// * `body` - The request body.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the response's `data` array on success, or a `UnifiError` on failure.
+    pub async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        site: &str,
+        path: &str,
+        body: &B,
+    ) -> Result<Vec<T>, UnifiError> {
+        self.request(Method::POST, site, path, Some(body)).await
+    }
+
+    /// Lists the WLAN configurations of a site.
+    ///
+    /// # Arguments
+    ///
+    /// * `site` - The site's short name, e.g. `"default"`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the raw WLAN configuration objects, or a `UnifiError` on failure.
+    pub async fn list_wlan_configs(
+        &self,
+        site: &str,
+    ) -> Result<Vec<serde_json::Value>, UnifiError> {
+        self.get(site, "rest/wlanconf").await
+    }
+
+    /// Lists the firewall rules of a site.
+    ///
+    /// # Arguments
+    ///
+    /// * `site` - The site's short name, e.g. `"default"`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the raw firewall rule objects, or a `UnifiError` on failure.
+    pub async fn list_firewall_rules(
+        &self,
+        site: &str,
+    ) -> Result<Vec<serde_json::Value>, UnifiError> {
+        self.get(site, "rest/firewallrule").await
+    }
+
+    /// Sends a request to a classic site endpoint, wrapping errors in `UnifiError::Request`.
+    async fn request<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        site: &str,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Vec<T>, UnifiError> {
+        let path = format!(
+            "/proxy/network/api/s/{}/{}",
+            site,
+            path.trim_start_matches('/')
+        );
+        let url = format!("{}{}", self.base_url, path);
+        self.request_url(method.clone(), &url, body)
+            .await
+            .map_err(|error| error.with_request(method, &path))
+    }
+
+    /// Sends a request to `url`, logging in first if there is no session or it has expired.
+    async fn request_url<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&B>,
+    ) -> Result<Vec<T>, UnifiError> {
+        if self.session.read().await.is_none() {
+            self.login().await?;
+        }
+        let mut response = self.send(method.clone(), url, body).await?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            self.login().await?;
+            response = self.send(method, url, body).await?;
+        }
+
+        let status = response.status();
+        let resource = response.url().path().to_string();
+        let body = response.bytes().await?;
+        let envelope: LegacyResponse<T> = parse_body(status.as_u16(), &body)?;
+        if status.is_success() && envelope.meta.rc == "ok" {
+            Ok(envelope.data)
+        } else {
+            Err(UnifiError::from_status(
+                status.as_u16(),
+                &resource,
+                envelope.meta.msg.unwrap_or(envelope.meta.rc),
+            ))
+        }
+    }
+
+    /// Sends a request with the CSRF token, keeping the token up to date.
+    async fn send<B: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&B>,
+    ) -> Result<Response, UnifiError> {
+        let mut request = self.client.request(method, url);
+        let token = self
+            .session
+            .read()
+            .await
+            .as_ref()
+            .and_then(|session| session.csrf_token.clone());
+        if let Some(token) = token {
+            request = request.header(CSRF_HEADER, token);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await?;
+        if let Some(token) = response.headers().get(UPDATED_CSRF_HEADER) {
+            if let Some(session) = self.session.write().await.as_mut() {
+                session.csrf_token = Some(token.clone());
+            }
+        }
+        Ok(response)
+    }
+}
@@ -17,7 +17,7 @@
 //! # Example
 //!
 //! ```rust,no_run
-//! use unifi_rs::{client::{UnifiClient, UnifiClientBuilder}};
+//! use unifi_rs::{client::{UnifiClient, UnifiClientBuilder}, models::common::ListParams};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -26,15 +26,69 @@
 //!         .verify_ssl(false)
 //!         .build()?;
 //!
-//!     let sites = client.list_sites(None, None).await?;
+//!     let sites = client.list_sites(&ListParams::default()).await?;
 //!     println!("Sites: {:#?}", sites);
 //!     Ok(())
 //! }
 //! ```
 
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+compile_error!("enable the `rustls` or `native-tls` feature to select a TLS backend");
+#[cfg(all(
+    target_arch = "wasm32",
+    any(
+        feature = "events",
+        feature = "legacy",
+        feature = "blocking",
+        feature = "cache",
+        feature = "history"
+    )
+))]
+compile_error!(
+    "the `events`, `legacy`, `blocking`, `cache` and `history` features are not supported on wasm32"
+);
+
+pub mod alerts;
+// The trait requires `Send` futures, which the fetch backend cannot provide.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod bulk;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod capabilities;
+#[cfg(feature = "test-util")]
+pub mod cassette;
 pub mod client;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod credentials;
 pub mod errors;
+#[cfg(feature = "events")]
+pub mod events;
+pub mod export;
+pub mod filter;
+pub mod fleet;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod interceptor;
+#[cfg(feature = "legacy")]
+pub mod legacy;
 pub mod models;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod notify;
+pub mod retry;
+pub mod site_handle;
+pub mod site_manager;
+pub mod snapshot;
+pub mod telemetry;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod time;
+pub mod topology;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watcher;
 
 pub use client::*;
 pub use errors::*;
@@ -42,10 +96,126 @@ pub use models::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::client::{ErrorResponse, UnifiClientBuilder};
-    use crate::models::client::ClientOverview;
-    use crate::models::device::DeviceDetails;
-    use crate::models::statistics::DeviceStatistics;
+    use crate::client::{ErrorResponse, RequestOptions, UnifiClientBuilder};
+    use crate::errors::UnifiError;
+    use crate::filter::Filter;
+    use crate::models::client::{
+        AuthorizeGuestRequest, ClientAccessType, ClientDetails, ClientOverview,
+    };
+    use crate::models::common::{
+        mask_ip, redact_mac, ApplicationVersion, ConnectorType, FrequencyBand, ListParams, Page,
+        PortState, WlanStandard,
+    };
+    use crate::models::device::{DeviceAction, DeviceDetails, DeviceOverview, DeviceState};
+    use crate::models::health::{ControllerHealth, HealthStatus};
+    use crate::models::id::{ClientId, DeviceId, SiteId};
+    use crate::models::site::SiteOverview;
+    use crate::models::statistics::{DeviceStatistics, DeviceUplinkStatistics};
+    use crate::models::voucher::VoucherCreateRequest;
+    use crate::retry::RetryPolicy;
+    use crate::snapshot::{DeviceChange, SiteDiff, SiteSnapshot};
+    use crate::topology::{topology_to_dot, TopologyNode};
+    use crate::watcher::{events_from_diff, WatchEvent, Watcher};
+    use std::net::IpAddr;
+    use std::time::Duration;
+    use uuid::Uuid;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SAMPLE_STATISTICS_JSON: &str = r#"{
+        "uptimeSec": 737201,
+        "lastHeartbeatAt": "2025-01-18T20:26:02Z",
+        "nextHeartbeatAt": "2025-01-18T20:26:07Z",
+        "loadAverage1Min": 1.65,
+        "loadAverage5Min": 1.28,
+        "loadAverage15Min": 1.3,
+        "cpuUtilizationPct": 30.8,
+        "memoryUtilizationPct": 74.2,
+        "uplink": {
+            "txRateBps": 309720,
+            "rxRateBps": 32288
+        },
+        "interfaces": {
+            "radios": [
+                {
+                    "frequencyGHz": 2.4,
+                    "txRetriesPct": 14.3
+                },
+                {
+                    "frequencyGHz": 5,
+                    "txRetriesPct": 0
+                }
+            ]
+        }
+    }"#;
+
+    fn mock_client(server: &MockServer) -> crate::client::UnifiClient {
+        UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .build()
+            .unwrap()
+    }
+
+    fn device_overview_json(id: impl Into<Uuid>, name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id.into(),
+            "name": name,
+            "model": "UHDIW",
+            "macAddress": "00:11:22:33:44:55",
+            "ipAddress": "192.168.1.1",
+            "state": "ONLINE",
+            "features": [],
+            "interfaces": []
+        })
+    }
+
+    fn wireless_client_json(uplink: impl Into<Uuid>, band: Option<&str>) -> serde_json::Value {
+        let mut client = serde_json::json!({
+            "type": "WIRELESS",
+            "id": Uuid::new_v4(),
+            "name": "Phone",
+            "connectedAt": "2025-01-18T12:00:00Z",
+            "ipAddress": "192.168.1.50",
+            "macAddress": "aa:bb:cc:dd:ee:ff",
+            "uplinkDeviceId": uplink.into()
+        });
+        if let Some(band) = band {
+            client["band"] = serde_json::json!(band);
+        }
+        client
+    }
+
+    fn page_json(data: Vec<serde_json::Value>) -> serde_json::Value {
+        serde_json::json!({
+            "offset": 0,
+            "limit": 100,
+            "count": data.len(),
+            "totalCount": data.len(),
+            "data": data
+        })
+    }
+
+    fn device_details_json(id: impl Into<Uuid>, state: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id.into(),
+            "name": "Test Device",
+            "model": "UHDIW",
+            "supported": true,
+            "macAddress": "00:11:22:33:44:55",
+            "ipAddress": "192.168.1.1",
+            "state": state,
+            "firmwareVersion": "6.6.55",
+            "firmwareUpdatable": false,
+            "adoptedAt": "2025-01-18T12:00:00Z",
+            "provisionedAt": "2025-01-18T12:00:00Z",
+            "configurationId": "test123",
+            "interfaces": {
+                "ports": [],
+                "radios": []
+            }
+        })
+    }
+
     #[tokio::test]
     async fn test_client_builder() {
         let client = UnifiClientBuilder::new("https://example.com")
@@ -125,34 +295,41 @@ mod tests {
         assert_eq!(error.message, "Unauthorized access");
     }
 
+    #[test]
+    fn test_switch_port_statistics_deserialization() {
+        let stats: DeviceStatistics = serde_json::from_value(serde_json::json!({
+            "uptimeSec": 1000,
+            "lastHeartbeatAt": "2025-01-18T20:26:02Z",
+            "nextHeartbeatAt": "2025-01-18T20:26:07Z",
+            "interfaces": {
+                "ports": [
+                    {
+                        "idx": 1,
+                        "txBytes": 1200,
+                        "rxBytes": 3400,
+                        "txPackets": 10,
+                        "rxPackets": 20,
+                        "txErrors": 0,
+                        "rxErrors": 2,
+                        "poePowerW": 4.5
+                    },
+                    { "idx": 2 }
+                ]
+            }
+        }))
+        .unwrap();
+
+        let interfaces = stats.interfaces.unwrap();
+        assert!(interfaces.radios.is_empty());
+        assert_eq!(interfaces.ports.len(), 2);
+        assert_eq!(interfaces.ports[0].rx_errors, Some(2));
+        assert_eq!(interfaces.ports[0].poe_power_w, Some(4.5));
+        assert_eq!(interfaces.ports[1].tx_bytes, None);
+    }
+
     #[tokio::test]
     async fn test_device_statistics_deserialization() {
-        let stats_json = r#"{
-        "uptimeSec": 737201,
-        "lastHeartbeatAt": "2025-01-18T20:26:02Z",
-        "nextHeartbeatAt": "2025-01-18T20:26:07Z",
-        "loadAverage1Min": 1.65,
-        "loadAverage5Min": 1.28,
-        "loadAverage15Min": 1.3,
-        "cpuUtilizationPct": 30.8,
-        "memoryUtilizationPct": 74.2,
-        "uplink": {
-            "txRateBps": 309720,
-            "rxRateBps": 32288
-        },
-        "interfaces": {
-            "radios": [
-                {
-                    "frequencyGHz": 2.4,
-                    "txRetriesPct": 14.3
-                },
-                {
-                    "frequencyGHz": 5,
-                    "txRetriesPct": 0
-                }
-            ]
-        }
-    }"#;
+        let stats_json = SAMPLE_STATISTICS_JSON;
 
         let stats: DeviceStatistics = match serde_json::from_str(stats_json) {
             Ok(stats) => stats,
@@ -190,4 +367,4970 @@ mod tests {
             "radio_1 frequency_ghz is None"
         );
     }
+
+    #[tokio::test]
+    async fn test_wait_for_updates_complete() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let fast = DeviceId::new(Uuid::new_v4());
+        let slow = DeviceId::new(Uuid::new_v4());
+
+        for (device_id, updating_polls) in [(fast, 1), (slow, 3)] {
+            let device_path = format!("/v1/sites/{}/devices/{}", site_id, device_id);
+            // The slow device is still online for one poll before its upgrade starts.
+            if device_id == slow {
+                Mock::given(method("GET"))
+                    .and(path(device_path.clone()))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .set_body_json(device_details_json(device_id, "ONLINE")),
+                    )
+                    .up_to_n_times(1)
+                    .mount(&server)
+                    .await;
+            }
+            Mock::given(method("GET"))
+                .and(path(device_path.clone()))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(device_details_json(device_id, "UPDATING")),
+                )
+                .up_to_n_times(updating_polls)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(device_path))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(device_details_json(device_id, "ONLINE")),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let results = client
+            .wait_for_updates_complete(
+                site_id,
+                &[fast, slow],
+                Duration::from_secs(5),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(results, vec![(fast, true), (slow, true)]);
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(
+            requests.len(),
+            7,
+            "each device should stop being polled once online after updating"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_updates_complete_timeout() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(device_details_json(device_id, "UPDATING")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let results = client
+            .wait_for_updates_complete(
+                site_id,
+                &[device_id],
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(results, vec![(device_id, false)]);
+    }
+
+    #[test]
+    fn test_page_require_non_empty() {
+        let empty: Page<SiteOverview> = Page {
+            offset: 0,
+            limit: 25,
+            count: 0,
+            total_count: 0,
+            data: vec![],
+            skipped: 0,
+        };
+        match empty.require_non_empty("sites") {
+            Err(UnifiError::NotFound { message, .. }) => assert_eq!(message, "no sites returned"),
+            other => panic!("Expected NotFound, got {:?}", other),
+        }
+
+        let page = Page {
+            offset: 0,
+            limit: 25,
+            count: 1,
+            total_count: 1,
+            data: vec![SiteOverview {
+                id: SiteId::new(Uuid::new_v4()),
+                name: Some("Default".to_string()),
+                #[cfg(feature = "raw-fields")]
+                extra: Default::default(),
+            }],
+            skipped: 0,
+        };
+        let page = page.require_non_empty("sites").unwrap();
+        assert_eq!(page.data.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_with_accept() {
+        let server = MockServer::start().await;
+        let csv = "id,name\n1,Default\n";
+
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .and(header("accept", "text/csv"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(csv))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let body = client
+            .get_with_accept("/v1/sites", "text/csv")
+            .await
+            .unwrap();
+
+        assert_eq!(&body[..], csv.as_bytes());
+    }
+
+    #[test]
+    fn test_application_version_parsing() {
+        let version: ApplicationVersion = "9.0.108".parse().unwrap();
+        assert_eq!(version, ApplicationVersion::new(9, 0, 108));
+        assert_eq!(
+            "8.6-beta".parse::<ApplicationVersion>().unwrap(),
+            ApplicationVersion::new(8, 6, 0)
+        );
+        assert!(version > ApplicationVersion::new(8, 6, 9));
+        assert!("not-a-version".parse::<ApplicationVersion>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_require_version_blocks_old_controller() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "8.6.9" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/statistics/latest",
+                site_id, device_id
+            )))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .require_version(true)
+            .build()
+            .unwrap();
+
+        for _ in 0..2 {
+            match client.get_device_statistics(site_id, device_id).await {
+                Err(UnifiError::Config(message)) => assert!(message.contains("8.6.9")),
+                other => panic!("Expected Config error, got {:?}", other),
+            }
+        }
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/hotspot/vouchers", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        match client.list_vouchers(site_id, &ListParams::default()).await {
+            Err(UnifiError::Config(message)) => assert!(message.contains("for vouchers")),
+            other => panic!("Expected Config error, got {:?}", other),
+        }
+        match client.power_cycle_port(site_id, device_id, 1).await {
+            Err(UnifiError::Config(message)) => assert!(message.contains("for port actions")),
+            other => panic!("Expected Config error, got {:?}", other),
+        }
+        match client.adopt_device(site_id, device_id).await {
+            Err(UnifiError::Config(message)) => assert!(message.contains("for device adoption")),
+            other => panic!("Expected Config error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_devices_with_uplink_names() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let gateway = DeviceId::new(Uuid::new_v4());
+        let switch = DeviceId::new(Uuid::new_v4());
+        let access_point = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(gateway, "Gateway"),
+                device_overview_json(switch, "Switch"),
+                device_overview_json(access_point, "Access Point"),
+            ])))
+            .mount(&server)
+            .await;
+
+        for (device_id, uplink) in [
+            (gateway, None),
+            (switch, Some(gateway)),
+            (access_point, Some(switch)),
+        ] {
+            let mut details = device_details_json(device_id, "ONLINE");
+            if let Some(uplink) = uplink {
+                details["uplink"] = serde_json::json!({ "deviceId": uplink });
+            }
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(details))
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let devices = client.devices_with_uplink_names(site_id).await.unwrap();
+
+        let resolved: Vec<(DeviceId, Option<String>)> = devices
+            .into_iter()
+            .map(|(device, uplink_name)| (device.id, uplink_name))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                (gateway, None),
+                (switch, Some("Gateway".to_string())),
+                (access_point, Some("Switch".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_redact_helpers() {
+        assert_eq!(redact_mac("00:11:22:33:44:55"), "**:**:**:**:44:55");
+        assert_eq!(redact_mac("00-11-22-33-44-55"), "**:**:**:**:44:55");
+        assert_eq!(redact_mac("garbage"), "*******");
+        assert_eq!(
+            mask_ip("192.168.1.100".parse().unwrap()),
+            "192.168.1.0".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            mask_ip("2001:db8:0:1::42".parse().unwrap()),
+            "2001:db8:0:1::".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_redacted_models() {
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let details: DeviceDetails =
+            serde_json::from_value(device_details_json(device_id, "ONLINE")).unwrap();
+        let redacted = details.redacted();
+        assert_eq!(redacted.id, device_id);
+        assert_eq!(redacted.name, "Test Device");
+        assert_eq!(redacted.mac_address, "**:**:**:**:44:55");
+        assert_eq!(redacted.ip_address, "192.168.1.0".parse().ok());
+
+        let client: ClientOverview = serde_json::from_value(serde_json::json!({
+            "type": "WIRELESS",
+            "id": "123e4567-e89b-12d3-a456-426614174000",
+            "name": "Phone",
+            "connectedAt": "2025-01-18T12:00:00Z",
+            "ipAddress": "10.0.5.23",
+            "macAddress": "aa:bb:cc:dd:ee:ff",
+            "uplinkDeviceId": "123e4567-e89b-12d3-a456-426614174001"
+        }))
+        .unwrap();
+        match client.redacted() {
+            ClientOverview::Wireless(c) => {
+                assert_eq!(c.base.name.as_deref(), Some("Phone"));
+                assert_eq!(c.base.ip_address, "10.0.5.0".parse().ok());
+                assert_eq!(c.mac_address, "**:**:**:**:ee:ff");
+            }
+            _ => panic!("Expected Wireless client"),
+        }
+    }
+
+    #[cfg(all(unix, feature = "unix-socket"))]
+    #[tokio::test]
+    async fn test_unix_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::UnixListener;
+
+        let socket_path = std::env::temp_dir().join(format!("unifi-rs-{}.sock", Uuid::new_v4()));
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            let body = r#"{"applicationVersion":"9.0.108"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+
+        let client = UnifiClientBuilder::new("http://localhost/proxy/network/integrations")
+            .api_key("test-key")
+            .unix_socket(&socket_path)
+            .build()
+            .unwrap();
+        let info = client.get_info().await.unwrap();
+        let request = server.await.unwrap();
+        std::fs::remove_file(&socket_path).ok();
+
+        assert_eq!(info.application_version, "9.0.108");
+        assert!(request.starts_with("GET /proxy/network/integrations/v1/info "));
+    }
+
+    #[tokio::test]
+    async fn test_sites_cached_until_refresh() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+
+        let client = mock_client(&server);
+        {
+            let _guard = Mock::given(method("GET"))
+                .and(path("/v1/sites"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                    serde_json::json!({ "id": site_id, "name": "Default" }),
+                ])))
+                .expect(1)
+                .mount_as_scoped(&server)
+                .await;
+
+            let first = client.sites_cached().await.unwrap();
+            let second = client.sites_cached().await.unwrap();
+            assert_eq!(first.len(), 1);
+            assert_eq!(second[0].name.as_deref(), Some("Default"));
+        }
+
+        let _guard = Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": site_id, "name": "Renamed" }),
+            ])))
+            .expect(1)
+            .mount_as_scoped(&server)
+            .await;
+
+        let refreshed = client.refresh_sites().await.unwrap();
+        assert_eq!(refreshed[0].name.as_deref(), Some("Renamed"));
+        let cached = client.sites_cached().await.unwrap();
+        assert_eq!(cached[0].name.as_deref(), Some("Renamed"));
+    }
+
+    #[tokio::test]
+    async fn test_deserialization_error_context() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        let mut details = device_details_json(device_id, "ONLINE");
+        details["id"] = serde_json::json!("");
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(details))
+            .mount(&server)
+            .await;
+
+        let mut overview = device_overview_json(device_id, "Broken");
+        overview["id"] = serde_json::json!("");
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(Uuid::new_v4(), "Fine"),
+                overview,
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        match client
+            .get_device_details(site_id, device_id)
+            .await
+            .map_err(UnifiError::into_inner)
+        {
+            Err(UnifiError::Deserialization { path, value, .. }) => {
+                assert_eq!(path, "id");
+                assert_eq!(value, "\"\"");
+            }
+            other => panic!("Expected Deserialization error, got {:?}", other),
+        }
+
+        match client.list_devices(site_id, &ListParams::default()).await {
+            Err(err) if matches!(err.inner(), UnifiError::Deserialization { .. }) => {
+                let message = err.to_string();
+                assert!(message.contains("data[1].id"), "{}", message);
+                assert!(message.contains("value: \"\""), "{}", message);
+            }
+            other => panic!("Expected Deserialization error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_wireless_client_band_deserialization() {
+        let client: ClientOverview =
+            serde_json::from_value(wireless_client_json(Uuid::new_v4(), Some("6"))).unwrap();
+        match client {
+            ClientOverview::Wireless(c) => assert_eq!(c.band, Some(FrequencyBand::Band6GHz)),
+            _ => panic!("Expected Wireless client"),
+        }
+
+        let client: ClientOverview =
+            serde_json::from_value(wireless_client_json(Uuid::new_v4(), None)).unwrap();
+        match client {
+            ClientOverview::Wireless(c) => assert_eq!(c.band, None),
+            _ => panic!("Expected Wireless client"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wireless_band_distribution() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let single_band_ap = DeviceId::new(Uuid::new_v4());
+        let dual_band_ap = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                wireless_client_json(dual_band_ap, Some("5")),
+                wireless_client_json(dual_band_ap, Some("5")),
+                wireless_client_json(single_band_ap, None),
+                wireless_client_json(dual_band_ap, None),
+            ])))
+            .mount(&server)
+            .await;
+
+        for (device_id, bands) in [(single_band_ap, vec![2.4]), (dual_band_ap, vec![2.4, 5.0])] {
+            let mut details = device_details_json(device_id, "ONLINE");
+            details["interfaces"]["radios"] = bands
+                .into_iter()
+                .map(|band| serde_json::json!({ "frequencyGHz": band }))
+                .collect();
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(details))
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let distribution = client.wireless_band_distribution(site_id).await.unwrap();
+
+        assert_eq!(distribution.len(), 2);
+        assert_eq!(distribution[&FrequencyBand::Band5GHz], 2);
+        assert_eq!(distribution[&FrequencyBand::Band2_4GHz], 1);
+    }
+
+    #[tokio::test]
+    async fn test_sign_request_hook() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/actions",
+                site_id, device_id
+            )))
+            .and(header(
+                "x-signature",
+                "POST /v1/sites/{site}/devices/{device}/actions",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .sign_request(move |request, context| {
+                assert_eq!(&context.body[..], br#"{"action":"RESTART"}"#);
+                let signature = format!(
+                    "{} {}",
+                    context.method,
+                    context
+                        .path
+                        .replace(&site_id.to_string(), "{site}")
+                        .replace(&device_id.to_string(), "{device}")
+                );
+                request
+                    .headers_mut()
+                    .insert("X-Signature", signature.parse().unwrap());
+            })
+            .build()
+            .unwrap();
+
+        client.restart_device(site_id, device_id).await.unwrap();
+    }
+
+    #[test]
+    fn test_uplink_statistics_deserialization() {
+        let minimal: DeviceUplinkStatistics = serde_json::from_str(
+            r#"{
+            "txRateBps": 309720,
+            "rxRateBps": 32288
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(minimal.tx_rate_bps, 309720);
+        assert!(minimal.interface_name.is_none());
+        assert!(minimal.speed_mbps.is_none());
+        assert!(minimal.full_duplex.is_none());
+
+        let enriched: DeviceUplinkStatistics = serde_json::from_str(
+            r#"{
+            "txRateBps": 309720,
+            "rxRateBps": 32288,
+            "interfaceName": "eth8",
+            "speedMbps": 2500,
+            "fullDuplex": true
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(enriched.rx_rate_bps, 32288);
+        assert_eq!(enriched.interface_name.as_deref(), Some("eth8"));
+        assert_eq!(enriched.speed_mbps, Some(2500));
+        assert_eq!(enriched.full_duplex, Some(true));
+    }
+
+    #[test]
+    fn test_device_is_gateway() {
+        let mut gateway = device_details_json(Uuid::new_v4(), "ONLINE");
+        gateway["model"] = serde_json::json!("UDM-Pro");
+        let gateway: DeviceDetails = serde_json::from_value(gateway).unwrap();
+        assert!(gateway.is_gateway());
+
+        let mut access_point = device_details_json(Uuid::new_v4(), "ONLINE");
+        access_point["uplink"] = serde_json::json!({ "deviceId": gateway.id });
+        let access_point: DeviceDetails = serde_json::from_value(access_point).unwrap();
+        assert!(!access_point.is_gateway());
+
+        let mut orphaned_access_point = device_details_json(Uuid::new_v4(), "OFFLINE");
+        orphaned_access_point["model"] = serde_json::json!("U6-Pro");
+        let orphaned_access_point: DeviceDetails =
+            serde_json::from_value(orphaned_access_point).unwrap();
+        assert!(!orphaned_access_point.is_gateway());
+    }
+
+    #[tokio::test]
+    async fn test_get_device_statistics_metrics() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/statistics/latest",
+                site_id, device_id
+            )))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(SAMPLE_STATISTICS_JSON, "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let metrics = client
+            .get_device_statistics_metrics(site_id, device_id)
+            .await
+            .unwrap();
+
+        assert_eq!(metrics.uptime_sec, 737201);
+        assert_eq!(metrics.load_average_15min, Some(1.3));
+        assert_eq!(metrics.cpu_utilization_pct, Some(30.8));
+        assert_eq!(metrics.memory_utilization_pct, Some(74.2));
+        let uplink = metrics.uplink.unwrap();
+        assert_eq!(uplink.tx_rate_bps, 309720);
+        assert_eq!(uplink.rx_rate_bps, 32288);
+    }
+
+    #[test]
+    fn test_device_interfaces_deserialization_forms() {
+        let mut object = device_details_json(Uuid::new_v4(), "ONLINE");
+        object["interfaces"]["radios"] = serde_json::json!([{ "frequencyGHz": 5 }]);
+        let details: DeviceDetails = serde_json::from_value(object).unwrap();
+        assert_eq!(details.interfaces.unwrap().radios.len(), 1);
+
+        for form in [serde_json::json!([]), serde_json::Value::Null] {
+            let mut details = device_details_json(Uuid::new_v4(), "ONLINE");
+            details["interfaces"] = form;
+            let details: DeviceDetails = serde_json::from_value(details).unwrap();
+            let interfaces = details.interfaces.unwrap();
+            assert!(interfaces.ports.is_empty());
+            assert!(interfaces.radios.is_empty());
+        }
+
+        let mut non_empty_array = device_details_json(Uuid::new_v4(), "ONLINE");
+        non_empty_array["interfaces"] = serde_json::json!(["eth0"]);
+        assert!(serde_json::from_value::<DeviceDetails>(non_empty_array).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_recent_clients() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let now = chrono::Utc::now();
+
+        let clients: Vec<serde_json::Value> = [5, 30, 120]
+            .into_iter()
+            .map(|minutes_ago| {
+                let mut client = wireless_client_json(Uuid::new_v4(), None);
+                client["name"] = serde_json::json!(format!("{} minutes", minutes_ago));
+                client["connectedAt"] =
+                    serde_json::json!(now - chrono::Duration::minutes(minutes_ago));
+                client
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(clients)))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let recent = client
+            .list_recent_clients(site_id, chrono::Duration::hours(1), now)
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = recent
+            .iter()
+            .filter_map(|client| client.base().name.as_deref())
+            .collect();
+        assert_eq!(names, vec!["5 minutes", "30 minutes"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicate_ips() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        let mut device = device_overview_json(device_id, "Switch");
+        device["ipAddress"] = serde_json::json!("192.168.1.20");
+        let mut unaddressed_device = device_overview_json(Uuid::new_v4(), "Pending");
+        unaddressed_device["ipAddress"] = serde_json::json!("");
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![device, unaddressed_device])),
+            )
+            .mount(&server)
+            .await;
+
+        let mut colliding = wireless_client_json(device_id, None);
+        colliding["ipAddress"] = serde_json::json!("192.168.1.20");
+        let colliding_id: Uuid = serde_json::from_value(colliding["id"].clone()).unwrap();
+        let mut unique = wireless_client_json(device_id, None);
+        unique["ipAddress"] = serde_json::json!("192.168.1.21");
+        let mut vpn = wireless_client_json(device_id, None);
+        vpn["type"] = serde_json::json!("VPN");
+        vpn.as_object_mut().unwrap().remove("ipAddress");
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(page_json(vec![colliding, unique, vpn])),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let duplicates = client.find_duplicate_ips(site_id).await.unwrap();
+
+        let ip: std::net::IpAddr = "192.168.1.20".parse().unwrap();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[&ip], vec![device_id.as_uuid(), colliding_id]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_override() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(format!(
+            "http://controller.invalid:{}",
+            server.address().port()
+        ))
+        .api_key("test-key")
+        .resolve("controller.invalid", *server.address())
+        .build()
+        .unwrap();
+
+        let info = client.get_info().await.unwrap();
+        assert_eq!(info.application_version, "9.0.108");
+    }
+
+    #[tokio::test]
+    async fn test_stream_all_devices() {
+        use futures::StreamExt;
+
+        let server = MockServer::start().await;
+        let broken_site = SiteId::new(Uuid::new_v4());
+        let healthy_site = SiteId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": broken_site, "name": "Broken" }),
+                serde_json::json!({ "id": healthy_site, "name": "Healthy" }),
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", broken_site)))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "statusCode": 500,
+                "message": "Internal error"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", healthy_site)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(Uuid::new_v4(), "Switch"),
+                device_overview_json(Uuid::new_v4(), "Access Point"),
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let items: Vec<_> = client.stream_all_devices().collect().await;
+
+        assert_eq!(items.len(), 3);
+        assert!(matches!(
+            items[0].as_ref().map_err(UnifiError::inner),
+            Err(UnifiError::ServerError {
+                status_code: 500,
+                ..
+            })
+        ));
+        let devices: Vec<(SiteId, String)> = items[1..]
+            .iter()
+            .map(|item| {
+                let (site, device) = item.as_ref().unwrap();
+                (site.id, device.name.clone())
+            })
+            .collect();
+        assert_eq!(
+            devices,
+            vec![
+                (healthy_site, "Switch".to_string()),
+                (healthy_site, "Access Point".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_controller_health_deserialization() {
+        let health_json = r#"{
+            "status": "WARNING",
+            "subsystems": [
+                { "name": "network", "status": "OK" },
+                { "name": "database", "status": "WARNING", "message": "High disk usage" },
+                { "name": "backup", "status": "DEGRADED" }
+            ]
+        }"#;
+
+        let health: ControllerHealth = serde_json::from_str(health_json).unwrap();
+        assert_eq!(health.status, HealthStatus::Warning);
+        assert_eq!(health.subsystems.len(), 3);
+        assert_eq!(
+            health.subsystems[1].message.as_deref(),
+            Some("High disk usage")
+        );
+        assert_eq!(health.subsystems[2].status, HealthStatus::Unknown);
+        assert!(!health.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_get_controller_health_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/health"))
+            .respond_with(ResponseTemplate::new(503).set_body_json(serde_json::json!({
+                "statusCode": 503,
+                "message": "Service unavailable"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        match client
+            .get_controller_health()
+            .await
+            .map_err(UnifiError::into_inner)
+        {
+            Err(UnifiError::ServerError {
+                status_code,
+                message,
+            }) => {
+                assert_eq!(status_code, 503);
+                assert_eq!(message, "Service unavailable");
+            }
+            other => panic!("Expected Api error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_site_statistics_bulk() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_ids = [DeviceId::new(Uuid::new_v4()), DeviceId::new(Uuid::new_v4())];
+
+        let entries = device_ids
+            .iter()
+            .map(|device_id| {
+                let mut entry: serde_json::Value =
+                    serde_json::from_str(SAMPLE_STATISTICS_JSON).unwrap();
+                entry["deviceId"] = serde_json::json!(device_id);
+                entry
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/statistics/latest",
+                site_id
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(entries)))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .bulk_statistics(true)
+            .build()
+            .unwrap();
+        let statistics = client.get_site_statistics(site_id).await.unwrap();
+
+        assert_eq!(statistics.len(), 2);
+        for device_id in device_ids {
+            assert_eq!(statistics[&device_id].uptime_sec, 737201);
+        }
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_site_statistics_fallback() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_ids = [DeviceId::new(Uuid::new_v4()), DeviceId::new(Uuid::new_v4())];
+
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/statistics/latest",
+                site_id
+            )))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(page_json(
+                    device_ids
+                        .iter()
+                        .map(|id| device_overview_json(*id, "Device"))
+                        .collect(),
+                )),
+            )
+            .mount(&server)
+            .await;
+        for device_id in device_ids {
+            Mock::given(method("GET"))
+                .and(path(format!(
+                    "/v1/sites/{}/devices/{}/statistics/latest",
+                    site_id, device_id
+                )))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_raw(SAMPLE_STATISTICS_JSON, "application/json"),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .bulk_statistics(true)
+            .build()
+            .unwrap();
+        let statistics = client.get_site_statistics(site_id).await.unwrap();
+
+        assert_eq!(statistics.len(), 2);
+        assert!(device_ids.iter().all(|id| statistics.contains_key(id)));
+    }
+
+    #[tokio::test]
+    async fn test_get_device_statistics_timestamped() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/statistics/latest",
+                site_id, device_id
+            )))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(SAMPLE_STATISTICS_JSON, "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let before = std::time::Instant::now();
+        let stats = client
+            .get_device_statistics_timestamped(site_id, device_id)
+            .await
+            .unwrap();
+
+        assert_eq!(stats.value.uptime_sec, 737201);
+        assert!(stats.fetched_at >= before);
+        assert!(stats.age() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_topology_to_dot() {
+        let gateway = DeviceId::new(Uuid::new_v4());
+        let switch = DeviceId::new(Uuid::new_v4());
+        let access_point = DeviceId::new(Uuid::new_v4());
+        let node = |id, name: &str, uplink| TopologyNode {
+            id,
+            name: name.to_string(),
+            model: "UHDIW".to_string(),
+            uplink_device_id: uplink,
+            is_gateway: uplink.is_none(),
+        };
+        let nodes = vec![
+            node(gateway, "Gateway", None),
+            node(switch, "Core \"Switch\"", Some(gateway)),
+            node(access_point, "Lobby AP", Some(switch)),
+            node(
+                DeviceId::new(Uuid::new_v4()),
+                "Orphan",
+                Some(DeviceId::new(Uuid::new_v4())),
+            ),
+        ];
+
+        let dot = topology_to_dot(&nodes);
+
+        assert!(dot.starts_with("digraph topology {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains(&format!("    \"{}\" [label=\"Gateway\"];", gateway)));
+        assert!(dot.contains(&format!(
+            "    \"{}\" [label=\"Core \\\"Switch\\\"\"];",
+            switch
+        )));
+        assert!(dot.contains(&format!("    \"{}\" -> \"{}\";", gateway, switch)));
+        assert!(dot.contains(&format!("    \"{}\" -> \"{}\";", switch, access_point)));
+        assert_eq!(dot.matches("->").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_skip_bad_records() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+
+        let mut bad = device_overview_json(Uuid::new_v4(), "Broken");
+        bad["state"] = serde_json::json!(42);
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(Uuid::new_v4(), "First"),
+                bad,
+                device_overview_json(Uuid::new_v4(), "Third"),
+            ])))
+            .mount(&server)
+            .await;
+
+        let strict = mock_client(&server);
+        assert!(matches!(
+            strict
+                .list_devices(site_id, &ListParams::default())
+                .await
+                .map_err(UnifiError::into_inner),
+            Err(UnifiError::Deserialization { .. })
+        ));
+
+        let lenient = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .skip_bad_records(true)
+            .build()
+            .unwrap();
+        let page = lenient
+            .list_devices(site_id, &ListParams::default())
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = page.data.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["First", "Third"]);
+        assert_eq!(page.skipped, 1);
+        assert_eq!(page.total_count, 3);
+    }
+
+    #[test]
+    fn test_site_snapshot_diff() {
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = || DeviceId::new(Uuid::new_v4());
+        let (kept, updated, removed, added) = (device_id(), device_id(), device_id(), device_id());
+        let device = |id, state: &str, firmware: &str, ip: &str| -> DeviceDetails {
+            let mut details = device_details_json(id, state);
+            details["firmwareVersion"] = serde_json::json!(firmware);
+            details["ipAddress"] = serde_json::json!(ip);
+            serde_json::from_value(details).unwrap()
+        };
+        let client = |id: ClientId| -> ClientOverview {
+            let mut client = wireless_client_json(kept, None);
+            client["id"] = serde_json::json!(id);
+            serde_json::from_value(client).unwrap()
+        };
+        let client_id = || ClientId::new(Uuid::new_v4());
+        let (staying_client, leaving_client, joining_client) =
+            (client_id(), client_id(), client_id());
+
+        let before = SiteSnapshot {
+            site_id,
+            taken_at: chrono::Utc::now(),
+            devices: vec![
+                device(kept, "ONLINE", "6.6.55", "192.168.1.2"),
+                device(updated, "ONLINE", "6.6.55", "192.168.1.3"),
+                device(removed, "ONLINE", "6.6.55", "192.168.1.4"),
+            ],
+            clients: vec![client(staying_client), client(leaving_client)],
+        };
+        let after = SiteSnapshot {
+            site_id,
+            taken_at: chrono::Utc::now(),
+            devices: vec![
+                device(kept, "ONLINE", "6.6.55", "192.168.1.2"),
+                device(updated, "OFFLINE", "6.6.65", "192.168.1.30"),
+                device(added, "ONLINE", "6.6.65", "192.168.1.5"),
+            ],
+            clients: vec![client(staying_client), client(joining_client)],
+        };
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.added_devices, vec![added]);
+        assert_eq!(diff.removed_devices, vec![removed]);
+        assert_eq!(
+            diff.changed_devices,
+            vec![DeviceChange {
+                device_id: updated,
+                state: Some((DeviceState::Online, DeviceState::Offline)),
+                firmware_version: Some(("6.6.55".to_string(), "6.6.65".to_string())),
+                ip_address: Some(("192.168.1.3".parse().ok(), "192.168.1.30".parse().ok())),
+            }]
+        );
+        assert_eq!(diff.added_clients, vec![joining_client]);
+        assert_eq!(diff.removed_clients, vec![leaving_client]);
+
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_device_name() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("PATCH"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .and(body_json(serde_json::json!({ "name": "Lobby AP" })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        client
+            .set_device_name(site_id, device_id, "Lobby AP")
+            .await
+            .unwrap();
+
+        for name in ["", "   ", &"x".repeat(129)] {
+            assert!(matches!(
+                client.set_device_name(site_id, device_id, name).await,
+                Err(UnifiError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clients_per_wlan() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let (corporate, guest) = (Uuid::new_v4(), Uuid::new_v4());
+        let uplink = DeviceId::new(Uuid::new_v4());
+
+        let on_wlan = |wlan: Option<Uuid>| {
+            let mut client = wireless_client_json(uplink, None);
+            if let Some(wlan) = wlan {
+                client["wlanId"] = serde_json::json!(wlan);
+            }
+            client
+        };
+        let mut wired = on_wlan(None);
+        wired["type"] = serde_json::json!("WIRED");
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                on_wlan(Some(corporate)),
+                on_wlan(Some(guest)),
+                on_wlan(Some(corporate)),
+                on_wlan(None),
+                wired,
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let counts = client.clients_per_wlan(site_id).await.unwrap();
+
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&corporate], 2);
+        assert_eq!(counts[&guest], 1);
+    }
+
+    #[test]
+    fn test_client_builder_identity_pem() {
+        let identity = include_bytes!("../tests/fixtures/identity.pem");
+        let client = UnifiClientBuilder::new("https://example.com")
+            .api_key("test-key")
+            .identity_pem(identity)
+            .build();
+        assert!(client.is_ok());
+
+        #[cfg(feature = "native-tls")]
+        assert!(UnifiClientBuilder::new("https://example.com")
+            .api_key("test-key")
+            .tls_backend(crate::client::TlsBackend::NativeTls)
+            .identity_pem(identity)
+            .build()
+            .is_ok());
+
+        let client = UnifiClientBuilder::new("https://example.com")
+            .api_key("test-key")
+            .identity_pem(b"not a pem")
+            .build();
+        assert!(matches!(client, Err(UnifiError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_model_counts() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+
+        let devices = ["U6-Pro", "USW-24-PoE", "U6-Pro", "U6-Pro"]
+            .into_iter()
+            .map(|model| {
+                let mut device = device_overview_json(Uuid::new_v4(), model);
+                device["model"] = serde_json::json!(model);
+                device
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(devices)))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let counts = client.model_counts(site_id).await.unwrap();
+
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["U6-Pro"], 3);
+        assert_eq!(counts["USW-24-PoE"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_across_sites() {
+        let server = MockServer::start().await;
+        let (first_site, second_site) = (SiteId::new(Uuid::new_v4()), SiteId::new(Uuid::new_v4()));
+
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": first_site, "name": "First" }),
+                serde_json::json!({ "id": second_site, "name": "Second" }),
+            ])))
+            .mount(&server)
+            .await;
+
+        let mut matching_device = device_overview_json(Uuid::new_v4(), "Switch");
+        matching_device["macAddress"] = serde_json::json!("f4:92:bf:00:11:22");
+        let mut matching_client = wireless_client_json(Uuid::new_v4(), None);
+        matching_client["macAddress"] = serde_json::json!("F4:92:BF:AA:BB:CC");
+        let site_data = [
+            (
+                first_site,
+                vec![matching_device],
+                vec![wireless_client_json(Uuid::new_v4(), None)],
+            ),
+            (
+                second_site,
+                vec![device_overview_json(Uuid::new_v4(), "Other")],
+                vec![matching_client],
+            ),
+        ];
+        for (site_id, devices, clients) in site_data {
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/sites/{}/devices", site_id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page_json(devices)))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/sites/{}/clients", site_id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page_json(clients)))
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let results = client.search("f4:92:bf").await.unwrap();
+
+        assert_eq!(results.devices.len(), 1);
+        assert_eq!(results.devices[0].site_id, first_site);
+        assert_eq!(results.devices[0].device.name, "Switch");
+        assert_eq!(results.clients.len(), 1);
+        assert_eq!(results.clients[0].site_id, second_site);
+
+        assert!(client.search("no-such-thing").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restart_device_action_ack() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let (done, pending) = (DeviceId::new(Uuid::new_v4()), DeviceId::new(Uuid::new_v4()));
+
+        for (device_id, status) in [(done, 200), (pending, 202)] {
+            Mock::given(method("POST"))
+                .and(path(format!(
+                    "/v1/sites/{}/devices/{}/actions",
+                    site_id, device_id
+                )))
+                .and(body_json(serde_json::json!({ "action": "RESTART" })))
+                .respond_with(ResponseTemplate::new(status))
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let ack = client.restart_device(site_id, done).await.unwrap();
+        assert!(ack.completed && !ack.accepted);
+        assert_eq!(ack.status_code, 200);
+
+        let ack = client.restart_device(site_id, pending).await.unwrap();
+        assert!(ack.accepted && !ack.completed);
+        assert_eq!(ack.status_code, 202);
+    }
+
+    #[tokio::test]
+    async fn test_default_site_id() {
+        let only_site = SiteId::new(Uuid::new_v4());
+        let cases = [
+            vec![],
+            vec![serde_json::json!({ "id": only_site, "name": "Default" })],
+            vec![
+                serde_json::json!({ "id": only_site, "name": "Default" }),
+                serde_json::json!({ "id": Uuid::new_v4(), "name": "Branch" }),
+            ],
+        ];
+
+        let mut results = Vec::new();
+        for sites in cases {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/v1/sites"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page_json(sites)))
+                .mount(&server)
+                .await;
+            results.push(mock_client(&server).default_site_id().await);
+        }
+
+        assert!(matches!(results[0], Err(UnifiError::NotFound { .. })));
+        assert_eq!(*results[1].as_ref().unwrap(), only_site);
+        match &results[2] {
+            Err(UnifiError::Config(message)) => assert!(message.contains("found 2")),
+            other => panic!("Expected Config error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_devices_stream_pages() {
+        use futures::TryStreamExt;
+
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let names = ["First", "Second", "Third"];
+
+        for (offset, chunk) in [(0, &names[..2]), (2, &names[2..])] {
+            let mut page = page_json(
+                chunk
+                    .iter()
+                    .map(|name| device_overview_json(Uuid::new_v4(), name))
+                    .collect(),
+            );
+            page["offset"] = serde_json::json!(offset);
+            page["totalCount"] = serde_json::json!(names.len());
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/sites/{}/devices", site_id)))
+                .and(query_param("offset", offset.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let devices: Vec<_> = client
+            .list_devices_stream(site_id)
+            .try_collect()
+            .await
+            .unwrap();
+
+        let streamed: Vec<&str> = devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(streamed, names);
+    }
+
+    #[tokio::test]
+    async fn test_list_all_devices_max_pages() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+
+        // A buggy controller that always claims more devices remain.
+        let mut page = page_json(vec![device_overview_json(Uuid::new_v4(), "Loop")]);
+        page["totalCount"] = serde_json::json!(1_000_000);
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .max_pages(3)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.list_all_devices(site_id).await,
+            Err(UnifiError::PageLimitExceeded { max_pages: 3 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_all_sites_and_clients() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": site_id, "name": "Default" }),
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                wireless_client_json(Uuid::new_v4(), None),
+                wireless_client_json(Uuid::new_v4(), None),
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let sites = client.list_all_sites().await.unwrap();
+        assert_eq!(sites.len(), 1);
+        let clients = client.list_all_clients(sites[0].id).await.unwrap();
+        assert_eq!(clients.len(), 2);
+    }
+
+    #[test]
+    fn test_filter_rendering() {
+        let filter = Filter::eq("state", "ONLINE")
+            .and(Filter::contains("name", "office"))
+            .and(
+                Filter::gt("uptimeSec", 3600)
+                    .or(Filter::is_null("uplink"))
+                    .not(),
+            );
+        assert_eq!(
+            filter.to_string(),
+            "and(state.eq('ONLINE'),name.like('*office*'),not(or(uptimeSec.gt(3600),uplink.isNull())))"
+        );
+        assert_eq!(
+            Filter::is_in("model", ["U6-LR", "O'Neil"]).to_string(),
+            r"model.in('U6-LR','O\'Neil')"
+        );
+        assert_eq!(
+            Filter::contains("name", r"10.0.*\x").to_string(),
+            r"name.like('*10\\.0\\.\\*\\\\x*')"
+        );
+        assert_eq!(
+            Filter::eq("path", r"C:\shares\").to_string(),
+            r"path.eq('C:\\shares\\')"
+        );
+        assert_eq!(
+            Filter::eq("name", r"a\'b").to_string(),
+            r"name.eq('a\\\'b')"
+        );
+        assert_eq!(
+            Filter::like("name", "AP.*").to_string(),
+            "name.like('AP.*')"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_devices_with_filter() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .and(query_param("filter", "state.eq('ONLINE')"))
+            .and(query_param("sort", "name"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(Uuid::new_v4(), "Office AP"),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let filter = Filter::eq("state", "ONLINE");
+        let page = client
+            .list_devices(site_id, &ListParams::default().filter(filter).sort("name"))
+            .await
+            .unwrap();
+        assert_eq!(page.data.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_client_details() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let client_id = ClientId::new(Uuid::new_v4());
+        let uplink = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients/{}", site_id, client_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "type": "WIRELESS",
+                "id": client_id,
+                "name": "Laptop",
+                "connectedAt": "2025-01-18T20:00:00Z",
+                "ipAddress": "192.168.1.50",
+                "access": { "type": "GUEST", "authorized": true },
+                "macAddress": "aa:bb:cc:dd:ee:ff",
+                "uplinkDeviceId": uplink,
+                "ssid": "Guests"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let details = client.get_client_details(site_id, client_id).await.unwrap();
+        assert_eq!(details.uplink_device_id(), Some(uplink));
+        let access = details.base().access.as_ref().unwrap();
+        assert_eq!(access.access_type, ClientAccessType::Guest);
+        assert_eq!(access.authorized, Some(true));
+        match details {
+            ClientDetails::Wireless(wireless) => {
+                assert_eq!(wireless.ssid.as_deref(), Some("Guests"))
+            }
+            other => panic!("expected wireless client, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_and_unblock_client() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let client_id = ClientId::new(Uuid::new_v4());
+        let actions = format!("/v1/sites/{}/clients/{}/actions", site_id, client_id);
+
+        Mock::given(method("POST"))
+            .and(path(actions.clone()))
+            .and(body_json(serde_json::json!({ "action": "BLOCK" })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(actions))
+            .and(body_json(serde_json::json!({ "action": "UNBLOCK" })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        assert!(
+            client
+                .block_client(site_id, client_id)
+                .await
+                .unwrap()
+                .completed
+        );
+        assert!(
+            client
+                .unblock_client(site_id, client_id)
+                .await
+                .unwrap()
+                .accepted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_client() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let client_id = ClientId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/clients/{}/actions",
+                site_id, client_id
+            )))
+            .and(body_json(serde_json::json!({ "action": "RECONNECT" })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        assert!(
+            client
+                .reconnect_client(site_id, client_id)
+                .await
+                .unwrap()
+                .completed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authorize_guest_access() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let client_id = ClientId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/clients/{}/actions",
+                site_id, client_id
+            )))
+            .and(body_json(serde_json::json!({
+                "action": "AUTHORIZE_GUEST_ACCESS",
+                "timeLimitMinutes": 60,
+                "dataUsageLimitMBytes": 500,
+                "rxRateLimitKbps": 2000
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "action": "AUTHORIZE_GUEST_ACCESS",
+                "grantedAuthorization": {
+                    "authorizedAt": "2025-01-18T20:00:00Z",
+                    "expiresAt": "2025-01-18T21:00:00Z",
+                    "dataUsageLimitMBytes": 500,
+                    "rxRateLimitKbps": 2000
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let request = AuthorizeGuestRequest::new()
+            .time_limit_minutes(60)
+            .data_usage_limit_mbytes(500)
+            .rx_rate_limit_kbps(2000);
+        let response = client
+            .authorize_guest_access(site_id, client_id, &request)
+            .await
+            .unwrap();
+        assert!(response.revoked_authorization.is_none());
+        let granted = response.granted_authorization.unwrap();
+        assert_eq!(granted.data_usage_limit_mbytes, Some(500));
+        assert_eq!(granted.tx_rate_limit_kbps, None);
+    }
+
+    fn voucher_json(id: Uuid, code: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "createdAt": "2025-01-18T20:00:00Z",
+            "name": "Conference",
+            "code": code,
+            "authorizedGuestLimit": 5,
+            "authorizedGuestCount": 0,
+            "expired": false,
+            "timeLimitMinutes": 1440,
+            "dataUsageLimitMBytes": 1024
+        })
+    }
+
+    #[tokio::test]
+    async fn test_voucher_lifecycle() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let voucher_id = Uuid::new_v4();
+        let vouchers = format!("/v1/sites/{}/hotspot/vouchers", site_id);
+        let voucher = format!("{}/{}", vouchers, voucher_id);
+
+        Mock::given(method("POST"))
+            .and(path(vouchers.clone()))
+            .and(body_json(serde_json::json!({
+                "count": 2,
+                "name": "Conference",
+                "timeLimitMinutes": 1440,
+                "authorizedGuestLimit": 5,
+                "dataUsageLimitMBytes": 1024
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "vouchers": [
+                    voucher_json(voucher_id, "12345"),
+                    voucher_json(Uuid::new_v4(), "67890")
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(vouchers))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![voucher_json(voucher_id, "12345")])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(voucher.clone()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(voucher_json(voucher_id, "12345")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(voucher))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "vouchersDeleted": 1 })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let request = VoucherCreateRequest::new("Conference", 1440)
+            .count(2)
+            .authorized_guest_limit(5)
+            .data_usage_limit_mbytes(1024);
+        let created = client.generate_vouchers(site_id, &request).await.unwrap();
+        assert_eq!(created.len(), 2);
+        // Unlimited guests is the default, sent by omitting the limit.
+        let unlimited = serde_json::to_value(VoucherCreateRequest::new("Lobby", 60)).unwrap();
+        assert!(unlimited.get("authorizedGuestLimit").is_none());
+
+        let page = client
+            .list_vouchers(site_id, &ListParams::default())
+            .await
+            .unwrap();
+        assert_eq!(page.data[0].code, "12345");
+
+        let details = client
+            .get_voucher_details(site_id, voucher_id)
+            .await
+            .unwrap();
+        assert_eq!(details.authorized_guest_limit, Some(5));
+        assert!(details.activated_at.is_none());
+
+        client.delete_voucher(site_id, voucher_id).await.unwrap();
+
+        assert!(matches!(
+            client
+                .generate_vouchers(site_id, &request.clone().count(0))
+                .await,
+            Err(UnifiError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_custom_device_action() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/actions",
+                site_id, device_id
+            )))
+            .and(body_json(serde_json::json!({ "action": "PROVISION" })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let ack = client
+            .execute_device_action(
+                site_id,
+                device_id,
+                DeviceAction::Custom("PROVISION".to_string()),
+            )
+            .await
+            .unwrap();
+        assert!(ack.accepted);
+    }
+
+    #[tokio::test]
+    async fn test_power_cycle_port() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/interfaces/ports/7/actions",
+                site_id, device_id
+            )))
+            .and(body_json(serde_json::json!({ "action": "POWER_CYCLE" })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let ack = client
+            .power_cycle_port(site_id, device_id, 7)
+            .await
+            .unwrap();
+        assert!(ack.completed);
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_device_firmware_to() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/actions",
+                site_id, device_id
+            )))
+            .and(body_json(serde_json::json!({
+                "action": "UPGRADE",
+                "firmwareVersion": "7.1.66"
+            })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(device_details_json(device_id, "UPDATING")),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let upgrade = client
+            .upgrade_device_firmware_to(site_id, device_id, "7.1.66")
+            .await
+            .unwrap();
+        assert!(upgrade.ack().accepted);
+        assert_eq!(upgrade.poll().await.unwrap(), None);
+        assert!(upgrade
+            .wait(Duration::from_secs(5), Duration::from_millis(10))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_list_pending_devices_and_adopt() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let pending_id = DeviceId::new(Uuid::new_v4());
+
+        let mut pending = device_overview_json(pending_id, "New AP");
+        pending["state"] = serde_json::json!("PENDINGADOPTION");
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(Uuid::new_v4(), "Switch"),
+                pending,
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/actions",
+                site_id, pending_id
+            )))
+            .and(body_json(serde_json::json!({ "action": "ADOPT" })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let pending = client.list_pending_devices(site_id).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, pending_id);
+        assert!(
+            client
+                .adopt_device(site_id, pending_id)
+                .await
+                .unwrap()
+                .accepted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_and_stop_locating_device() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let actions = format!("/v1/sites/{}/devices/{}/actions", site_id, device_id);
+
+        for action in ["START_LOCATING", "STOP_LOCATING"] {
+            Mock::given(method("POST"))
+                .and(path(actions.clone()))
+                .and(body_json(serde_json::json!({ "action": action })))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        client
+            .start_locating_device(site_id, device_id)
+            .await
+            .unwrap();
+        client
+            .stop_locating_device(site_id, device_id)
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "events")]
+    #[tokio::test]
+    #[allow(clippy::result_large_err)]
+    async fn test_subscribe_events() {
+        use crate::events::UnifiEvent;
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let expected_path = format!("/integrations/v1/sites/{}/events", site_id);
+            let mut socket = tokio_tungstenite::accept_hdr_async(
+                stream,
+                |request: &Request, response: Response| {
+                    assert_eq!(request.uri().path(), expected_path);
+                    assert_eq!(request.headers()["X-API-KEY"], "test-key");
+                    Ok(response)
+                },
+            )
+            .await
+            .unwrap();
+            let events = [
+                serde_json::json!({
+                    "type": "DEVICE_STATE_CHANGED",
+                    "deviceId": device_id,
+                    "state": "OFFLINE"
+                }),
+                serde_json::json!({ "type": "SOMETHING_NEW" }),
+            ];
+            for event in events {
+                socket.send(Message::Text(event.to_string())).await.unwrap();
+            }
+            socket.close(None).await.unwrap();
+        });
+
+        let client = UnifiClientBuilder::new(format!("http://{}/integrations", addr))
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        let events: Vec<UnifiEvent> = client
+            .subscribe_events(site_id)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        server.await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                UnifiEvent::DeviceStateChanged {
+                    device_id,
+                    state: DeviceState::Offline
+                },
+                UnifiEvent::Unknown
+            ]
+        );
+    }
+
+    #[cfg(all(feature = "events", feature = "rustls"))]
+    #[tokio::test]
+    async fn test_subscribe_events_insecure_rustls() {
+        // A listener that is dropped at once, so the TLS connection is refused.
+        let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let client = UnifiClientBuilder::new(format!("https://{}/integrations", addr))
+            .api_key("test-key")
+            .tls_backend(crate::client::TlsBackend::Rustls)
+            .verify_ssl(false)
+            .build()
+            .unwrap();
+
+        // The connection is attempted rather than rejected as unsupported.
+        match client.subscribe_events(SiteId::new(Uuid::new_v4())).await {
+            Err(UnifiError::WebSocket(_)) => {}
+            Err(other) => panic!("Expected WebSocket error, got {:?}", other),
+            Ok(_) => panic!("Expected WebSocket error, got a stream"),
+        }
+    }
+
+    #[test]
+    fn test_events_from_diff() {
+        let device_id = || DeviceId::new(Uuid::new_v4());
+        let (online, offline, upgraded) = (device_id(), device_id(), device_id());
+        let joined = ClientId::new(Uuid::new_v4());
+        let diff = SiteDiff {
+            changed_devices: vec![
+                DeviceChange {
+                    device_id: offline,
+                    state: Some((DeviceState::Online, DeviceState::Offline)),
+                    firmware_version: None,
+                    ip_address: None,
+                },
+                DeviceChange {
+                    device_id: online,
+                    state: Some((DeviceState::Offline, DeviceState::Online)),
+                    firmware_version: None,
+                    ip_address: None,
+                },
+                DeviceChange {
+                    device_id: upgraded,
+                    state: None,
+                    firmware_version: Some(("7.0.0".to_string(), "7.1.0".to_string())),
+                    ip_address: Some(("10.0.0.1".parse().ok(), "10.0.0.2".parse().ok())),
+                },
+            ],
+            added_clients: vec![joined],
+            ..SiteDiff::default()
+        };
+
+        assert_eq!(
+            events_from_diff(&diff),
+            vec![
+                WatchEvent::DeviceWentOffline { device_id: offline },
+                WatchEvent::DeviceStateChanged {
+                    device_id: online,
+                    from: DeviceState::Offline,
+                    to: DeviceState::Online
+                },
+                WatchEvent::FirmwareUpdated {
+                    device_id: upgraded,
+                    from: "7.0.0".to_string(),
+                    to: "7.1.0".to_string()
+                },
+                WatchEvent::NewClientJoined { client_id: joined },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watcher_broadcasts_device_going_offline() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![device_overview_json(device_id, "AP")])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "OFFLINE")),
+            )
+            .mount(&server)
+            .await;
+
+        // A zero capacity is clamped rather than panicking in `broadcast::channel`.
+        let watcher = Watcher::spawn(mock_client(&server), site_id, Duration::from_millis(20), 0);
+        let mut events = watcher.subscribe();
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event, WatchEvent::DeviceWentOffline { device_id });
+    }
+
+    #[cfg(feature = "legacy")]
+    #[tokio::test]
+    async fn test_legacy_client_login_and_csrf() {
+        use crate::legacy::LegacyClientBuilder;
+
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/auth/login"))
+            .and(body_json(serde_json::json!({
+                "username": "admin",
+                "password": "secret"
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Set-Cookie", "TOKEN=session; Path=/")
+                    .insert_header("X-CSRF-Token", "csrf-1"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/proxy/network/api/s/default/rest/wlanconf"))
+            .and(header("cookie", "TOKEN=session"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("X-Updated-CSRF-Token", "csrf-2")
+                    .set_body_json(serde_json::json!({
+                        "meta": { "rc": "ok" },
+                        "data": [{ "name": "Home" }]
+                    })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/proxy/network/api/s/default/cmd/devmgr"))
+            .and(header("x-csrf-token", "csrf-2"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "meta": { "rc": "error", "msg": "api.err.UnknownDevice" },
+                "data": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = LegacyClientBuilder::new(server.uri())
+            .username("admin")
+            .password("secret")
+            .build()
+            .unwrap();
+
+        let wlans = client.list_wlan_configs("default").await.unwrap();
+        assert_eq!(wlans[0]["name"], "Home");
+
+        let result: Result<Vec<serde_json::Value>, _> = client
+            .post(
+                "default",
+                "cmd/devmgr",
+                &serde_json::json!({ "cmd": "restart" }),
+            )
+            .await;
+        match result.map_err(UnifiError::into_inner) {
+            Err(UnifiError::Api {
+                status_code,
+                message,
+            }) => {
+                assert_eq!(status_code, 400);
+                assert_eq!(message, "api.err.UnknownDevice");
+            }
+            other => panic!("expected API error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_site_manager_paginates_hosts_and_lists_devices() {
+        use crate::site_manager::SiteManagerClientBuilder;
+
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/ea/hosts"))
+            .and(query_param("nextToken", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{ "id": "host-2", "type": "console" }],
+                "httpStatusCode": 200
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ea/hosts"))
+            .and(header("X-API-KEY", "cloud-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{ "id": "host-1", "type": "console", "ipAddress": "203.0.113.7" }],
+                "httpStatusCode": 200,
+                "nextToken": "page-2"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ea/devices"))
+            .and(query_param("hostIds[]", "host-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{
+                    "hostId": "host-1",
+                    "hostName": "HQ",
+                    "devices": [{ "id": "dev-1", "model": "U6-LR", "status": "online" }]
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ea/sites"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "code": "unauthorized",
+                "httpStatusCode": 401,
+                "message": "invalid API key"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = SiteManagerClientBuilder::new()
+            .base_url(server.uri())
+            .api_key("cloud-key")
+            .build()
+            .unwrap();
+
+        let hosts = client.list_hosts().await.unwrap();
+        let ids: Vec<&str> = hosts.iter().map(|host| host.id.as_str()).collect();
+        assert_eq!(ids, ["host-1", "host-2"]);
+
+        let devices = client.list_devices(&["host-1"]).await.unwrap();
+        assert_eq!(devices[0].devices[0].model.as_deref(), Some("U6-LR"));
+
+        match client.list_sites().await.map_err(UnifiError::into_inner) {
+            Err(UnifiError::Unauthorized {
+                status_code,
+                message,
+            }) => {
+                assert_eq!(status_code, 401);
+                assert_eq!(message, "invalid API key");
+            }
+            other => panic!("expected API error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_site_manager_isp_metrics() {
+        use crate::models::site_manager::{IspMetricInterval, IspMetricRange};
+        use crate::site_manager::SiteManagerClientBuilder;
+
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/ea/isp-metrics/1h"))
+            .and(query_param("duration", "7d"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{
+                    "metricType": "1h",
+                    "hostId": "host-1",
+                    "siteId": "site-1",
+                    "periods": [{
+                        "metricTime": "2025-01-18T20:00:00Z",
+                        "data": {
+                            "wan": {
+                                "avgLatency": 12,
+                                "maxLatency": 40,
+                                "packetLoss": 0.5,
+                                "download_kbps": 250000,
+                                "upload_kbps": 20000,
+                                "uptime": 100,
+                                "downtime": 0,
+                                "ispName": "Example ISP",
+                                "ispAsn": "64500"
+                            }
+                        }
+                    }]
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = SiteManagerClientBuilder::new()
+            .base_url(server.uri())
+            .api_key("cloud-key")
+            .build()
+            .unwrap();
+
+        let metrics = client
+            .get_isp_metrics(IspMetricInterval::Hourly, IspMetricRange::LastWeek)
+            .await
+            .unwrap();
+        let wan = metrics[0].periods[0].data.wan.as_ref().unwrap();
+        assert_eq!(wan.avg_latency, Some(12.0));
+        assert_eq!(wan.download_kbps, Some(250000.0));
+        assert_eq!(wan.isp_name.as_deref(), Some("Example ISP"));
+
+        assert!(matches!(
+            client
+                .get_isp_metrics(IspMetricInterval::FiveMinutes, IspMetricRange::LastMonth)
+                .await,
+            Err(UnifiError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_retries_transient_statuses() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .retry_policy(
+                RetryPolicy::default().backoff(Duration::from_millis(1), Duration::from_millis(5)),
+            )
+            .build()
+            .unwrap();
+        let info = client.get_info().await.unwrap();
+        assert_eq!(info.application_version, "9.0.108");
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy::default()
+            .backoff(Duration::from_millis(100), Duration::from_millis(300))
+            .jitter(false);
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(300));
+
+        let jittered = policy.jitter(true).delay(2);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_error_and_auto_retry() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" })),
+            )
+            .mount(&server)
+            .await;
+
+        let strict = mock_client(&server);
+        assert!(matches!(
+            strict.get_info().await.map_err(UnifiError::into_inner),
+            Err(UnifiError::RateLimited {
+                retry_after: Some(d),
+                message,
+            }) if d.is_zero() && message == "Too Many Requests"
+        ));
+
+        let patient = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .rate_limit_retries(1)
+            .build()
+            .unwrap();
+        assert_eq!(
+            patient.get_info().await.unwrap().application_version,
+            "9.0.108"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" }))
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .connect_timeout(Duration::from_secs(1))
+            .request_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        match client.get_info().await.map_err(UnifiError::into_inner) {
+            Err(UnifiError::Http(error)) => assert!(error.is_timeout()),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_options_overrides_per_call() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .and(header("X-Trace", "sweep"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" }))
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let sweep = client.with_options(
+            RequestOptions::new()
+                .timeout(Duration::from_millis(50))
+                .header(
+                    reqwest::header::HeaderName::from_static("x-trace"),
+                    reqwest::header::HeaderValue::from_static("sweep"),
+                ),
+        );
+        match sweep.get_info().await.map_err(UnifiError::into_inner) {
+            Err(UnifiError::Http(error)) => assert!(error.is_timeout()),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+
+        let patient = sweep.with_options(RequestOptions::new().timeout(Duration::from_secs(5)));
+        assert_eq!(
+            patient.get_info().await.unwrap().application_version,
+            "9.0.108"
+        );
+        // The original client is unaffected and sends no trace header.
+        assert!(client.get_info().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_and_no_proxy() {
+        let proxy = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/integrations/v1/info"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" })),
+            )
+            .expect(2)
+            .mount(&proxy)
+            .await;
+
+        let proxied = UnifiClientBuilder::new("http://controller.invalid/integrations")
+            .api_key("test-key")
+            .proxy(proxy.uri())
+            .build()
+            .unwrap();
+        assert_eq!(
+            proxied.get_info().await.unwrap().application_version,
+            "9.0.108"
+        );
+
+        let bypassed = UnifiClientBuilder::new(format!("{}/integrations", proxy.uri()))
+            .api_key("test-key")
+            .proxy("http://127.0.0.1:9")
+            .no_proxy(["127.0.0.1"])
+            .build()
+            .unwrap();
+        assert!(bypassed.get_info().await.is_ok());
+
+        assert!(matches!(
+            UnifiClientBuilder::new("http://controller.invalid")
+                .api_key("test-key")
+                .proxy("not a url")
+                .build(),
+            Err(UnifiError::Config(_))
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_client_lists_sites() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let site_id = SiteId::new(Uuid::new_v4());
+        let server = runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/v1/sites"))
+                .and(header("X-API-KEY", "test-key"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                    serde_json::json!({ "id": site_id, "name": "Default" }),
+                ])))
+                .expect(1)
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .build_blocking()
+            .unwrap();
+        let sites = client.list_sites(&ListParams::default()).unwrap();
+
+        assert_eq!(sites.data.len(), 1);
+        assert_eq!(sites.data[0].id, site_id);
+        runtime.block_on(server.verify());
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_modifies_requests_and_sees_responses() {
+        use crate::interceptor::Interceptor;
+        use futures::future::BoxFuture;
+        use std::sync::{Arc, Mutex};
+
+        struct Audit(Arc<Mutex<Vec<u16>>>);
+
+        impl Interceptor for Audit {
+            fn before_request<'a>(
+                &'a self,
+                request: &'a mut reqwest::Request,
+            ) -> BoxFuture<'a, Result<(), UnifiError>> {
+                Box::pin(async move {
+                    request
+                        .headers_mut()
+                        .insert("X-Audit", "on".parse().unwrap());
+                    Ok(())
+                })
+            }
+
+            fn after_response<'a>(&'a self, response: &'a reqwest::Response) -> BoxFuture<'a, ()> {
+                Box::pin(async move {
+                    self.0.lock().unwrap().push(response.status().as_u16());
+                })
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .and(header("X-Audit", "on"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .interceptor(Audit(statuses.clone()))
+            .build()
+            .unwrap();
+        client.list_sites(&ListParams::default()).await.unwrap();
+
+        assert_eq!(*statuses.lock().unwrap(), vec![200]);
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_error_aborts_request() {
+        use crate::interceptor::Interceptor;
+        use futures::future::BoxFuture;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Deny(Arc<AtomicUsize>);
+
+        impl Interceptor for Deny {
+            fn before_request<'a>(
+                &'a self,
+                _request: &'a mut reqwest::Request,
+            ) -> BoxFuture<'a, Result<(), UnifiError>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Err(UnifiError::Config("denied".to_string())) })
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .interceptor(Deny(calls.clone()))
+            .retry_policy(
+                RetryPolicy::default().backoff(Duration::from_millis(1), Duration::from_millis(5)),
+            )
+            .build()
+            .unwrap();
+        let result = client
+            .list_sites(&ListParams::default())
+            .await
+            .map_err(UnifiError::into_inner);
+
+        assert!(matches!(result, Err(UnifiError::Config(message)) if message == "denied"));
+        // A rejected request is not retried.
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "middleware")]
+    #[tokio::test]
+    async fn test_middleware_client_sends_through_stack() {
+        use futures::future::BoxFuture;
+        use reqwest_middleware::Next;
+
+        fn tag<'a>(
+            mut request: reqwest::Request,
+            extensions: &'a mut http::Extensions,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, reqwest_middleware::Result<reqwest::Response>> {
+            request
+                .headers_mut()
+                .insert("X-Middleware", "yes".parse().unwrap());
+            Box::pin(next.run(request, extensions))
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .and(header("X-API-KEY", "test-key"))
+            .and(header("X-Middleware", "yes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let stack = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+            .with(tag)
+            .build();
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .middleware_client(stack)
+            .build()
+            .unwrap();
+
+        client.list_sites(&ListParams::default()).await.unwrap();
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics_record_requests_errors_and_retries() {
+        use crate::telemetry::{
+            REQUESTS_TOTAL, REQUEST_DURATION_SECONDS, REQUEST_ERRORS_TOTAL, RETRIES_TOTAL,
+        };
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(async {
+                let server = MockServer::start().await;
+                Mock::given(method("GET"))
+                    .and(path("/v1/sites"))
+                    .respond_with(ResponseTemplate::new(503))
+                    .up_to_n_times(1)
+                    .with_priority(1)
+                    .mount(&server)
+                    .await;
+                Mock::given(method("GET"))
+                    .and(path("/v1/sites"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![])))
+                    .mount(&server)
+                    .await;
+
+                let client = UnifiClientBuilder::new(server.uri())
+                    .api_key("test-key")
+                    .retry_policy(
+                        RetryPolicy::default()
+                            .max_attempts(2)
+                            .backoff(Duration::from_millis(1), Duration::from_millis(1))
+                            .jitter(false),
+                    )
+                    .build()
+                    .unwrap();
+                client.list_sites(&ListParams::default()).await.unwrap();
+            })
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let value = |name: &str, label: (&str, &str)| {
+            snapshot
+                .iter()
+                .find(|(key, ..)| {
+                    key.key().name() == name
+                        && key
+                            .key()
+                            .labels()
+                            .any(|l| l.key() == label.0 && l.value() == label.1)
+                })
+                .map(|(.., value)| value)
+        };
+
+        assert_eq!(
+            value(REQUESTS_TOTAL, ("status", "503")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            value(REQUESTS_TOTAL, ("status", "200")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            value(REQUEST_ERRORS_TOTAL, ("status", "503")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(value(REQUEST_ERRORS_TOTAL, ("status", "200")), None);
+        assert_eq!(
+            value(RETRIES_TOTAL, ("reason", "retry_policy")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert!(matches!(
+            value(REQUEST_DURATION_SECONDS, ("method", "GET")),
+            Some(DebugValue::Histogram(samples)) if samples.len() == 2
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unifi_client_implements_unifi_api() {
+        use crate::api::UnifiApi;
+
+        async fn count_sites(api: &dyn UnifiApi) -> usize {
+            api.list_all_sites()
+                .await
+                .map(|sites| sites.len())
+                .unwrap_or(0)
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": Uuid::new_v4(), "name": "Default" }),
+            ])))
+            .mount(&server)
+            .await;
+
+        assert_eq!(count_sites(&mock_client(&server)).await, 1);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_mock_unifi_api() {
+        use crate::api::{MockUnifiApi, UnifiApi};
+
+        let site_id = SiteId::new(Uuid::new_v4());
+        let mut mock = MockUnifiApi::new();
+        mock.expect_default_site_id()
+            .times(1)
+            .returning(move || Ok(site_id));
+        mock.expect_restart_device()
+            .withf(move |site, _| *site == site_id)
+            .returning(|_, _| {
+                Err(UnifiError::NotFound {
+                    resource: "device".to_string(),
+                    message: "device not found".to_string(),
+                })
+            });
+
+        assert_eq!(mock.default_site_id().await.unwrap(), site_id);
+        assert!(matches!(
+            mock.restart_device(site_id, DeviceId::new(Uuid::new_v4()))
+                .await,
+            Err(UnifiError::NotFound { .. })
+        ));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_fake_unifi_server_serves_fixtures() {
+        use crate::test_util::FakeUnifiServer;
+
+        let fake = FakeUnifiServer::start().await;
+        let client = fake.client();
+        let site_id = client.default_site_id().await.unwrap();
+        assert_eq!(site_id, fake.site_id());
+
+        let page = client
+            .list_devices(site_id, &ListParams::page(1, 1))
+            .await
+            .unwrap();
+        assert_eq!(page.total_count, 2);
+        assert_eq!(page.data[0].id, fake.access_point_id());
+
+        let details = client
+            .get_device_details(site_id, fake.access_point_id())
+            .await
+            .unwrap();
+        assert_eq!(details.uplink.unwrap().device_id, fake.gateway_id());
+        client
+            .get_device_statistics(site_id, fake.gateway_id())
+            .await
+            .unwrap();
+        assert!(client
+            .restart_device(site_id, fake.gateway_id())
+            .await
+            .is_ok());
+
+        let clients = client.list_all_clients(site_id).await.unwrap();
+        assert_eq!(clients.len(), 2);
+        let details = client
+            .get_client_details(site_id, fake.wireless_client_id())
+            .await
+            .unwrap();
+        assert_eq!(details.uplink_device_id(), Some(fake.access_point_id()));
+
+        let vouchers = client
+            .generate_vouchers(site_id, &VoucherCreateRequest::new("Event", 60).count(3))
+            .await
+            .unwrap();
+        assert_eq!(vouchers.len(), 3);
+        assert!(client.get_controller_health().await.unwrap().is_healthy());
+
+        let missing = client
+            .get_device_details(site_id, DeviceId::new(Uuid::new_v4()))
+            .await;
+        assert!(matches!(
+            missing.map_err(UnifiError::into_inner),
+            Err(UnifiError::NotFound { .. })
+        ));
+
+        let unauthorized = UnifiClientBuilder::new(fake.uri())
+            .api_key("wrong")
+            .build()
+            .unwrap()
+            .list_sites(&ListParams::default())
+            .await;
+        assert!(matches!(
+            unauthorized.map_err(UnifiError::into_inner),
+            Err(UnifiError::Unauthorized {
+                status_code: 401,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_cassette_records_and_replays() {
+        use crate::cassette::{Cassette, CassetteMode};
+        use std::sync::Arc;
+
+        let site_id = SiteId::new(Uuid::new_v4());
+        let file = std::env::temp_dir().join(format!("unifi-cassette-{}.json", Uuid::new_v4()));
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": site_id, "name": "Default" }),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "statusCode": 500,
+                "message": "boom"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let recording = Arc::new(Cassette::record(&file));
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .cassette(recording.clone())
+            .build()
+            .unwrap();
+        let sites = client.list_sites(&ListParams::default()).await.unwrap();
+        assert_eq!(sites.data[0].id, site_id);
+        assert!(matches!(
+            client.get_info().await,
+            Err(UnifiError::Request { path, .. }) if path == "/v1/info"
+        ));
+        recording.save().unwrap();
+        assert_eq!(recording.interactions().len(), 2);
+        assert!(!std::fs::read_to_string(&file).unwrap().contains("test-key"));
+
+        let replaying = Arc::new(Cassette::replay(&file).unwrap());
+        assert_eq!(replaying.mode(), CassetteMode::Replay);
+        let client = UnifiClientBuilder::new("http://cassette.invalid")
+            .api_key("test-key")
+            .cassette(replaying)
+            .build()
+            .unwrap();
+        let sites = client.list_sites(&ListParams::default()).await.unwrap();
+        assert_eq!(sites.data[0].id, site_id);
+        let error = client.get_info().await.unwrap_err();
+        assert!(matches!(&error, UnifiError::Request { path, .. } if path == "/v1/info"));
+        assert!(matches!(
+            error.into_inner(),
+            UnifiError::ServerError {
+                status_code: 500,
+                ..
+            }
+        ));
+        assert!(matches!(
+            client
+                .list_sites(&ListParams::default())
+                .await
+                .map_err(UnifiError::into_inner),
+            Err(UnifiError::Config(_))
+        ));
+
+        drop(recording);
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_builders_produce_servable_models() {
+        use crate::test_util::{
+            ClientOverviewBuilder, DeviceDetailsBuilder, DeviceStatisticsBuilder,
+        };
+
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let gateway = DeviceId::new(Uuid::new_v4());
+        let details = DeviceDetailsBuilder::new()
+            .name("Office AP")
+            .uplink(gateway)
+            .ports(2)
+            .radio(FrequencyBand::Band5GHz)
+            .build();
+        let statistics = DeviceStatisticsBuilder::new()
+            .cpu_utilization_pct(91.0)
+            .radio(FrequencyBand::Band5GHz, 3.5)
+            .port(1, 1_000, 2_000)
+            .build();
+        let wireless = ClientOverviewBuilder::wireless()
+            .uplink_device_id(details.id)
+            .build();
+
+        let device = format!("/v1/sites/{}/devices/{}", site_id, details.id);
+        Mock::given(method("GET"))
+            .and(path(&device))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&details))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{}/statistics/latest", device)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&statistics))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![serde_json::to_value(&wireless).unwrap()])),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let fetched = client
+            .get_device_details(site_id, details.id)
+            .await
+            .unwrap();
+        assert_eq!(fetched.uplink.unwrap().device_id, gateway);
+        assert_eq!(fetched.interfaces.as_ref().unwrap().ports.len(), 2);
+        let fetched = client
+            .get_device_statistics(site_id, details.id)
+            .await
+            .unwrap();
+        assert_eq!(fetched.cpu_utilization_pct, Some(91.0));
+        assert_eq!(fetched.interfaces.unwrap().ports[0].rx_bytes, Some(2_000));
+        let clients = client.list_all_clients(site_id).await.unwrap();
+        match &clients[0] {
+            ClientOverview::Wireless(client) => {
+                assert_eq!(client.uplink_device_id, details.id);
+                assert_eq!(client.band, Some(FrequencyBand::Band5GHz));
+            }
+            other => panic!("expected wireless client, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ip_address_deserialization() {
+        let mut device = device_overview_json(Uuid::new_v4(), "Gateway");
+        let parsed: DeviceOverview = serde_json::from_value(device.clone()).unwrap();
+        assert_eq!(parsed.ip_address, Some(IpAddr::from([192, 168, 1, 1])));
+
+        device["ipAddress"] = serde_json::json!("");
+        let parsed: DeviceOverview = serde_json::from_value(device.clone()).unwrap();
+        assert_eq!(parsed.ip_address, None);
+
+        device["ipAddress"] = serde_json::Value::Null;
+        let parsed: DeviceOverview = serde_json::from_value(device.clone()).unwrap();
+        assert_eq!(parsed.ip_address, None);
+
+        device["ipAddress"] = serde_json::json!("192.168.1");
+        let err = serde_json::from_value::<DeviceOverview>(device.clone()).unwrap_err();
+        assert!(err.to_string().contains("an IP address"));
+    }
+
+    #[test]
+    fn test_id_newtypes() {
+        let uuid = Uuid::new_v4();
+        let site_id = SiteId::from(uuid);
+        assert_eq!(site_id.as_uuid(), uuid);
+        assert_eq!(site_id.to_string(), uuid.to_string());
+        assert_eq!(uuid.to_string().parse::<SiteId>().unwrap(), site_id);
+        assert!("not-a-uuid".parse::<DeviceId>().is_err());
+
+        let json = serde_json::to_value(DeviceId::new(uuid)).unwrap();
+        assert_eq!(json, serde_json::json!(uuid));
+        let client_id: ClientId = serde_json::from_value(json).unwrap();
+        assert_eq!(Uuid::from(client_id), uuid);
+    }
+
+    #[test]
+    fn test_unknown_enum_variants() {
+        let mut device = device_details_json(Uuid::new_v4(), "HIBERNATING");
+        device["interfaces"]["ports"] = serde_json::json!([{
+            "idx": 1,
+            "state": "FLAPPING",
+            "connector": "OSFP",
+            "maxSpeedMbps": 800000,
+            "speedMbps": 0
+        }]);
+        device["interfaces"]["radios"] = serde_json::json!([{
+            "wlanStandard": "802.11bn",
+            "frequencyGHz": 6,
+            "channelWidthMHz": 320,
+            "channel": 37
+        }]);
+        let device: DeviceDetails = serde_json::from_value(device).unwrap();
+        assert_eq!(device.state, DeviceState::Unknown);
+        let interfaces = device.interfaces.unwrap();
+        assert!(matches!(interfaces.ports[0].state, PortState::Unknown));
+        assert!(matches!(
+            interfaces.ports[0].connector,
+            ConnectorType::Unknown
+        ));
+        assert!(matches!(
+            interfaces.radios[0].wlan_standard,
+            Some(WlanStandard::Unknown)
+        ));
+
+        let mut client = wireless_client_json(Uuid::new_v4(), None);
+        client["type"] = serde_json::json!("SATELLITE");
+        let client: ClientOverview = serde_json::from_value(client).unwrap();
+        match &client {
+            ClientOverview::Unknown(unknown) => assert_eq!(unknown.client_type, "SATELLITE"),
+            other => panic!("expected an unknown client, got {:?}", other),
+        }
+        assert_eq!(client.base().name.as_deref(), Some("Phone"));
+        assert_eq!(client.mac_address(), None);
+        assert_eq!(
+            serde_json::to_value(&client).unwrap()["type"],
+            serde_json::json!("SATELLITE")
+        );
+    }
+
+    #[cfg(feature = "raw-fields")]
+    #[test]
+    fn test_raw_fields_are_preserved() {
+        let mut details = device_details_json(Uuid::new_v4(), "ONLINE");
+        details["thermalState"] = serde_json::json!("NORMAL");
+        let parsed: DeviceDetails = serde_json::from_value(details).unwrap();
+        assert_eq!(parsed.extra.len(), 1);
+        assert_eq!(parsed.extra["thermalState"], "NORMAL");
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap()["thermalState"],
+            "NORMAL"
+        );
+
+        let mut client = wireless_client_json(Uuid::new_v4(), None);
+        client["hostname"] = serde_json::json!("phone.local");
+        let parsed: ClientOverview = serde_json::from_value(client).unwrap();
+        assert_eq!(parsed.base().extra["hostname"], "phone.local");
+        assert!(!parsed.base().extra.contains_key("macAddress"));
+    }
+
+    #[test]
+    fn test_model_constructors() {
+        let page = Page::new(vec![1, 2, 3]);
+        assert_eq!(
+            (page.offset, page.limit, page.count, page.total_count),
+            (0, 3, 3, 3)
+        );
+        assert_eq!(page.skipped, 0);
+        assert!(Page::<i32>::default().data.is_empty());
+
+        let device = DeviceDetails::default();
+        assert_eq!(device.state, DeviceState::Unknown);
+        assert_eq!(device.clone(), device);
+    }
+
+    #[tokio::test]
+    async fn test_error_taxonomy() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_for = |status: u16| DeviceId::new(Uuid::from_u128(status as u128));
+        for (status, message) in [
+            (401, "unauthorized"),
+            (403, "forbidden"),
+            (404, "missing"),
+            (409, "conflict"),
+            (503, "unavailable"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!(
+                    "/v1/sites/{}/devices/{}",
+                    site_id,
+                    device_for(status)
+                )))
+                .respond_with(
+                    ResponseTemplate::new(status).set_body_json(serde_json::json!({
+                        "statusCode": status,
+                        "message": message
+                    })),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let fetch = |status: u16| {
+            let client = &client;
+            async move {
+                client
+                    .get_device_details(site_id, device_for(status))
+                    .await
+                    .unwrap_err()
+                    .into_inner()
+            }
+        };
+
+        assert!(matches!(
+            fetch(401).await,
+            UnifiError::Unauthorized {
+                status_code: 401,
+                ..
+            }
+        ));
+        assert!(matches!(
+            fetch(403).await,
+            UnifiError::Forbidden {
+                status_code: 403,
+                ..
+            }
+        ));
+        match fetch(404).await {
+            UnifiError::NotFound { resource, message } => {
+                assert_eq!(
+                    resource,
+                    format!("/v1/sites/{}/devices/{}", site_id, device_for(404))
+                );
+                assert_eq!(message, "missing");
+            }
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+        let conflict = fetch(409).await;
+        assert!(matches!(
+            conflict,
+            UnifiError::Api {
+                status_code: 409,
+                ..
+            }
+        ));
+        assert!(!conflict.is_retryable());
+
+        let unavailable = fetch(503).await;
+        assert!(matches!(
+            unavailable,
+            UnifiError::ServerError {
+                status_code: 503,
+                ..
+            }
+        ));
+        assert_eq!(unavailable.status_code(), Some(503));
+        assert!(unavailable.is_retryable());
+        assert!(UnifiError::RateLimited {
+            retry_after: None,
+            message: String::new()
+        }
+        .is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_decode_errors_keep_the_body() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html>login</html>"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        match client
+            .list_devices(site_id, &ListParams::default())
+            .await
+            .map_err(UnifiError::into_inner)
+        {
+            Err(UnifiError::Decode { status, body, .. }) => {
+                assert_eq!(status, 200);
+                assert_eq!(body, "<html>login</html>");
+            }
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+        // Error responses are classified by their status, whatever their body.
+        let error = client
+            .list_clients(site_id, &ListParams::default())
+            .await
+            .unwrap_err();
+        assert!(error.is_retryable());
+        match error.into_inner() {
+            UnifiError::ServerError {
+                status_code,
+                message,
+            } => {
+                assert_eq!(status_code, 502);
+                assert_eq!(message, "Bad Gateway");
+            }
+            other => panic!("expected a server error, got {:?}", other),
+        }
+        match client
+            .get_with_accept("/v1/export.csv", "text/csv")
+            .await
+            .map_err(UnifiError::into_inner)
+        {
+            Err(UnifiError::NotFound { resource, message }) => {
+                assert_eq!(resource, "/v1/export.csv");
+                assert_eq!(message, "Not Found");
+            }
+            other => panic!("expected a not found error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors_name_the_request() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "statusCode": 404,
+                "message": "missing"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let error = client
+            .get_device_details(site_id, device_id)
+            .await
+            .unwrap_err();
+        let expected_path = format!("/v1/sites/{}/devices/{}", site_id, device_id);
+        match &error {
+            UnifiError::Request { method, path, .. } => {
+                assert_eq!(method, reqwest::Method::GET);
+                assert_eq!(path, &expected_path);
+            }
+            other => panic!("expected request context, got {:?}", other),
+        }
+        assert!(error
+            .to_string()
+            .starts_with(&format!("GET {}: ", expected_path)));
+        assert!(matches!(error.inner(), UnifiError::NotFound { .. }));
+        assert_eq!(error.status_code(), Some(404));
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_base_url_normalization() {
+        let client = UnifiClientBuilder::new(" https://192.168.1.1/proxy/network/integrations// ")
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.base_url(),
+            "https://192.168.1.1/proxy/network/integrations"
+        );
+
+        for base_url in ["192.168.1.1", "ftp://192.168.1.1", "https://host/?site=x"] {
+            let result = UnifiClientBuilder::new(base_url)
+                .api_key("test-key")
+                .build();
+            assert!(
+                matches!(&result, Err(UnifiError::Config(message)) if message.contains(base_url)),
+                "{}",
+                base_url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_detected_appends_integrations_path() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/proxy/network/integrations/v1/info"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "applicationVersion": "9.0.108"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(format!("{}/", server.uri()))
+            .api_key("test-key")
+            .build_detected()
+            .await
+            .unwrap();
+        assert_eq!(
+            client.base_url(),
+            format!("{}/proxy/network/integrations", server.uri())
+        );
+
+        let missing = UnifiClientBuilder::new(format!("{}/nothing", server.uri()))
+            .api_key("test-key")
+            .build_detected()
+            .await;
+        assert!(
+            matches!(missing, Err(UnifiError::Config(message)) if message.contains("/nothing/proxy"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capabilities() {
+        use crate::capabilities::Feature;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "applicationVersion": "9.1.120"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let site_id = SiteId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/hotspot/vouchers", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .require_version(true)
+            .build()
+            .unwrap();
+        let capabilities = client.capabilities().await.unwrap();
+        assert_eq!(capabilities.version(), ApplicationVersion::new(9, 1, 120));
+        assert!(capabilities.supports(Feature::DeviceStatistics));
+        assert!(capabilities.supports(Feature::Vouchers));
+        assert!(!capabilities.supports(Feature::DeviceAdoption));
+
+        // Version gating agrees with `supports`, and reuses the cached version, so the
+        // mock's single expected call is not exceeded.
+        assert_eq!(client.capabilities().await.unwrap(), capabilities);
+        client
+            .list_vouchers(site_id, &ListParams::default())
+            .await
+            .unwrap();
+        match client.list_pending_devices(site_id).await {
+            Err(UnifiError::Config(message)) => assert!(message.contains("9.3.0")),
+            other => panic!("Expected Config error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        use crate::models::health::ConnectivityStatus;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .and(header("X-API-KEY", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "applicationVersion": "9.0.108"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "statusCode": 401,
+                "message": "invalid API key"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/bare/v1/info"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/router/v1/info"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html>router</html>"))
+            .mount(&server)
+            .await;
+
+        let check = |base_url: String, api_key: &'static str| async move {
+            UnifiClientBuilder::new(base_url)
+                .api_key(api_key)
+                .build()
+                .unwrap()
+                .health_check()
+                .await
+        };
+
+        let healthy = check(server.uri(), "test-key").await;
+        assert!(healthy.is_healthy());
+        assert_eq!(healthy.version, Some(ApplicationVersion::new(9, 0, 108)));
+        assert_eq!(healthy.error, None);
+
+        let cases = [
+            (server.uri(), ConnectivityStatus::Unauthorized),
+            (
+                format!("{}/bare", server.uri()),
+                ConnectivityStatus::Unauthorized,
+            ),
+            (
+                format!("{}/router", server.uri()),
+                ConnectivityStatus::NotUnifi,
+            ),
+            (
+                server.uri().replace("http://", "https://"),
+                ConnectivityStatus::TlsFailure,
+            ),
+            (
+                "http://127.0.0.1:1".to_string(),
+                ConnectivityStatus::Unreachable,
+            ),
+            (
+                "http://controller.invalid".to_string(),
+                ConnectivityStatus::DnsFailure,
+            ),
+        ];
+        for (base_url, status) in cases {
+            let report = check(base_url.clone(), "wrong").await;
+            assert_eq!(report.status, status, "{}: {:?}", base_url, report.error);
+            assert!(report.error.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_site_handle() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![device_overview_json(device_id, "Gateway")])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/statistics/latest",
+                site_id, device_id
+            )))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(SAMPLE_STATISTICS_JSON, "application/json"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![wireless_client_json(device_id, Some("5"))])),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let site = client.site(site_id);
+        assert_eq!(site.id(), site_id);
+
+        let devices = site.devices().await.unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].id, device_id);
+        assert_eq!(site.device(device_id).await.unwrap().id, device_id);
+        assert_eq!(site.statistics(device_id).await.unwrap().uptime_sec, 737201);
+        assert_eq!(site.clients().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_for_each_bounds_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let report = crate::bulk::for_each(0..10u32, 3, |n| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if n % 4 == 0 {
+                    Err(UnifiError::InvalidInput(n.to_string()))
+                } else {
+                    Ok(n * 2)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(report.len(), 10);
+        assert!(!report.is_complete());
+        let mut failed: Vec<u32> = report.failed.iter().map(|(n, _)| *n).collect();
+        failed.sort();
+        assert_eq!(failed, [0, 4, 8]);
+        assert!(report
+            .succeeded
+            .iter()
+            .all(|(n, doubled)| *doubled == n * 2));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_for_each_device() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let (online, broken) = (DeviceId::new(Uuid::new_v4()), DeviceId::new(Uuid::new_v4()));
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(online, "Switch"),
+                device_overview_json(broken, "Access Point"),
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, online)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(online, "ONLINE")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, broken)))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "statusCode": 500,
+                "message": "boom"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let report = crate::bulk::for_each_device(&client, site_id, 4, |device| {
+            let client = &client;
+            async move { client.get_device_details(site_id, device.id).await }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.succeeded.len(), 1);
+        assert_eq!(report.succeeded[0].0, online);
+        assert_eq!(report.succeeded[0].1.state, DeviceState::Online);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, broken);
+        assert_eq!(report.failed[0].1.status_code(), Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_fleet_client_tags_and_isolates_controllers() {
+        use crate::fleet::FleetClient;
+
+        let (office, branch) = (MockServer::start().await, MockServer::start().await);
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": site_id, "name": "Default" }),
+            ])))
+            .mount(&office)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![device_overview_json(device_id, "Switch")])),
+            )
+            .mount(&office)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "statusCode": 401,
+                "message": "invalid API key"
+            })))
+            .mount(&branch)
+            .await;
+
+        let fleet: FleetClient = [
+            ("office", mock_client(&office)),
+            ("branch", mock_client(&branch)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(fleet.names().collect::<Vec<_>>(), ["branch", "office"]);
+
+        let devices = fleet.list_all_devices().await;
+        assert!(!devices.is_complete());
+        assert_eq!(devices.items.len(), 1);
+        assert_eq!(devices.items[0].controller, "office");
+        assert_eq!(devices.items[0].site_id, site_id);
+        assert_eq!(devices.items[0].item.id, device_id);
+        assert_eq!(devices.failed.len(), 1);
+        assert_eq!(devices.failed[0].0, "branch");
+        assert_eq!(devices.failed[0].1.status_code(), Some(401));
+
+        let sites = fleet.list_all_sites().await;
+        assert_eq!(sites.items.len(), 1);
+        assert_eq!(sites.items[0].item.name.as_deref(), Some("Default"));
+    }
+
+    #[tokio::test]
+    async fn test_inventory_snapshot() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": site_id, "name": "Default" }),
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![device_overview_json(device_id, "Switch")])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![wireless_client_json(device_id, None)])),
+            )
+            .mount(&server)
+            .await;
+
+        let inventory = mock_client(&server).snapshot().await.unwrap();
+        assert_eq!(inventory.sites.len(), 1);
+        let site = inventory.site(site_id).unwrap();
+        assert_eq!(site.site.name.as_deref(), Some("Default"));
+        assert_eq!(site.snapshot.devices[0].state, DeviceState::Online);
+        assert_eq!(
+            inventory
+                .devices()
+                .map(|(site, _)| site)
+                .collect::<Vec<_>>(),
+            [site_id]
+        );
+        assert_eq!(inventory.clients().count(), 1);
+
+        let json = serde_json::to_string(&inventory).unwrap();
+        let restored: crate::snapshot::Inventory = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.taken_at, inventory.taken_at);
+        assert_eq!(restored.sites[0].snapshot.devices[0].id, device_id);
+    }
+
+    #[test]
+    fn test_inventory_diff() {
+        use crate::snapshot::{Inventory, InventoryChange, SiteInventory};
+
+        let device = |id: DeviceId, state: &str, firmware: &str| -> DeviceDetails {
+            let mut details = device_details_json(id, state);
+            details["firmwareVersion"] = serde_json::json!(firmware);
+            serde_json::from_value(details).unwrap()
+        };
+        let client = |id: ClientId, ip: &str| -> ClientOverview {
+            let mut client = wireless_client_json(Uuid::new_v4(), None);
+            client["id"] = serde_json::json!(id);
+            client["ipAddress"] = serde_json::json!(ip);
+            serde_json::from_value(client).unwrap()
+        };
+        let site = |id: SiteId, devices, clients| SiteInventory {
+            site: serde_json::from_value(serde_json::json!({ "id": id })).unwrap(),
+            snapshot: SiteSnapshot {
+                site_id: id,
+                taken_at: chrono::Utc::now(),
+                devices,
+                clients,
+            },
+        };
+        let (home, closed, opened) = (
+            SiteId::new(Uuid::new_v4()),
+            SiteId::new(Uuid::new_v4()),
+            SiteId::new(Uuid::new_v4()),
+        );
+        let (gateway, new_ap) = (DeviceId::new(Uuid::new_v4()), DeviceId::new(Uuid::new_v4()));
+        let (laptop, phone) = (ClientId::new(Uuid::new_v4()), ClientId::new(Uuid::new_v4()));
+
+        let before = Inventory {
+            taken_at: chrono::Utc::now(),
+            sites: vec![
+                site(
+                    home,
+                    vec![device(gateway, "ONLINE", "4.0.6")],
+                    vec![
+                        client(laptop, "192.168.1.20"),
+                        client(phone, "192.168.1.21"),
+                    ],
+                ),
+                site(closed, vec![], vec![]),
+            ],
+        };
+        let after = Inventory {
+            taken_at: chrono::Utc::now(),
+            sites: vec![
+                site(
+                    home,
+                    vec![device(gateway, "OFFLINE", "4.0.21")],
+                    vec![client(laptop, "192.168.1.42")],
+                ),
+                site(opened, vec![device(new_ap, "ONLINE", "6.6.65")], vec![]),
+            ],
+        };
+
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                InventoryChange::DeviceStateChanged {
+                    site_id: home,
+                    device_id: gateway,
+                    before: DeviceState::Online,
+                    after: DeviceState::Offline,
+                },
+                InventoryChange::FirmwareChanged {
+                    site_id: home,
+                    device_id: gateway,
+                    before: "4.0.6".to_string(),
+                    after: "4.0.21".to_string(),
+                },
+                InventoryChange::ClientLeft {
+                    site_id: home,
+                    client_id: phone,
+                },
+                InventoryChange::ClientIpChanged {
+                    site_id: home,
+                    client_id: laptop,
+                    before: "192.168.1.20".parse().ok(),
+                    after: "192.168.1.42".parse().ok(),
+                },
+                InventoryChange::SiteRemoved { site_id: closed },
+                InventoryChange::SiteAdded { site_id: opened },
+                InventoryChange::DeviceAdded {
+                    site_id: opened,
+                    device_id: new_ap,
+                },
+            ]
+        );
+        assert!(before.diff(&before).is_empty());
+
+        let json = serde_json::to_value(InventoryChange::SiteAdded { site_id: opened }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "type": "siteAdded", "siteId": opened })
+        );
+    }
+
+    #[test]
+    fn test_topology_to_mermaid() {
+        use crate::topology::Topology;
+
+        let gateway = DeviceId::new(Uuid::new_v4());
+        let switch = DeviceId::new(Uuid::new_v4());
+        let node = |id, name: &str, uplink| TopologyNode {
+            id,
+            name: name.to_string(),
+            model: "USW-24".to_string(),
+            uplink_device_id: uplink,
+            is_gateway: uplink.is_none(),
+        };
+        let orphan = DeviceId::new(Uuid::new_v4());
+        let mut topology = Topology::new(vec![
+            node(orphan, "Attic AP", Some(DeviceId::new(Uuid::new_v4()))),
+            node(gateway, "Gateway", None),
+            node(switch, "Rack \"A\" #1", Some(gateway)),
+        ]);
+
+        let mermaid = topology.to_mermaid();
+        let (gateway_id, switch_id) = (
+            format!("d{}", gateway.as_uuid().simple()),
+            format!("d{}", switch.as_uuid().simple()),
+        );
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains(&format!("    {}([\"Gateway\"])\n", gateway_id)));
+        assert!(mermaid.contains(&format!(
+            "    {}[\"Rack #quot;A#quot; #35;1\"]\n",
+            switch_id
+        )));
+        assert!(mermaid.contains(&format!("    {} --> {}\n", gateway_id, switch_id)));
+
+        assert_eq!(topology.to_dot(), topology_to_dot(&topology.nodes));
+        assert_eq!(
+            topology.roots().map(|node| node.id).collect::<Vec<_>>(),
+            [gateway]
+        );
+        assert_eq!(
+            topology.orphans().map(|node| node.id).collect::<Vec<_>>(),
+            [orphan]
+        );
+        assert_eq!(
+            topology
+                .children(gateway)
+                .map(|node| node.id)
+                .collect::<Vec<_>>(),
+            [switch]
+        );
+
+        // Without a gateway, every node without an uplink in the topology is a root.
+        topology.nodes.retain(|node| node.id != gateway);
+        assert_eq!(
+            topology.roots().map(|node| node.id).collect::<Vec<_>>(),
+            [orphan, switch]
+        );
+        assert_eq!(topology.orphans().count(), 0);
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn test_cache_memoizes_get_responses() {
+        use crate::cache::CachePolicy;
+        use crate::models::health::ConnectivityStatus;
+
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let details = format!("/v1/sites/{}/devices/{}", site_id, device_id);
+        Mock::given(method("GET"))
+            .and(path(details.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![device_overview_json(device_id, "Gateway")])),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("{}/actions", details)))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .cache(
+                CachePolicy::new(Duration::from_secs(60))
+                    .endpoint_ttl("/v1/sites/*/devices", Duration::ZERO),
+            )
+            .build()
+            .unwrap();
+
+        // Served from the cache the second time.
+        for _ in 0..2 {
+            let device = client.get_device_details(site_id, device_id).await.unwrap();
+            assert_eq!(device.id, device_id);
+        }
+        // A zero time-to-live is never cached.
+        for _ in 0..2 {
+            assert_eq!(client.list_all_devices(site_id).await.unwrap().len(), 1);
+        }
+        // An action invalidates the cache.
+        client.restart_device(site_id, device_id).await.unwrap();
+        client.get_device_details(site_id, device_id).await.unwrap();
+
+        // Health checks always reach the controller, so they see it go down.
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.1.120" })),
+            )
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        assert_eq!(
+            client.health_check().await.status,
+            ConnectivityStatus::Healthy
+        );
+        assert_ne!(
+            client.health_check().await.status,
+            ConnectivityStatus::Healthy
+        );
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn test_cache_serves_stale_while_revalidating() {
+        use crate::cache::CachePolicy;
+
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let details = format!("/v1/sites/{}/devices/{}", site_id, device_id);
+        Mock::given(method("GET"))
+            .and(path(details.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(details.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "OFFLINE")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .cache(
+                CachePolicy::new(Duration::from_millis(50))
+                    .stale_while_revalidate(Duration::from_secs(60)),
+            )
+            .build()
+            .unwrap();
+
+        let device = client.get_device_details(site_id, device_id).await.unwrap();
+        assert_eq!(device.state, DeviceState::Online);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // The expired response is returned while it is refreshed in the background.
+        let device = client.get_device_details(site_id, device_id).await.unwrap();
+        assert_eq!(device.state, DeviceState::Online);
+
+        let mut refreshed = false;
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let device = client.get_device_details(site_id, device_id).await.unwrap();
+            if device.state == DeviceState::Offline {
+                refreshed = true;
+                break;
+            }
+        }
+        assert!(refreshed);
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_cache_policy_ttl() {
+        use crate::cache::CachePolicy;
+
+        let policy = CachePolicy::new(Duration::from_secs(30))
+            .endpoint_ttl("/v1/sites", Duration::from_secs(300))
+            .endpoint_ttl("/v1/sites/*/devices/*/statistics/latest", Duration::ZERO);
+        assert_eq!(policy.ttl("/v1/sites/"), Duration::from_secs(300));
+        assert_eq!(
+            policy.ttl("/v1/sites/abc/devices/def/statistics/latest"),
+            Duration::ZERO
+        );
+        assert_eq!(
+            policy.ttl("/v1/sites/abc/devices/def"),
+            Duration::from_secs(30)
+        );
+        let policy = policy.endpoint_ttl("/v1/info", Duration::from_secs(300));
+        assert_eq!(policy.ttl("/v1/info"), Duration::ZERO);
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn test_cache_revalidates_with_etag() {
+        use crate::cache::CachePolicy;
+
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let clients = format!("/v1/sites/{}/clients", site_id);
+        let device_id = DeviceId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path(clients.as_str()))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(clients.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(page_json(vec![wireless_client_json(device_id, Some("5"))])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .cache(CachePolicy::new(Duration::from_millis(10)))
+            .build()
+            .unwrap();
+
+        // Each expired read is answered by a `304`, renewing the cached body.
+        for _ in 0..3 {
+            assert_eq!(client.list_all_clients(site_id).await.unwrap().len(), 1);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_compression_negotiation() {
+        let server = MockServer::start().await;
+        // Echoes the encodings the client accepts.
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(|request: &wiremock::Request| {
+                let encodings = request
+                    .headers
+                    .get("accept-encoding")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                ResponseTemplate::new(200).set_body_string(encodings)
+            })
+            .mount(&server)
+            .await;
+
+        for enabled in [true, false] {
+            let client = UnifiClientBuilder::new(server.uri())
+                .api_key("test-key")
+                .compression(enabled)
+                .build()
+                .unwrap();
+            let body = client
+                .get_with_accept("/v1/sites", "text/plain")
+                .await
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&body).contains("gzip"), enabled);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pool_max_idle_per_host() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        // Answers every request on a connection, keeping it alive.
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    while stream.read(&mut buf).await.is_ok_and(|n| n > 0) {
+                        let body = r#"{"applicationVersion":"9.0.108"}"#;
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        if stream.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        for (max_idle, expected) in [(None, 1), (Some(0), 2)] {
+            connections.store(0, Ordering::SeqCst);
+            let mut builder = UnifiClientBuilder::new(format!("http://{}", addr))
+                .api_key("test-key")
+                .pool_idle_timeout(Some(Duration::from_secs(30)))
+                .tcp_keepalive(Duration::from_secs(15));
+            if let Some(max_idle) = max_idle {
+                builder = builder.pool_max_idle_per_host(max_idle);
+            }
+            let client = builder.build().unwrap();
+            client.get_info().await.unwrap();
+            client.get_info().await.unwrap();
+            assert_eq!(connections.load(Ordering::SeqCst), expected);
+        }
+    }
+
+    #[cfg(feature = "config")]
+    #[tokio::test]
+    async fn test_config_builds_clients() {
+        use crate::config::UnifiConfig;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .and(header("X-API-KEY", "office-key"))
+            .and(header("user-agent", "netops-agent/1.2"))
+            .and(header("X-Proxy-Auth", "secret"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        std::env::set_var("UNIFI_RS_TEST_HOME_KEY", "home-key");
+        let config: UnifiConfig = format!(
+            r#"
+            [controllers.home]
+            base_url = "https://192.168.1.1/proxy/network/integrations/"
+            api_key_env = "UNIFI_RS_TEST_HOME_KEY"
+            verify_ssl = false
+
+            [controllers.office]
+            base_url = "{}"
+            api_key = "office-key"
+            request_timeout_secs = 30
+            user_agent = "netops-agent/1.2"
+            headers = {{ "X-Proxy-Auth" = "secret" }}
+            "#,
+            server.uri()
+        )
+        .parse()
+        .unwrap();
+
+        assert!(!config.controller("home").unwrap().verify_ssl);
+        assert_eq!(
+            config.client("home").unwrap().base_url(),
+            "https://192.168.1.1/proxy/network/integrations"
+        );
+        let info = config.client("office").unwrap().get_info().await.unwrap();
+        assert_eq!(info.application_version, "9.0.108");
+        assert_eq!(config.fleet().unwrap().len(), 2);
+
+        assert!(matches!(config.client("lab"), Err(UnifiError::Config(_))));
+        // With two controllers and no `default`, there is no default client.
+        assert!(matches!(
+            config.default_client(),
+            Err(UnifiError::Config(_))
+        ));
+        assert!(matches!(
+            "[controllers.lab]\nbase_url = \"https://10.0.0.1\"\napi_key = \"k\"\nverify = false"
+                .parse::<UnifiConfig>(),
+            Err(UnifiError::Config(_))
+        ));
+        let config: UnifiConfig = "[controllers.lab]\nbase_url = \"https://10.0.0.1\""
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            config.default_client(),
+            Err(UnifiError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_provider_rotation() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .and(header("X-API-KEY", "rotated-key"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" })),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .and(header("X-API-KEY", "old-key"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        // Hands out the old key first, as if it was rotated after the client started.
+        let reads = Arc::new(AtomicUsize::new(0));
+        let provider_reads = reads.clone();
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("ignored")
+            .api_key_provider(move || {
+                let read = provider_reads.fetch_add(1, Ordering::SeqCst);
+                async move { Ok(if read == 0 { "old-key" } else { "rotated-key" }.to_string()) }
+            })
+            .build()
+            .unwrap();
+
+        client.get_info().await.unwrap();
+        client.get_info().await.unwrap();
+        assert_eq!(reads.load(Ordering::SeqCst), 2);
+
+        // A key that stays rejected surfaces the 401.
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key_provider(|| async { Ok("old-key".to_string()) })
+            .build()
+            .unwrap();
+        let error = client.get_info().await.unwrap_err();
+        assert_eq!(error.status_code(), Some(401));
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_and_default_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/info"))
+            .and(header("user-agent", "netops-agent/1.2"))
+            .and(header("X-Proxy-Auth", "secret"))
+            .and(header("X-API-KEY", "test-key"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "applicationVersion": "9.0.108" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .user_agent("netops-agent/1.2")
+            .header("X-Proxy-Auth", "stale")
+            .header("X-Proxy-Auth", "secret")
+            .header("X-API-KEY", "not-the-key")
+            .build()
+            .unwrap();
+        client.get_info().await.unwrap();
+
+        let result = UnifiClientBuilder::new(server.uri())
+            .api_key("test-key")
+            .header("X Proxy", "secret")
+            .build();
+        assert!(matches!(result, Err(UnifiError::Config(_))));
+    }
+
+    #[test]
+    fn test_export_inventory() {
+        use crate::export::{to_csv, to_json, RowKind};
+        use crate::snapshot::{Inventory, SiteInventory};
+
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let mut client = wireless_client_json(device_id, None);
+        client["name"] = serde_json::json!("Alice's \"work\" laptop, 2");
+        let inventory = Inventory {
+            taken_at: chrono::Utc::now(),
+            sites: vec![SiteInventory {
+                site: serde_json::from_value(serde_json::json!({ "id": site_id, "name": "HQ" }))
+                    .unwrap(),
+                snapshot: SiteSnapshot {
+                    site_id,
+                    taken_at: chrono::Utc::now(),
+                    devices: vec![
+                        serde_json::from_value(device_details_json(device_id, "ONLINE")).unwrap(),
+                    ],
+                    clients: vec![serde_json::from_value(client).unwrap()],
+                },
+            }],
+        };
+
+        let rows = crate::export::rows(&inventory);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].kind, RowKind::Device);
+        assert_eq!(rows[0].state.as_deref(), Some("ONLINE"));
+        assert_eq!(rows[1].kind, RowKind::Client);
+        assert_eq!(rows[1].connection_type.as_deref(), Some("WIRELESS"));
+
+        let mut csv = Vec::new();
+        to_csv(&mut csv, &inventory).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "kind,siteId,siteName,id,name,model,macAddress,ipAddress,state,firmwareVersion,connectionType"
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "device,{site_id},HQ,{device_id},Test Device,UHDIW,00:11:22:33:44:55,192.168.1.1,ONLINE,6.6.55,"
+            )
+        );
+        assert!(lines[2].contains(",\"Alice's \"\"work\"\" laptop, 2\",,aa:bb:cc:dd:ee:ff,"));
+        assert!(lines[2].ends_with(",WIRELESS"));
+
+        let mut json = Vec::new();
+        to_json(&mut json, &inventory).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json[0]["kind"], "device");
+        assert_eq!(json[0]["firmwareVersion"], "6.6.55");
+        assert_eq!(json[1]["ipAddress"], "192.168.1.50");
+        assert_eq!(json[1]["model"], serde_json::Value::Null);
+    }
+
+    #[cfg(feature = "influx")]
+    #[test]
+    fn test_influx_line_protocol() {
+        use crate::export::influx::{client_counts, device_statistics, Line};
+
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let site: SiteOverview =
+            serde_json::from_value(serde_json::json!({ "id": site_id, "name": "Main Office" }))
+                .unwrap();
+        let device: DeviceOverview =
+            serde_json::from_value(device_overview_json(device_id, "Core, Switch")).unwrap();
+        let statistics: DeviceStatistics = serde_json::from_str(SAMPLE_STATISTICS_JSON).unwrap();
+
+        assert_eq!(
+            device_statistics(&site, &device, &statistics),
+            format!(
+                "unifi_device,device=Core\\,\\ Switch,device_id={device_id},model={model},\
+                 site=Main\\ Office,site_id={site_id} uptime_sec=737201i,\
+                 cpu_utilization_pct=30.8,memory_utilization_pct=74.2,load_average_1min=1.65,\
+                 load_average_5min=1.28,load_average_15min=1.3,uplink_tx_rate_bps=309720i,\
+                 uplink_rx_rate_bps=32288i 1737231962000000000",
+                model = device.model,
+            )
+        );
+
+        let clients: Vec<ClientOverview> = vec![
+            serde_json::from_value(wireless_client_json(device_id, None)).unwrap(),
+            serde_json::from_value(wireless_client_json(device_id, Some("5"))).unwrap(),
+        ];
+        assert_eq!(
+            client_counts(&site, &clients, statistics.last_heartbeat_at),
+            format!(
+                "unifi_clients,site=Main\\ Office,site_id={site_id} \
+                 total=2i,wired=0i,wireless=2i,vpn=0i,teleport=0i 1737231962000000000"
+            )
+        );
+
+        let line = Line::new("custom measure")
+            .tag("empty", "")
+            .tag("a=b", "x")
+            .string("note", "say \"hi\"\n")
+            .boolean("up", true)
+            .float("nan", f64::NAN);
+        assert_eq!(
+            line.to_string(),
+            "custom\\ measure,a\\=b=x note=\"say \\\"hi\\\" \",up=true"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notify_webhooks() {
+        use crate::notify::{Notifier, Webhook};
+
+        let mock_server = MockServer::start().await;
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let client_id = ClientId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path("/slack"))
+            .and(body_json(serde_json::json!({
+                "text": format!("Device {} went offline", device_id)
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/generic"))
+            .and(body_json(serde_json::json!({
+                "kind": "newClientJoined",
+                "client": client_id.to_string(),
+                "text": "quote \" and "
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let notifier = Notifier::new()
+            .webhook(
+                Webhook::slack(format!("{}/slack", mock_server.uri()))
+                    .only(|event| matches!(event, WatchEvent::DeviceWentOffline { .. })),
+            )
+            .webhook(
+                Webhook::generic(format!("{}/generic", mock_server.uri()))
+                    .only(|event| matches!(event, WatchEvent::NewClientJoined { .. }))
+                    .template(
+                        r#"{"kind": "{{ event }}", "client": "{{client_id}}", "text": "quote \" and {{device_id}}"}"#,
+                    ),
+            );
+
+        notifier
+            .notify(&WatchEvent::DeviceWentOffline { device_id })
+            .await
+            .unwrap();
+        notifier
+            .notify(&WatchEvent::NewClientJoined { client_id })
+            .await
+            .unwrap();
+
+        let generic = Webhook::generic("http://unused");
+        let payload = generic
+            .payload(&WatchEvent::FirmwareUpdated {
+                device_id,
+                from: "6.6.55".to_string(),
+                to: "6.7.10".to_string(),
+            })
+            .unwrap();
+        assert_eq!(payload["event"], "firmwareUpdated");
+        assert_eq!(payload["device_id"], device_id.to_string());
+        assert_eq!(payload["to"], "6.7.10");
+
+        let broken = Webhook::generic("http://unused").template("{\"a\": {{event}}}");
+        assert!(matches!(
+            broken.payload(&WatchEvent::DeviceAdded { device_id }),
+            Err(UnifiError::Config(_))
+        ));
+
+        let failing =
+            Notifier::new().webhook(Webhook::discord(format!("{}/missing", mock_server.uri())));
+        assert!(matches!(
+            failing.notify(&WatchEvent::ClientLeft { client_id }).await,
+            Err(UnifiError::Api {
+                status_code: 404,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_alert_engine_lifecycle() {
+        use crate::alerts::{AlertEngine, AlertRule, AlertStatus, Metric};
+        use crate::models::statistics::{DeviceInterfaceStatistics, WirelessRadioStatistics};
+        use crate::notify::{Notifier, Webhook};
+        use chrono::{TimeZone, Utc};
+
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let minutes = |n: i64| start + chrono::Duration::minutes(n);
+        let stats = |cpu: f64, retries: f64| DeviceStatistics {
+            cpu_utilization_pct: Some(cpu),
+            interfaces: Some(DeviceInterfaceStatistics {
+                radios: vec![
+                    WirelessRadioStatistics {
+                        frequency_ghz: None,
+                        tx_retries_pct: Some(5.0),
+                    },
+                    WirelessRadioStatistics {
+                        frequency_ghz: None,
+                        tx_retries_pct: Some(retries),
+                    },
+                ],
+                ports: vec![],
+            }),
+            ..Default::default()
+        };
+
+        let mut engine = AlertEngine::new()
+            .rule(
+                AlertRule::above("high-cpu", Metric::CpuUtilizationPct, 90.0)
+                    .for_duration(Duration::from_secs(300)),
+            )
+            .rule(AlertRule::above("retries", Metric::TxRetriesPct, 40.0))
+            .rule(AlertRule::device_offline("offline"));
+
+        // Retries fire at once; CPU only after five minutes above the threshold.
+        let alerts = engine.observe_statistics(device_id, &stats(95.0, 45.0), minutes(0));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule, "retries");
+        assert_eq!(alerts[0].value, Some(45.0));
+        assert!(engine
+            .observe_statistics(device_id, &stats(97.0, 50.0), minutes(4))
+            .is_empty());
+        let alerts = engine.observe_statistics(device_id, &stats(96.0, 10.0), minutes(5));
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(
+            (alerts[0].rule.as_str(), alerts[0].status, alerts[0].since),
+            ("high-cpu", AlertStatus::Firing, minutes(0))
+        );
+        assert_eq!(
+            (alerts[1].rule.as_str(), alerts[1].status),
+            ("retries", AlertStatus::Resolved)
+        );
+        assert_eq!(engine.firing().len(), 1);
+
+        // Dipping below the threshold resets the duration.
+        let alerts = engine.observe_statistics(device_id, &stats(50.0, 10.0), minutes(6));
+        assert_eq!(alerts[0].status, AlertStatus::Resolved);
+        engine.observe_statistics(device_id, &stats(95.0, 10.0), minutes(7));
+        assert!(engine
+            .observe_statistics(device_id, &stats(95.0, 10.0), minutes(11))
+            .is_empty());
+
+        let alerts = engine.observe_state(device_id, &DeviceState::Offline, minutes(12));
+        assert_eq!(alerts[0].rule, "offline");
+        assert!(engine
+            .observe_state(device_id, &DeviceState::Offline, minutes(13))
+            .is_empty());
+        let alerts = engine.forget_device(device_id, minutes(14));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].status, AlertStatus::Resolved);
+        assert!(engine.firing().is_empty());
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/alerts"))
+            .and(body_json(serde_json::json!({
+                "text": format!("offline resolved for device {}", device_id)
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let notifier = Notifier::new()
+            .webhook(
+                Webhook::slack(format!("{}/alerts", mock_server.uri()))
+                    .only_alerts(|alert| alert.status == AlertStatus::Resolved),
+            )
+            .webhook(
+                Webhook::generic(format!("{}/ignored", mock_server.uri()))
+                    .only_alerts(|alert| alert.rule == "high-cpu"),
+            );
+        notifier.notify_alert(&alerts[0]).await.unwrap();
+
+        let payload = crate::notify::alert_payload(&alerts[0]);
+        assert_eq!(payload["event"], "alertResolved");
+        assert_eq!(payload["rule"], "offline");
+        assert_eq!(payload["since"], minutes(12).to_rfc3339());
+    }
+
+    #[cfg(feature = "history")]
+    #[test]
+    fn test_history_store() {
+        use crate::history::HistoryStore;
+        use chrono::{TimeZone, Utc};
+        use std::collections::HashMap;
+
+        let store = HistoryStore::open_in_memory().unwrap();
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let minutes = |n: i64| start + chrono::Duration::minutes(n);
+        let stats = |at, cpu: f64| DeviceStatistics {
+            uptime_sec: 100,
+            last_heartbeat_at: at,
+            cpu_utilization_pct: Some(cpu),
+            uplink: Some(DeviceUplinkStatistics {
+                tx_rate_bps: 1000,
+                rx_rate_bps: 2000,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        for (n, cpu) in [(0, 10.0), (10, 20.0), (20, 30.0), (70, 50.0)] {
+            assert!(store
+                .record(site_id, device_id, &stats(minutes(n), cpu))
+                .unwrap());
+        }
+        // The same heartbeat is only recorded once.
+        assert!(!store
+            .record(site_id, device_id, &stats(minutes(0), 99.0))
+            .unwrap());
+        let other = DeviceId::new(Uuid::new_v4());
+        let site = HashMap::from([
+            (device_id, stats(minutes(70), 50.0)),
+            (other, stats(minutes(70), 5.0)),
+        ]);
+        assert_eq!(store.record_site(site_id, &site).unwrap(), 1);
+
+        let samples = store.samples(device_id, minutes(10)..minutes(70)).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].sampled_at, minutes(10));
+        assert_eq!(samples[0].site_id, site_id);
+        assert_eq!(samples[1].cpu_utilization_pct, Some(30.0));
+        assert_eq!(samples[1].uplink_rx_rate_bps, Some(2000));
+        assert_eq!(samples[1].tx_retries_pct, None);
+
+        let hourly = store
+            .downsample(
+                device_id,
+                minutes(0)..minutes(120),
+                Duration::from_secs(3600),
+            )
+            .unwrap();
+        assert_eq!(hourly.len(), 2);
+        assert_eq!(hourly[0].start, minutes(0));
+        assert_eq!(hourly[0].samples, 3);
+        assert_eq!(hourly[0].cpu_utilization_pct, Some(20.0));
+        assert_eq!(hourly[1].start, minutes(60));
+        assert_eq!(hourly[1].uplink_tx_rate_bps, Some(1000.0));
+        assert!(matches!(
+            store.downsample(device_id, minutes(0)..minutes(1), Duration::ZERO),
+            Err(UnifiError::InvalidInput(_))
+        ));
+
+        let latest = store.latest(device_id).unwrap().unwrap();
+        assert_eq!(latest.sampled_at, minutes(70));
+        assert_eq!(store.latest(DeviceId::new(Uuid::new_v4())).unwrap(), None);
+
+        assert_eq!(store.prune(minutes(15)).unwrap(), 2);
+        assert_eq!(
+            store
+                .samples(device_id, minutes(0)..minutes(120))
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poll_device_statistics() {
+        use futures::StreamExt;
+
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        let stats_path = format!(
+            "/v1/sites/{}/devices/{}/statistics/latest",
+            site_id, device_id
+        );
+
+        Mock::given(method("GET"))
+            .and(path(stats_path.clone()))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "statusCode": 500,
+                "message": "Internal error"
+            })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(stats_path))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(SAMPLE_STATISTICS_JSON, "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let interval = Duration::from_millis(20);
+        let polls = client.poll_device_statistics(site_id, device_id, interval);
+        futures::pin_mut!(polls);
+
+        let started = std::time::Instant::now();
+        let error = polls.next().await.unwrap().unwrap_err();
+        assert!(matches!(
+            error.inner(),
+            UnifiError::ServerError {
+                status_code: 500,
+                ..
+            }
+        ));
+        // The first poll is immediate; after a failure a tick is skipped, so the next poll
+        // waits for the second tick, however long the failed poll took.
+        let stats = polls.next().await.unwrap().unwrap();
+        assert_eq!(stats.uptime_sec, 737201);
+        assert!(started.elapsed() >= Duration::from_millis(38));
+        assert!(polls.next().await.unwrap().is_ok());
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_site_statistics_rollup() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let ap = Uuid::new_v4();
+        let switch = Uuid::new_v4();
+        let offline = Uuid::new_v4();
+
+        let mut offline_json = device_overview_json(offline, "Garage AP");
+        offline_json["state"] = serde_json::json!("OFFLINE");
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                device_overview_json(ap, "Office AP"),
+                device_overview_json(switch, "Switch"),
+                offline_json,
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                wireless_client_json(ap, Some("5")),
+                wireless_client_json(ap, None),
+                serde_json::json!({
+                    "type": "WIRED",
+                    "id": Uuid::new_v4(),
+                    "name": "Desktop PC",
+                    "connectedAt": "2025-01-18T12:00:00Z",
+                    "macAddress": "00:11:22:33:44:66",
+                    "uplinkDeviceId": switch
+                }),
+            ])))
+            .mount(&server)
+            .await;
+
+        let mut switch_stats: serde_json::Value =
+            serde_json::from_str(SAMPLE_STATISTICS_JSON).unwrap();
+        switch_stats["cpuUtilizationPct"] = serde_json::json!(10.0);
+        switch_stats["memoryUtilizationPct"] = serde_json::Value::Null;
+        switch_stats["uplink"] = serde_json::json!({ "txRateBps": 280, "rxRateBps": 712 });
+        for (device, body) in [
+            (ap, serde_json::from_str(SAMPLE_STATISTICS_JSON).unwrap()),
+            (switch, switch_stats),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!(
+                    "/v1/sites/{}/devices/{}/statistics/latest",
+                    site_id, device
+                )))
+                .respond_with(ResponseTemplate::new(200).set_body_json::<serde_json::Value>(body))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/statistics/latest",
+                site_id, offline
+            )))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let stats = mock_client(&server).site_statistics(site_id).await.unwrap();
+
+        assert_eq!(stats.total_devices, 3);
+        assert_eq!(stats.online_devices, 2);
+        assert_eq!(stats.uplink_tx_rate_bps, 309720 + 280);
+        assert_eq!(stats.uplink_rx_rate_bps, 32288 + 712);
+        assert_eq!(stats.average_cpu_utilization_pct, Some((30.8 + 10.0) / 2.0));
+        assert_eq!(stats.average_memory_utilization_pct, Some(74.2));
+        assert_eq!(stats.total_clients, 3);
+        assert_eq!(stats.wireless_clients, 2);
+        assert_eq!(stats.wired_clients, 1);
+        assert_eq!(stats.vpn_clients, 0);
+    }
+
+    #[tokio::test]
+    async fn test_firmware_upgrade_wait_ignores_state_before_upgrade() {
+        let server = MockServer::start().await;
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v1/sites/{}/devices/{}/actions",
+                site_id, device_id
+            )))
+            .respond_with(ResponseTemplate::new(202))
+            .mount(&server)
+            .await;
+        for state in ["ONLINE", "UPDATING"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(device_details_json(device_id, state)),
+                )
+                .up_to_n_times(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let upgrade = client
+            .upgrade_device_firmware(site_id, device_id)
+            .await
+            .unwrap();
+        assert!(upgrade
+            .wait(Duration::from_secs(5), Duration::from_millis(10))
+            .await
+            .unwrap());
+        // The first ONLINE poll precedes the upgrade and must not end the wait.
+        let polls = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.method.as_str() == "GET")
+            .count();
+        assert_eq!(polls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_fleet_snapshot_ndjson_round_trip() {
+        use crate::export::ndjson::{
+            read_fleet_snapshot_ndjson, write_fleet_snapshot_ndjson, NdjsonError,
+        };
+        use crate::fleet::FleetClient;
+
+        let (office, branch) = (MockServer::start().await, MockServer::start().await);
+        let site_id = SiteId::new(Uuid::new_v4());
+        let device_id = DeviceId::new(Uuid::new_v4());
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": site_id, "name": "Default" }),
+            ])))
+            .mount(&office)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![device_overview_json(device_id, "Switch")])),
+            )
+            .mount(&office)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/devices/{}", site_id, device_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(device_details_json(device_id, "ONLINE")),
+            )
+            .mount(&office)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/sites/{}/clients", site_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(page_json(vec![wireless_client_json(device_id, None)])),
+            )
+            .mount(&office)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![])))
+            .mount(&branch)
+            .await;
+
+        let fleet: FleetClient = [
+            ("office", mock_client(&office)),
+            ("branch", mock_client(&branch)),
+        ]
+        .into_iter()
+        .collect();
+        let (snapshot, failed) = fleet.snapshot().await;
+        assert!(failed.is_empty());
+        assert_eq!(snapshot.controllers["office"].devices().count(), 1);
+
+        let mut ndjson = Vec::new();
+        write_fleet_snapshot_ndjson(&snapshot, &mut ndjson).unwrap();
+        let text = String::from_utf8(ndjson.clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        // header, two controllers, one site, one device, one client, end
+        assert_eq!(lines.len(), 7);
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["kind"], "header");
+        assert_eq!(header["schemaVersion"], 1);
+        let end: serde_json::Value = serde_json::from_str(lines[6]).unwrap();
+        assert_eq!(end, serde_json::json!({ "kind": "end", "records": 5 }));
+
+        let restored = read_fleet_snapshot_ndjson(ndjson.as_slice()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&snapshot).unwrap()
+        );
+
+        let line_of = |input: &[u8]| match read_fleet_snapshot_ndjson(input) {
+            Err(NdjsonError::InvalidLine { line, .. }) => line,
+            other => panic!("expected an invalid line, got {:?}", other.map(|_| ())),
+        };
+        // A record cut off mid-line.
+        let cut = text.find(&lines[4][10..]).unwrap();
+        assert_eq!(line_of(&ndjson[..cut]), 5);
+        // A corrupt record.
+        let corrupt = text.replacen("\"kind\":\"device\"", "\"kind\":\"devic", 1);
+        assert_eq!(line_of(corrupt.as_bytes()), 5);
+        // Truncated on a line boundary, without the end record.
+        let truncated = lines[..6].join("\n") + "\n";
+        assert_eq!(line_of(truncated.as_bytes()), 7);
+        // Another schema version.
+        let newer = text.replacen("\"schemaVersion\":1", "\"schemaVersion\":2", 1);
+        assert_eq!(line_of(newer.as_bytes()), 1);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_cassette_replays_through_prefixed_base_url() {
+        use crate::cassette::Cassette;
+        use std::sync::Arc;
+
+        let site_id = SiteId::new(Uuid::new_v4());
+        let file = std::env::temp_dir().join(format!("unifi-cassette-{}.json", Uuid::new_v4()));
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/proxy/network/integrations/v1/sites"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page_json(vec![
+                serde_json::json!({ "id": site_id, "name": "Default" }),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let recording = Arc::new(Cassette::record(&file));
+        let client =
+            UnifiClientBuilder::new(format!("{}/proxy/network/integrations", server.uri()))
+                .api_key("test-key")
+                .cassette(recording.clone())
+                .build()
+                .unwrap();
+        client.list_sites(&ListParams::default()).await.unwrap();
+        recording.save().unwrap();
+        assert_eq!(recording.interactions()[0].path, "/v1/sites");
+
+        let client = UnifiClientBuilder::new("https://cassette.invalid")
+            .api_key("test-key")
+            .cassette(Arc::new(Cassette::replay(&file).unwrap()))
+            .build()
+            .unwrap();
+        let sites = client.list_sites(&ListParams::default()).await.unwrap();
+        assert_eq!(sites.data[0].id, site_id);
+
+        drop(recording);
+        std::fs::remove_file(&file).unwrap();
+    }
 }
@@ -1,5 +1,8 @@
+use crate::models::common::{deserialize_optional_ip, mask_ip, redact_mac, FrequencyBand};
+use crate::models::id::{ClientId, DeviceId};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,46 +16,360 @@ pub enum ClientOverview {
     Vpn(VpnClientOverview),
     #[serde(rename = "TELEPORT")]
     Teleport(TeleportClientOverview),
+    /// A client type introduced by a newer controller than this crate knows about.
+    #[serde(untagged)]
+    Unknown(UnknownClientOverview),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ClientOverview {
+    /// Returns the fields shared by every client type.
+    pub fn base(&self) -> &BaseClientOverview {
+        match self {
+            ClientOverview::Wired(client) => &client.base,
+            ClientOverview::Wireless(client) => &client.base,
+            ClientOverview::Vpn(client) => &client.base,
+            ClientOverview::Teleport(client) => &client.base,
+            ClientOverview::Unknown(client) => &client.base,
+        }
+    }
+
+    /// Returns the client's MAC address, if its type has one.
+    pub fn mac_address(&self) -> Option<&str> {
+        match self {
+            ClientOverview::Wired(client) => Some(&client.mac_address),
+            ClientOverview::Wireless(client) => Some(&client.mac_address),
+            ClientOverview::Vpn(_) | ClientOverview::Teleport(_) | ClientOverview::Unknown(_) => {
+                None
+            }
+        }
+    }
+
+    /// Returns a copy with MAC and IP addresses masked, for logging.
+    pub fn redacted(&self) -> Self {
+        match self {
+            ClientOverview::Wired(client) => ClientOverview::Wired(WiredClientOverview {
+                base: client.base.redacted(),
+                mac_address: redact_mac(&client.mac_address),
+                ..client.clone()
+            }),
+            ClientOverview::Wireless(client) => ClientOverview::Wireless(WirelessClientOverview {
+                base: client.base.redacted(),
+                mac_address: redact_mac(&client.mac_address),
+                ..client.clone()
+            }),
+            ClientOverview::Vpn(client) => ClientOverview::Vpn(VpnClientOverview {
+                base: client.base.redacted(),
+            }),
+            ClientOverview::Teleport(client) => ClientOverview::Teleport(TeleportClientOverview {
+                base: client.base.redacted(),
+            }),
+            ClientOverview::Unknown(client) => ClientOverview::Unknown(UnknownClientOverview {
+                base: client.base.redacted(),
+                ..client.clone()
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct BaseClientOverview {
-    pub id: Uuid,
+    pub id: ClientId,
     pub name: Option<String>,
     pub connected_at: DateTime<Utc>,
-    #[serde(default)]
-    pub ip_address: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_ip")]
+    pub ip_address: Option<IpAddr>,
+    /// Fields returned by the controller that this crate does not model yet.
+    #[cfg(feature = "raw-fields")]
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BaseClientOverview {
+    /// Returns a copy with the IP address masked, for logging.
+    pub fn redacted(&self) -> Self {
+        Self {
+            ip_address: self.ip_address.map(mask_ip),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct WiredClientOverview {
     #[serde(flatten)]
     pub base: BaseClientOverview,
     pub mac_address: String,
-    pub uplink_device_id: Uuid,
+    pub uplink_device_id: DeviceId,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct WirelessClientOverview {
     #[serde(flatten)]
     pub base: BaseClientOverview,
     pub mac_address: String,
-    pub uplink_device_id: Uuid,
+    pub uplink_device_id: DeviceId,
+    /// The radio band the client is associated on, when reported by the controller.
+    #[serde(default)]
+    pub band: Option<FrequencyBand>,
+    /// The WLAN (SSID) the client is associated with, when reported by the controller.
+    #[serde(default)]
+    pub wlan_id: Option<Uuid>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct VpnClientOverview {
     #[serde(flatten)]
     pub base: BaseClientOverview,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TeleportClientOverview {
     #[serde(flatten)]
     pub base: BaseClientOverview,
 }
+
+/// A client whose `type` this crate does not recognise; only the shared fields are kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct UnknownClientOverview {
+    /// The raw `type` reported by the controller.
+    #[serde(rename = "type")]
+    pub client_type: String,
+    #[serde(flatten)]
+    pub base: BaseClientOverview,
+}
+
+/// The full details of a single client, as returned by the client details endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum ClientDetails {
+    #[serde(rename = "WIRED")]
+    Wired(WiredClientDetails),
+    #[serde(rename = "WIRELESS")]
+    Wireless(WirelessClientDetails),
+    #[serde(rename = "VPN")]
+    Vpn(VpnClientDetails),
+    #[serde(rename = "TELEPORT")]
+    Teleport(TeleportClientDetails),
+    /// A client type introduced by a newer controller than this crate knows about.
+    #[serde(untagged)]
+    Unknown(UnknownClientDetails),
+}
+
+impl ClientDetails {
+    /// Returns the fields shared by every client type.
+    pub fn base(&self) -> &BaseClientDetails {
+        match self {
+            ClientDetails::Wired(client) => &client.base,
+            ClientDetails::Wireless(client) => &client.base,
+            ClientDetails::Vpn(client) => &client.base,
+            ClientDetails::Teleport(client) => &client.base,
+            ClientDetails::Unknown(client) => &client.base,
+        }
+    }
+
+    /// Returns the device the client is connected through, if its type has one.
+    pub fn uplink_device_id(&self) -> Option<DeviceId> {
+        match self {
+            ClientDetails::Wired(client) => Some(client.uplink_device_id),
+            ClientDetails::Wireless(client) => Some(client.uplink_device_id),
+            ClientDetails::Vpn(_) | ClientDetails::Teleport(_) | ClientDetails::Unknown(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BaseClientDetails {
+    pub id: ClientId,
+    pub name: Option<String>,
+    pub connected_at: DateTime<Utc>,
+    #[serde(default, deserialize_with = "deserialize_optional_ip")]
+    pub ip_address: Option<IpAddr>,
+    #[serde(default)]
+    pub access: Option<ClientAccess>,
+    /// Fields returned by the controller that this crate does not model yet.
+    #[cfg(feature = "raw-fields")]
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// How a client is granted network access.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ClientAccess {
+    #[serde(rename = "type")]
+    pub access_type: ClientAccessType,
+    /// Whether a guest client has been authorized, when applicable.
+    #[serde(default)]
+    pub authorized: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClientAccessType {
+    #[default]
+    Default,
+    Guest,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct WiredClientDetails {
+    #[serde(flatten)]
+    pub base: BaseClientDetails,
+    pub mac_address: String,
+    pub uplink_device_id: DeviceId,
+    /// The negotiated link speed, when reported by the controller.
+    #[serde(default)]
+    pub link_speed_mbps: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct WirelessClientDetails {
+    #[serde(flatten)]
+    pub base: BaseClientDetails,
+    pub mac_address: String,
+    pub uplink_device_id: DeviceId,
+    /// The SSID the client is associated with, when reported by the controller.
+    #[serde(default)]
+    pub ssid: Option<String>,
+    #[serde(default)]
+    pub band: Option<FrequencyBand>,
+    #[serde(default)]
+    pub wlan_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct VpnClientDetails {
+    #[serde(flatten)]
+    pub base: BaseClientDetails,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TeleportClientDetails {
+    #[serde(flatten)]
+    pub base: BaseClientDetails,
+}
+
+/// Details of a client whose `type` this crate does not recognise.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct UnknownClientDetails {
+    /// The raw `type` reported by the controller.
+    #[serde(rename = "type")]
+    pub client_type: String,
+    #[serde(flatten)]
+    pub base: BaseClientDetails,
+}
+
+/// An action that can be executed on a client through the client actions endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClientAction {
+    Block,
+    Unblock,
+    /// Forces a wireless client to disconnect and re-associate.
+    Reconnect,
+    AuthorizeGuestAccess,
+}
+
+/// Limits applied when authorizing a guest client, built with chained setters.
+///
+/// Unset limits fall back to the guest hotspot defaults configured on the controller.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorizeGuestRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_limit_minutes: Option<u32>,
+    #[serde(
+        rename = "dataUsageLimitMBytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_usage_limit_mbytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rx_rate_limit_kbps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_rate_limit_kbps: Option<u32>,
+}
+
+impl AuthorizeGuestRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long the authorization lasts, in minutes.
+    pub fn time_limit_minutes(mut self, minutes: u32) -> Self {
+        self.time_limit_minutes = Some(minutes);
+        self
+    }
+
+    /// Sets the total data the guest may transfer, in megabytes.
+    pub fn data_usage_limit_mbytes(mut self, mbytes: u64) -> Self {
+        self.data_usage_limit_mbytes = Some(mbytes);
+        self
+    }
+
+    /// Sets the download rate limit, in kilobits per second.
+    pub fn rx_rate_limit_kbps(mut self, kbps: u32) -> Self {
+        self.rx_rate_limit_kbps = Some(kbps);
+        self
+    }
+
+    /// Sets the upload rate limit, in kilobits per second.
+    pub fn tx_rate_limit_kbps(mut self, kbps: u32) -> Self {
+        self.tx_rate_limit_kbps = Some(kbps);
+        self
+    }
+}
+
+/// The outcome of a guest authorization.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuthorizeGuestResponse {
+    /// The authorization that was replaced, if the guest was already authorized.
+    #[serde(default)]
+    pub revoked_authorization: Option<GuestAuthorization>,
+    #[serde(default)]
+    pub granted_authorization: Option<GuestAuthorization>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct GuestAuthorization {
+    #[serde(default)]
+    pub authorized_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, rename = "dataUsageLimitMBytes")]
+    pub data_usage_limit_mbytes: Option<u64>,
+    #[serde(default)]
+    pub rx_rate_limit_kbps: Option<u32>,
+    #[serde(default)]
+    pub tx_rate_limit_kbps: Option<u32>,
+}
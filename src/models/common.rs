@@ -1,7 +1,64 @@
-use serde::de::Error;
+use crate::errors::UnifiError;
+use crate::filter::Filter;
 use serde::{de, Deserialize, Deserializer, Serialize};
+use std::net::IpAddr;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Query parameters accepted by list endpoints.
+///
+/// Unset fields are left to the controller's defaults, except `offset` and `limit`
+/// which default to 0 and 25.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListParams {
+    /// The starting point of the list.
+    pub offset: Option<i32>,
+    /// The maximum number of items to return.
+    pub limit: Option<i32>,
+    /// A server-side filter restricting which items are returned.
+    pub filter: Option<Filter>,
+    /// The property to sort by, e.g. `"name"` or `"-name"` for descending order.
+    pub sort: Option<String>,
+}
+
+impl ListParams {
+    /// Creates parameters for a single page at `offset` holding at most `limit` items.
+    pub fn page(offset: i32, limit: i32) -> Self {
+        Self {
+            offset: Some(offset),
+            limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the server-side filter.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Sets the sort property.
+    pub fn sort(mut self, sort: impl Into<String>) -> Self {
+        self.sort = Some(sort.into());
+        self
+    }
+
+    /// Returns the parameters as query string pairs.
+    pub(crate) fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![
+            ("offset", self.offset.unwrap_or(0).to_string()),
+            ("limit", self.limit.unwrap_or(25).to_string()),
+        ];
+        if let Some(filter) = &self.filter {
+            pairs.push(("filter", filter.to_string()));
+        }
+        if let Some(sort) = &self.sort {
+            pairs.push(("sort", sort.clone()));
+        }
+        pairs
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Page<T> {
     pub offset: i32,
     pub limit: i32,
@@ -9,32 +66,239 @@ pub struct Page<T> {
     #[serde(rename = "totalCount")]
     pub total_count: i32,
     pub data: Vec<T>,
+    /// The number of records dropped because they failed to deserialize.
+    ///
+    /// Always zero unless `UnifiClientBuilder::skip_bad_records` is enabled.
+    #[serde(skip)]
+    pub skipped: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl<T> Page<T> {
+    /// Creates a page holding all of `data`, as a single-page response would.
+    pub fn new(data: Vec<T>) -> Self {
+        let len = data.len() as i32;
+        Self {
+            offset: 0,
+            limit: len,
+            count: len,
+            total_count: len,
+            data,
+            skipped: 0,
+        }
+    }
+
+    /// Ensures the page contains at least one item.
+    ///
+    /// # Arguments
+    ///
+    /// * `what` - A description of the expected items, used in the error message (e.g. `"sites"`).
+    ///
+    /// # Returns
+    ///
+    /// The page unchanged if `data` is non-empty, or a `UnifiError::NotFound` otherwise.
+    pub fn require_non_empty(self, what: &str) -> Result<Page<T>, UnifiError> {
+        if self.data.is_empty() {
+            Err(UnifiError::NotFound {
+                resource: what.to_string(),
+                message: format!("no {} returned", what),
+            })
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl<T> Default for Page<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ApplicationInfo {
     pub application_version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ApplicationInfo {
+    /// Parses `application_version` into a comparable `ApplicationVersion`.
+    pub fn version(&self) -> Result<ApplicationVersion, UnifiError> {
+        self.application_version.parse()
+    }
+}
+
+/// A `major.minor.patch` Network application version, as reported by `v1/info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ApplicationVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ApplicationVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl std::fmt::Display for ApplicationVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl std::str::FromStr for ApplicationVersion {
+    type Err = UnifiError;
+
+    /// Parses versions such as `9.0.108`; missing components default to zero and any
+    /// pre-release suffix (e.g. `-beta`) is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || UnifiError::Config(format!("invalid application version: {}", s));
+        let core = s.split(['-', '+']).next().unwrap_or_default();
+        let mut parts = core.split('.').map(|part| part.parse::<u32>());
+
+        let major = parts.next().ok_or_else(invalid)?.map_err(|_| invalid())?;
+        let minor = parts.next().transpose().map_err(|_| invalid())?;
+        let patch = parts.next().transpose().map_err(|_| invalid())?;
+
+        Ok(Self::new(major, minor.unwrap_or(0), patch.unwrap_or(0)))
+    }
+}
+
+/// The controller's acknowledgement of an action request.
+///
+/// Actions answered with `202 Accepted` have been queued and are still pending; any other
+/// success status means the action has completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ActionAck {
+    /// The HTTP status code of the response.
+    pub status_code: u16,
+    /// Whether the controller reported the action as done.
+    pub completed: bool,
+    /// Whether the controller accepted the action for asynchronous processing.
+    pub accepted: bool,
+}
+
+impl ActionAck {
+    /// Builds an acknowledgement from a successful response status code.
+    pub fn from_status(status_code: u16) -> Self {
+        let accepted = status_code == 202;
+        Self {
+            status_code,
+            completed: !accepted,
+            accepted,
+        }
+    }
+}
+
+/// A value paired with the moment it was fetched, so consumers can judge its freshness.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Timestamped<T> {
+    pub value: T,
+    pub fetched_at: crate::time::Instant,
+}
+
+impl<T> Timestamped<T> {
+    /// Wraps `value`, stamping it with the current instant.
+    pub fn now(value: T) -> Self {
+        Self {
+            value,
+            fetched_at: crate::time::Instant::now(),
+        }
+    }
+
+    /// Returns how long ago the value was fetched.
+    pub fn age(&self) -> std::time::Duration {
+        self.fetched_at.elapsed()
+    }
+}
+
+/// Masks all but the last two octets of a MAC address, e.g. `**:**:**:**:44:55`.
+///
+/// Values that are not colon- or dash-separated MAC addresses are fully masked.
+pub fn redact_mac(mac: &str) -> String {
+    let octets: Vec<&str> = mac.split([':', '-']).collect();
+    if octets.len() != 6 {
+        return "*".repeat(mac.len().max(1));
+    }
+    let masked = octets.len() - 2;
+    octets
+        .iter()
+        .enumerate()
+        .map(|(i, octet)| if i < masked { "**" } else { octet })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Zeroes the host portion of an IP address.
+///
+/// IPv4 addresses keep their `/24` network and IPv6 addresses keep their `/64` network,
+/// so the result is still a valid address, e.g. `192.168.1.77` becomes `192.168.1.0`.
+/// Used by the models' `redacted` helpers.
+pub fn mask_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            IpAddr::from([a, b, c, 0])
+        }
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            IpAddr::from([s[0], s[1], s[2], s[3], 0, 0, 0, 0])
+        }
+    }
+}
+
+/// Deserializes an optional IP address, treating `null` and `""` as `None`.
+///
+/// Controllers report devices and clients without an address as an empty string; any
+/// other value that is not a valid IP address is an error.
+pub(crate) fn deserialize_optional_ip<'de, D>(deserializer: D) -> Result<Option<IpAddr>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(value) if value.trim().is_empty() => Ok(None),
+        Some(value) => {
+            value.trim().parse().map(Some).map_err(|_| {
+                de::Error::invalid_value(de::Unexpected::Str(&value), &"an IP address")
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum PortState {
     Up,
     Down,
+    /// Also used for states introduced by a newer controller.
+    #[default]
+    #[serde(other)]
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConnectorType {
     RJ45,
     SFP,
     SFPPLUS,
     SFP28,
     QSFP28,
+    /// A connector introduced by a newer controller than this crate knows about.
+    #[default]
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WlanStandard {
     #[serde(rename = "802.11a")]
     IEEE802_11A,
@@ -50,9 +314,13 @@ pub enum WlanStandard {
     IEEE802_11AX,
     #[serde(rename = "802.11be")]
     IEEE802_11BE,
+    /// A standard introduced by a newer controller than this crate knows about.
+    #[default]
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
 pub enum FrequencyBand {
     #[serde(rename = "2.4")]
     Band2_4GHz,
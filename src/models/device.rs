@@ -1,9 +1,13 @@
-use crate::models::common::{ConnectorType, FrequencyBand, PortState, WlanStandard};
+use crate::models::common::{
+    deserialize_optional_ip, mask_ip, redact_mac, ConnectorType, FrequencyBand, PortState,
+    WlanStandard,
+};
+use crate::models::id::DeviceId;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::net::IpAddr;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum DeviceState {
     Online,
@@ -15,23 +19,89 @@ pub enum DeviceState {
     Deleting,
     ConnectionInterrupted,
     Isolated,
+    /// A state introduced by a newer controller than this crate knows about.
+    #[default]
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An action that can be executed on a device through the device actions endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceAction {
+    Restart,
+    /// Upgrades the device firmware, to the latest release unless a version is given.
+    Upgrade,
+    /// Adopts a device that is pending adoption into the site.
+    Adopt,
+    /// Starts flashing the device's locate LED.
+    StartLocating,
+    /// Stops flashing the device's locate LED.
+    StopLocating,
+    /// An action not modelled by this crate, sent verbatim (e.g. `"PROVISION"`).
+    Custom(String),
+}
+
+impl DeviceAction {
+    /// Returns the action name sent to the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            DeviceAction::Restart => "RESTART",
+            DeviceAction::Upgrade => "UPGRADE",
+            DeviceAction::Adopt => "ADOPT",
+            DeviceAction::StartLocating => "START_LOCATING",
+            DeviceAction::StopLocating => "STOP_LOCATING",
+            DeviceAction::Custom(action) => action,
+        }
+    }
+}
+
+impl Serialize for DeviceAction {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// An action that can be executed on a single device port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PortAction {
+    /// Cuts and restores PoE power on the port.
+    PowerCycle,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceOverview {
-    pub id: Uuid,
+    pub id: DeviceId,
     pub name: String,
     pub model: String,
     pub mac_address: String,
-    pub ip_address: String,
+    #[serde(default, deserialize_with = "deserialize_optional_ip")]
+    pub ip_address: Option<IpAddr>,
     pub state: DeviceState,
     pub features: Vec<String>,
     pub interfaces: Vec<String>,
+    /// Fields returned by the controller that this crate does not model yet.
+    #[cfg(feature = "raw-fields")]
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DeviceOverview {
+    /// Returns a copy with the MAC and IP addresses masked, for logging.
+    pub fn redacted(&self) -> Self {
+        Self {
+            mac_address: redact_mac(&self.mac_address),
+            ip_address: self.ip_address.map(mask_ip),
+            ..self.clone()
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DevicePhysicalInterfaces {
     #[serde(default)]
     pub ports: Vec<EthernetPortOverview>,
@@ -39,8 +109,9 @@ pub struct DevicePhysicalInterfaces {
     pub radios: Vec<WirelessRadioOverview>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct EthernetPortOverview {
     pub idx: i32,
     pub state: PortState,
@@ -49,8 +120,9 @@ pub struct EthernetPortOverview {
     pub speed_mbps: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct WirelessRadioOverview {
     pub wlan_standard: Option<WlanStandard>,
     #[serde(default, rename = "frequencyGHz")]
@@ -60,15 +132,17 @@ pub struct WirelessRadioOverview {
     pub channel: Option<i32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceDetails {
-    pub id: Uuid,
+    pub id: DeviceId,
     pub name: String,
     pub model: String,
     pub supported: bool,
     pub mac_address: String,
-    pub ip_address: String,
+    #[serde(default, deserialize_with = "deserialize_optional_ip")]
+    pub ip_address: Option<IpAddr>,
     pub state: DeviceState,
     pub firmware_version: String,
     pub firmware_updatable: bool,
@@ -79,25 +153,116 @@ pub struct DeviceDetails {
     pub uplink: Option<DeviceUplinkInterface>,
     #[serde(default)]
     pub features: Option<DeviceFeatures>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_physical_interfaces")]
     pub interfaces: Option<DevicePhysicalInterfaces>,
+    /// Fields returned by the controller that this crate does not model yet.
+    #[cfg(feature = "raw-fields")]
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Deserializes `DeviceDetails::interfaces`, treating `null` and `[]` as empty interfaces.
+///
+/// Some controllers send an empty array instead of an object for devices without
+/// interfaces.
+fn deserialize_physical_interfaces<'de, D>(
+    deserializer: D,
+) -> Result<Option<DevicePhysicalInterfaces>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct PhysicalInterfacesVisitor;
+
+    impl<'de> de::Visitor<'de> for PhysicalInterfacesVisitor {
+        type Value = Option<DevicePhysicalInterfaces>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a device interfaces object, an empty array or null")
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::MapAccess<'de>,
+        {
+            DevicePhysicalInterfaces::deserialize(de::value::MapAccessDeserializer::new(map))
+                .map(Some)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::SeqAccess<'de>,
+        {
+            if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                return Err(de::Error::invalid_length(1, &self));
+            }
+            Ok(Some(DevicePhysicalInterfaces::default()))
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(Some(DevicePhysicalInterfaces::default()))
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            self.visit_unit()
+        }
+    }
+
+    deserializer.deserialize_any(PhysicalInterfacesVisitor)
+}
+
+/// Model prefixes of UniFi gateways and consoles that route traffic.
+const GATEWAY_MODEL_PREFIXES: &[&str] = &["UDM", "UDR", "UDW", "UCG", "USG", "UX", "EFG"];
+
+impl DeviceDetails {
+    /// Returns whether this device is the site's gateway/router.
+    ///
+    /// The integrations API does not expose routing features, so this is a heuristic:
+    /// a gateway has no uplink device (its uplink is the WAN) and its model belongs to a
+    /// known gateway family such as `UDM`, `UCG` or `UXG`. A disconnected access point or
+    /// switch also lacks an uplink, which is why the model check is required.
+    pub fn is_gateway(&self) -> bool {
+        let model = self.model.to_ascii_uppercase();
+        self.uplink.is_none()
+            && GATEWAY_MODEL_PREFIXES
+                .iter()
+                .any(|prefix| model.starts_with(prefix))
+    }
+
+    /// Returns a copy with the MAC and IP addresses masked, for logging.
+    pub fn redacted(&self) -> Self {
+        Self {
+            mac_address: redact_mac(&self.mac_address),
+            ip_address: self.ip_address.map(mask_ip),
+            ..self.clone()
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceUplinkInterface {
-    pub device_id: Uuid,
+    pub device_id: DeviceId,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceFeatures {
     pub switching: Option<SwitchFeatureOverview>,
     pub access_point: Option<AccessPointFeatureOverview>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct SwitchFeatureOverview {}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct AccessPointFeatureOverview {}
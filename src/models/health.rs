@@ -0,0 +1,107 @@
+use crate::models::common::ApplicationVersion;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Error,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ControllerHealth {
+    pub status: HealthStatus,
+    #[serde(default)]
+    pub subsystems: Vec<SubsystemHealth>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl ControllerHealth {
+    /// Returns whether the controller and all of its subsystems report `Ok`.
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Ok
+            && self
+                .subsystems
+                .iter()
+                .all(|subsystem| subsystem.status == HealthStatus::Ok)
+    }
+}
+
+/// The outcome of a connectivity check, see `UnifiClient::health_check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectivityStatus {
+    /// The API answered and accepted the API key.
+    Healthy,
+    /// The controller's host name could not be resolved.
+    DnsFailure,
+    /// The host refused or did not accept a TCP connection.
+    Unreachable,
+    /// The TLS handshake failed, e.g. an untrusted certificate or a plain HTTP port.
+    TlsFailure,
+    /// The request timed out.
+    Timeout,
+    /// The API rejected the API key with a 401 or 403 response.
+    Unauthorized,
+    /// The server answered, but not as the UniFi Network integration API.
+    NotUnifi,
+    /// Any other failure, described in `HealthReport::error`.
+    Error,
+}
+
+/// The result of `UnifiClient::health_check`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HealthReport {
+    /// What the check found.
+    pub status: ConnectivityStatus,
+    /// The controller's Network application version, if the check succeeded.
+    pub version: Option<ApplicationVersion>,
+    /// How long the check took, including any retries.
+    pub latency: Duration,
+    /// The error the check failed with, for display.
+    pub error: Option<String>,
+}
+
+impl HealthReport {
+    /// A report of a successful check of a controller running `version`.
+    pub fn healthy(version: ApplicationVersion, latency: Duration) -> Self {
+        Self {
+            status: ConnectivityStatus::Healthy,
+            version: Some(version),
+            latency,
+            error: None,
+        }
+    }
+
+    /// A report of a check that failed with `status`, described by `error`.
+    pub fn failed(status: ConnectivityStatus, latency: Duration, error: impl Into<String>) -> Self {
+        Self {
+            status,
+            version: None,
+            latency,
+            error: Some(error.into()),
+        }
+    }
+
+    /// Returns whether the controller is reachable and accepted the API key.
+    pub fn is_healthy(&self) -> bool {
+        self.status == ConnectivityStatus::Healthy
+    }
+}
@@ -0,0 +1,69 @@
+//! Typed identifiers for sites, devices and clients.
+//!
+//! Each wraps the controller's `Uuid` so that, for example, a device id cannot be passed
+//! where a site id is expected. They serialize as plain UUID strings.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+macro_rules! uuid_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize,
+            Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub Uuid);
+
+        impl $name {
+            pub const fn new(id: Uuid) -> Self {
+                Self(id)
+            }
+
+            /// Returns the underlying `Uuid`.
+            pub const fn as_uuid(&self) -> Uuid {
+                self.0
+            }
+        }
+
+        impl From<Uuid> for $name {
+            fn from(id: Uuid) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for Uuid {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = uuid::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+    };
+}
+
+uuid_id!(
+    /// The id of a site, as returned in `SiteOverview::id`.
+    SiteId
+);
+uuid_id!(
+    /// The id of an adopted device, as returned in `DeviceOverview::id`.
+    DeviceId
+);
+uuid_id!(
+    /// The id of a connected client, as returned in `BaseClientOverview::id`.
+    ClientId
+);
@@ -1,5 +1,19 @@
+//! Request and response types for the UniFi APIs.
+//!
+//! Response structs are `#[non_exhaustive]` so that fields the controller adds can be
+//! modelled without a breaking release. To construct one outside this crate, e.g. as a
+//! mock return value, start from `Default::default()` and assign the fields you need,
+//! use `Page::new`, or use the builders in `test_util` with the `test-util` feature.
+
 pub mod client;
 pub mod common;
 pub mod device;
+pub mod health;
+pub mod id;
+pub mod search;
 pub mod site;
+pub mod site_manager;
 pub mod statistics;
+pub mod voucher;
+
+pub use id::{ClientId, DeviceId, SiteId};
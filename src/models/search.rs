@@ -0,0 +1,56 @@
+use crate::models::client::ClientOverview;
+use crate::models::device::DeviceOverview;
+use crate::models::id::SiteId;
+use serde::{Deserialize, Serialize};
+
+/// Devices and clients matching a search query, grouped by kind.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SearchResults {
+    pub devices: Vec<DeviceMatch>,
+    pub clients: Vec<ClientMatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DeviceMatch {
+    pub site_id: SiteId,
+    pub device: DeviceOverview,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ClientMatch {
+    pub site_id: SiteId,
+    pub client: ClientOverview,
+}
+
+impl DeviceMatch {
+    pub fn new(site_id: SiteId, device: DeviceOverview) -> Self {
+        Self { site_id, device }
+    }
+}
+
+impl ClientMatch {
+    pub fn new(site_id: SiteId, client: ClientOverview) -> Self {
+        Self { site_id, client }
+    }
+}
+
+impl SearchResults {
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty() && self.clients.is_empty()
+    }
+}
+
+/// Returns whether any of `fields` contains `query`, ignoring ASCII case.
+///
+/// `query` is expected to already be lowercase.
+pub(crate) fn matches_query<'a>(query: &str, fields: impl IntoIterator<Item = &'a str>) -> bool {
+    fields
+        .into_iter()
+        .any(|field| field.to_ascii_lowercase().contains(query))
+}
@@ -1,9 +1,14 @@
+use crate::models::id::SiteId;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SiteOverview {
-    pub id: Uuid,
+    pub id: SiteId,
     pub name: Option<String>,
+    /// Fields returned by the controller that this crate does not model yet.
+    #[cfg(feature = "raw-fields")]
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
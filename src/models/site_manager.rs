@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A UniFi console (host) registered with the Site Manager.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Host {
+    pub id: String,
+    #[serde(default)]
+    pub hardware_id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub host_type: Option<String>,
+    #[serde(default)]
+    pub ip_address: Option<String>,
+    #[serde(default)]
+    pub owner: Option<bool>,
+    #[serde(default)]
+    pub is_blocked: Option<bool>,
+    #[serde(default)]
+    pub registration_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_connection_state_change: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub latest_backup_time: Option<DateTime<Utc>>,
+    /// The state last reported by the console, left untyped as it varies by hardware.
+    #[serde(default)]
+    pub reported_state: Option<serde_json::Value>,
+}
+
+/// A site on one of the consoles managed through the Site Manager.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ManagedSite {
+    pub site_id: String,
+    pub host_id: String,
+    #[serde(default)]
+    pub meta: Option<serde_json::Value>,
+    #[serde(default)]
+    pub statistics: Option<serde_json::Value>,
+    #[serde(default)]
+    pub permission: Option<String>,
+    #[serde(default)]
+    pub is_owner: Option<bool>,
+}
+
+/// The devices adopted by one console.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct HostDevices {
+    pub host_id: String,
+    #[serde(default)]
+    pub host_name: Option<String>,
+    #[serde(default)]
+    pub devices: Vec<ManagedDevice>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ManagedDevice {
+    pub id: String,
+    #[serde(default)]
+    pub mac: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub firmware_status: Option<String>,
+}
+
+/// A page of Site Manager results, continued with `next_token`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SiteManagerPage<T> {
+    pub data: Vec<T>,
+    #[serde(default)]
+    pub next_token: Option<String>,
+}
+
+impl<T> SiteManagerPage<T> {
+    /// Creates a final page holding `data`.
+    pub fn new(data: Vec<T>) -> Self {
+        Self {
+            data,
+            next_token: None,
+        }
+    }
+}
+
+impl<T> Default for SiteManagerPage<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+/// The aggregation interval of ISP metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IspMetricInterval {
+    /// 5-minute samples, retained for 24 hours.
+    FiveMinutes,
+    /// Hourly samples, retained for 30 days.
+    Hourly,
+}
+
+impl IspMetricInterval {
+    /// Returns the interval as used in the endpoint path.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IspMetricInterval::FiveMinutes => "5m",
+            IspMetricInterval::Hourly => "1h",
+        }
+    }
+}
+
+/// The time range to fetch ISP metrics for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IspMetricRange {
+    /// The last 24 hours.
+    LastDay,
+    /// The last 7 days; hourly interval only.
+    LastWeek,
+    /// The last 30 days; hourly interval only.
+    LastMonth,
+    /// An explicit time window.
+    Between {
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+}
+
+impl IspMetricRange {
+    /// Returns the range as query string pairs.
+    pub(crate) fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        match self {
+            IspMetricRange::LastDay => vec![("duration", "24h".to_string())],
+            IspMetricRange::LastWeek => vec![("duration", "7d".to_string())],
+            IspMetricRange::LastMonth => vec![("duration", "30d".to_string())],
+            IspMetricRange::Between { begin, end } => vec![
+                ("beginTimestamp", begin.to_rfc3339()),
+                ("endTimestamp", end.to_rfc3339()),
+            ],
+        }
+    }
+}
+
+/// ISP metrics for one site.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct IspMetrics {
+    pub metric_type: String,
+    pub host_id: String,
+    pub site_id: String,
+    #[serde(default)]
+    pub periods: Vec<IspMetricPeriod>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct IspMetricPeriod {
+    pub metric_time: DateTime<Utc>,
+    pub data: IspMetricData,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct IspMetricData {
+    #[serde(default)]
+    pub wan: Option<WanMetrics>,
+}
+
+/// Link quality of a WAN over one metric period.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct WanMetrics {
+    /// Average latency in milliseconds.
+    #[serde(default)]
+    pub avg_latency: Option<f64>,
+    /// Maximum latency in milliseconds.
+    #[serde(default)]
+    pub max_latency: Option<f64>,
+    /// Packet loss in percent.
+    #[serde(default)]
+    pub packet_loss: Option<f64>,
+    #[serde(default, rename = "download_kbps")]
+    pub download_kbps: Option<f64>,
+    #[serde(default, rename = "upload_kbps")]
+    pub upload_kbps: Option<f64>,
+    /// Uptime in percent.
+    #[serde(default)]
+    pub uptime: Option<f64>,
+    /// Downtime in seconds.
+    #[serde(default)]
+    pub downtime: Option<f64>,
+    #[serde(default)]
+    pub isp_name: Option<String>,
+    #[serde(default)]
+    pub isp_asn: Option<String>,
+}
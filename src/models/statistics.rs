@@ -1,9 +1,11 @@
 use crate::models::common::FrequencyBand;
+use crate::models::id::DeviceId;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceStatistics {
     pub uptime_sec: i64,
     pub last_heartbeat_at: DateTime<Utc>,
@@ -20,27 +22,128 @@ pub struct DeviceStatistics {
     pub uplink: Option<DeviceUplinkStatistics>,
     #[serde(default)]
     pub interfaces: Option<DeviceInterfaceStatistics>,
+    /// Fields returned by the controller that this crate does not model yet.
+    #[cfg(feature = "raw-fields")]
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Latest statistics for one device, as returned by the site-wide statistics endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SiteDeviceStatistics {
+    pub device_id: DeviceId,
+    #[serde(flatten)]
+    pub statistics: DeviceStatistics,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceUplinkStatistics {
     pub tx_rate_bps: i64,
     pub rx_rate_bps: i64,
+    #[serde(default)]
+    pub interface_name: Option<String>,
+    #[serde(default)]
+    pub speed_mbps: Option<i32>,
+    #[serde(default)]
+    pub full_duplex: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceInterfaceStatistics {
     #[serde(default)]
     pub radios: Vec<WirelessRadioStatistics>,
+    /// Per-port counters, reported by switches and gateways.
+    #[serde(default)]
+    pub ports: Vec<EthernetPortStatistics>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct EthernetPortStatistics {
+    pub idx: i32,
+    #[serde(default)]
+    pub tx_bytes: Option<i64>,
+    #[serde(default)]
+    pub rx_bytes: Option<i64>,
+    #[serde(default)]
+    pub tx_packets: Option<i64>,
+    #[serde(default)]
+    pub rx_packets: Option<i64>,
+    #[serde(default)]
+    pub tx_errors: Option<i64>,
+    #[serde(default)]
+    pub rx_errors: Option<i64>,
+    /// The PoE power currently drawn by the connected device, in watts.
+    #[serde(default, rename = "poePowerW")]
+    pub poe_power_w: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct WirelessRadioStatistics {
     #[serde(default, rename = "frequencyGHz")]
     pub frequency_ghz: Option<FrequencyBand>,
     #[serde(rename = "txRetriesPct")]
     pub tx_retries_pct: Option<f64>,
 }
+
+/// A rollup of a site's devices and clients, as returned by `UnifiClient::site_statistics`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SiteStatistics {
+    pub total_devices: usize,
+    pub online_devices: usize,
+    /// The sum of the online devices' uplink transmit rates. Traffic crossing several
+    /// devices is counted at each of them.
+    pub uplink_tx_rate_bps: i64,
+    /// The sum of the online devices' uplink receive rates.
+    pub uplink_rx_rate_bps: i64,
+    /// The average CPU utilization of the online devices that report it.
+    pub average_cpu_utilization_pct: Option<f64>,
+    /// The average memory utilization of the online devices that report it.
+    pub average_memory_utilization_pct: Option<f64>,
+    /// Every connected client, including those of unknown types.
+    pub total_clients: usize,
+    pub wired_clients: usize,
+    pub wireless_clients: usize,
+    pub vpn_clients: usize,
+    pub teleport_clients: usize,
+}
+
+/// A numeric-only view of the device statistics body.
+///
+/// Timestamps and per-radio data are skipped during deserialization, which keeps this
+/// cheap to parse in tight polling loops such as metrics exporters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DeviceStatisticsMetrics {
+    pub uptime_sec: i64,
+    #[serde(default, rename = "loadAverage1Min")]
+    pub load_average_1min: Option<f64>,
+    #[serde(default, rename = "loadAverage5Min")]
+    pub load_average_5min: Option<f64>,
+    #[serde(default, rename = "loadAverage15Min")]
+    pub load_average_15min: Option<f64>,
+    pub cpu_utilization_pct: Option<f64>,
+    pub memory_utilization_pct: Option<f64>,
+    #[serde(default)]
+    pub uplink: Option<DeviceUplinkMetrics>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DeviceUplinkMetrics {
+    pub tx_rate_bps: i64,
+    pub rx_rate_bps: i64,
+}
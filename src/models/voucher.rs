@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A hotspot voucher granting guest access.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Voucher {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub name: String,
+    pub code: String,
+    /// How many guests may use the voucher, or `None` if any number of guests may.
+    #[serde(default)]
+    pub authorized_guest_limit: Option<u32>,
+    pub authorized_guest_count: u32,
+    #[serde(default)]
+    pub activated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub expired: bool,
+    pub time_limit_minutes: u32,
+    #[serde(default, rename = "dataUsageLimitMBytes")]
+    pub data_usage_limit_mbytes: Option<u64>,
+    #[serde(default)]
+    pub rx_rate_limit_kbps: Option<u32>,
+    #[serde(default)]
+    pub tx_rate_limit_kbps: Option<u32>,
+}
+
+/// Parameters for generating vouchers, built with chained setters.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoucherCreateRequest {
+    pub count: u32,
+    pub name: String,
+    /// How long a guest stays authorized after redeeming a voucher.
+    pub time_limit_minutes: u32,
+    /// How many guests may use each voucher. Omitted from the request when `None`, which
+    /// the controller treats as unlimited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_guest_limit: Option<u32>,
+    #[serde(
+        rename = "dataUsageLimitMBytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_usage_limit_mbytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rx_rate_limit_kbps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_rate_limit_kbps: Option<u32>,
+}
+
+impl VoucherCreateRequest {
+    /// Creates a request for a single voucher named `name` valid for `time_limit_minutes`,
+    /// usable by any number of guests and without data or rate limits.
+    pub fn new(name: impl Into<String>, time_limit_minutes: u32) -> Self {
+        Self {
+            count: 1,
+            name: name.into(),
+            time_limit_minutes,
+            authorized_guest_limit: None,
+            data_usage_limit_mbytes: None,
+            rx_rate_limit_kbps: None,
+            tx_rate_limit_kbps: None,
+        }
+    }
+
+    /// Sets how many vouchers to generate.
+    pub fn count(mut self, count: u32) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Sets how many guests may use each voucher; unset means unlimited. Use `1` for
+    /// single-use vouchers.
+    pub fn authorized_guest_limit(mut self, limit: u32) -> Self {
+        self.authorized_guest_limit = Some(limit);
+        self
+    }
+
+    /// Sets the total data each guest may transfer, in megabytes.
+    pub fn data_usage_limit_mbytes(mut self, mbytes: u64) -> Self {
+        self.data_usage_limit_mbytes = Some(mbytes);
+        self
+    }
+
+    /// Sets the download rate limit, in kilobits per second.
+    pub fn rx_rate_limit_kbps(mut self, kbps: u32) -> Self {
+        self.rx_rate_limit_kbps = Some(kbps);
+        self
+    }
+
+    /// Sets the upload rate limit, in kilobits per second.
+    pub fn tx_rate_limit_kbps(mut self, kbps: u32) -> Self {
+        self.tx_rate_limit_kbps = Some(kbps);
+        self
+    }
+}
+
+/// The response to a voucher generation request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct VoucherCreateResponse {
+    pub vouchers: Vec<Voucher>,
+}
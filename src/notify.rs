@@ -0,0 +1,413 @@
+//! Posting `Watcher` events to webhooks, e.g. Slack, Discord or a generic HTTP endpoint.
+//!
+//! A `Notifier` holds a set of `Webhook`s and posts a JSON payload to each for every
+//! event it is given, either directly with `Notifier::notify` or for everything a
+//! `Watcher` emits with `Notifier::spawn`. Each webhook may be limited to some events
+//! and may replace its default payload with a template. Alerts of the `alerts` module are
+//! posted the same way with `Notifier::notify_alert`:
+//!
+//! ```rust,no_run
+//! # fn example(watcher: &unifi_rs::watcher::Watcher) {
+//! use unifi_rs::notify::{Notifier, Webhook};
+//! use unifi_rs::watcher::WatchEvent;
+//!
+//! let notifier = Notifier::new()
+//!     .webhook(
+//!         Webhook::slack("https://hooks.slack.com/services/...")
+//!             .only(|event| matches!(event, WatchEvent::DeviceWentOffline { .. })),
+//!     )
+//!     .webhook(
+//!         Webhook::generic("https://alerts.example.com/unifi")
+//!             .template(r#"{"kind": "{{event}}", "device": "{{device_id}}"}"#),
+//!     );
+//! let _task = notifier.spawn(watcher);
+//! # }
+//! ```
+
+use crate::alerts::{Alert, AlertStatus};
+use crate::errors::UnifiError;
+use crate::watcher::{WatchEvent, Watcher};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// The shape of the default payload of a `Webhook`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// An object with the event name, its fields and a summary, see `event_payload`.
+    Generic,
+    /// A Slack incoming-webhook message: `{"text": summary}`.
+    Slack,
+    /// A Discord webhook message: `{"content": summary}`.
+    Discord,
+}
+
+type EventFilter = Arc<dyn Fn(&WatchEvent) -> bool + Send + Sync>;
+type AlertFilter = Arc<dyn Fn(&Alert) -> bool + Send + Sync>;
+
+/// A URL to post events to, with its payload format and event filter.
+#[derive(Clone)]
+pub struct Webhook {
+    url: String,
+    format: WebhookFormat,
+    template: Option<String>,
+    filter: Option<EventFilter>,
+    alert_filter: Option<AlertFilter>,
+}
+
+impl Webhook {
+    pub fn new(url: impl Into<String>, format: WebhookFormat) -> Self {
+        Self {
+            url: url.into(),
+            format,
+            template: None,
+            filter: None,
+            alert_filter: None,
+        }
+    }
+
+    /// A webhook receiving the `Generic` payload.
+    pub fn generic(url: impl Into<String>) -> Self {
+        Self::new(url, WebhookFormat::Generic)
+    }
+
+    /// A Slack incoming webhook.
+    pub fn slack(url: impl Into<String>) -> Self {
+        Self::new(url, WebhookFormat::Slack)
+    }
+
+    /// A Discord webhook.
+    pub fn discord(url: impl Into<String>) -> Self {
+        Self::new(url, WebhookFormat::Discord)
+    }
+
+    /// Replaces the default payload with `template`, a JSON document in which
+    /// `{{placeholder}}`s are replaced with the event's fields.
+    ///
+    /// The placeholders are `event`, `summary` and the keys of `event_payload`, such as
+    /// `device_id`, `client_id`, `from`, `to` and `message`, or for alerts those of
+    /// `alert_payload`, such as `rule`, `status` and `value`; values are escaped for use
+    /// inside JSON strings and placeholders the event lacks become empty. A template that
+    /// is not valid JSON after substitution fails with `UnifiError::Config`.
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    /// Posts only the events for which `filter` returns `true`. Alerts are not affected.
+    pub fn only(mut self, filter: impl Fn(&WatchEvent) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Posts only the alerts for which `filter` returns `true`.
+    pub fn only_alerts(mut self, filter: impl Fn(&Alert) -> bool + Send + Sync + 'static) -> Self {
+        self.alert_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Returns the URL events are posted to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns whether the webhook wants `event`.
+    pub fn accepts(&self, event: &WatchEvent) -> bool {
+        self.filter.as_ref().is_none_or(|filter| filter(event))
+    }
+
+    /// Returns whether the webhook wants `alert`.
+    pub fn accepts_alert(&self, alert: &Alert) -> bool {
+        self.alert_filter
+            .as_ref()
+            .is_none_or(|filter| filter(alert))
+    }
+
+    /// Renders the payload posted for `event`.
+    pub fn payload(&self, event: &WatchEvent) -> Result<Value, UnifiError> {
+        self.render(&Message::event(event))
+    }
+
+    /// Renders the payload posted for `alert`.
+    pub fn alert_payload(&self, alert: &Alert) -> Result<Value, UnifiError> {
+        self.render(&Message::alert(alert))
+    }
+
+    fn render(&self, message: &Message) -> Result<Value, UnifiError> {
+        if let Some(template) = &self.template {
+            return render_template(template, message);
+        }
+        Ok(match self.format {
+            WebhookFormat::Generic => message.generic(),
+            WebhookFormat::Slack => json!({ "text": message.summary }),
+            WebhookFormat::Discord => json!({ "content": message.summary }),
+        })
+    }
+}
+
+/// Posts events to a set of webhooks.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    http: reqwest::Client,
+    webhooks: Vec<Webhook>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Posts through `client` instead of a default `reqwest::Client`, e.g. to set a proxy
+    /// or timeouts.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Adds a webhook.
+    pub fn webhook(mut self, webhook: Webhook) -> Self {
+        self.webhooks.push(webhook);
+        self
+    }
+
+    /// Returns the webhooks, in the order they were added.
+    pub fn webhooks(&self) -> &[Webhook] {
+        &self.webhooks
+    }
+
+    /// Posts `event` to every webhook that accepts it.
+    ///
+    /// Every webhook is tried even if an earlier one fails.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, or the first `UnifiError` if any webhook could not
+    /// be reached or answered with an error status.
+    pub async fn notify(&self, event: &WatchEvent) -> Result<(), UnifiError> {
+        let mut first_error = None;
+        for webhook in self
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.accepts(event))
+        {
+            if let Err(error) = self.post(webhook, webhook.payload(event)).await {
+                first_error.get_or_insert(error);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Posts `alert` to every webhook that accepts it, like `Notifier::notify`.
+    pub async fn notify_alert(&self, alert: &Alert) -> Result<(), UnifiError> {
+        let mut first_error = None;
+        for webhook in self
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.accepts_alert(alert))
+        {
+            if let Err(error) = self.post(webhook, webhook.alert_payload(alert)).await {
+                first_error.get_or_insert(error);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Posts every event `watcher` emits from now on, until the returned task is aborted
+    /// or the watcher is dropped. Failed posts are skipped.
+    pub fn spawn(self, watcher: &Watcher) -> JoinHandle<()> {
+        let mut events = watcher.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let _ = self.notify(&event).await;
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    async fn post(
+        &self,
+        webhook: &Webhook,
+        payload: Result<Value, UnifiError>,
+    ) -> Result<(), UnifiError> {
+        let payload = payload?;
+        let response = self.http.post(&webhook.url).json(&payload).send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        Err(UnifiError::Api {
+            status_code: status.as_u16(),
+            message: response.text().await.unwrap_or_default(),
+        })
+    }
+}
+
+/// Returns the camelCase name of an event, e.g. `deviceWentOffline`.
+pub fn event_name(event: &WatchEvent) -> &'static str {
+    match event {
+        WatchEvent::DeviceWentOffline { .. } => "deviceWentOffline",
+        WatchEvent::DeviceStateChanged { .. } => "deviceStateChanged",
+        WatchEvent::FirmwareUpdated { .. } => "firmwareUpdated",
+        WatchEvent::DeviceAdded { .. } => "deviceAdded",
+        WatchEvent::DeviceRemoved { .. } => "deviceRemoved",
+        WatchEvent::NewClientJoined { .. } => "newClientJoined",
+        WatchEvent::ClientLeft { .. } => "clientLeft",
+        WatchEvent::PollFailed { .. } => "pollFailed",
+    }
+}
+
+/// Returns a one-line, human-readable description of an event.
+pub fn summary(event: &WatchEvent) -> String {
+    match event {
+        WatchEvent::DeviceWentOffline { device_id } => format!("Device {} went offline", device_id),
+        WatchEvent::DeviceStateChanged {
+            device_id,
+            from,
+            to,
+        } => format!("Device {} changed from {:?} to {:?}", device_id, from, to),
+        WatchEvent::FirmwareUpdated {
+            device_id,
+            from,
+            to,
+        } => format!(
+            "Device {} updated from firmware {} to {}",
+            device_id, from, to
+        ),
+        WatchEvent::DeviceAdded { device_id } => format!("Device {} was added", device_id),
+        WatchEvent::DeviceRemoved { device_id } => format!("Device {} was removed", device_id),
+        WatchEvent::NewClientJoined { client_id } => format!("Client {} joined", client_id),
+        WatchEvent::ClientLeft { client_id } => format!("Client {} left", client_id),
+        WatchEvent::PollFailed { message } => format!("Polling the site failed: {}", message),
+    }
+}
+
+/// Returns the generic payload of an event: its `event` name, its fields in snake_case
+/// and a `summary`.
+///
+/// For example, `{"event": "deviceWentOffline", "device_id": "…", "summary": "…"}`.
+pub fn event_payload(event: &WatchEvent) -> Value {
+    Message::event(event).generic()
+}
+
+/// Returns the generic payload of an alert, with the event name `alertFiring` or
+/// `alertResolved` and the fields `rule`, `device_id`, `status`, `since`, `at` and, for
+/// threshold rules, `value`.
+pub fn alert_payload(alert: &Alert) -> Value {
+    Message::alert(alert).generic()
+}
+
+/// The text a payload is made of: an event name, fields and a summary.
+struct Message {
+    name: &'static str,
+    fields: Vec<(&'static str, String)>,
+    summary: String,
+}
+
+impl Message {
+    fn event(event: &WatchEvent) -> Self {
+        Self {
+            name: event_name(event),
+            fields: event_fields(event),
+            summary: summary(event),
+        }
+    }
+
+    fn alert(alert: &Alert) -> Self {
+        let (name, status) = match alert.status {
+            AlertStatus::Firing => ("alertFiring", "firing"),
+            AlertStatus::Resolved => ("alertResolved", "resolved"),
+        };
+        let mut fields = vec![
+            ("rule", alert.rule.clone()),
+            ("device_id", alert.device_id.to_string()),
+            ("status", status.to_string()),
+            ("since", alert.since.to_rfc3339()),
+            ("at", alert.at.to_rfc3339()),
+        ];
+        if let Some(value) = alert.value {
+            fields.push(("value", value.to_string()));
+        }
+        Self {
+            name,
+            fields,
+            summary: alert.summary(),
+        }
+    }
+
+    fn generic(&self) -> Value {
+        let mut payload = Map::new();
+        payload.insert("event".to_string(), json!(self.name));
+        for (key, value) in &self.fields {
+            payload.insert(key.to_string(), json!(value));
+        }
+        payload.insert("summary".to_string(), json!(self.summary));
+        Value::Object(payload)
+    }
+}
+
+/// Returns the fields of an event, as text.
+fn event_fields(event: &WatchEvent) -> Vec<(&'static str, String)> {
+    match event {
+        WatchEvent::DeviceWentOffline { device_id }
+        | WatchEvent::DeviceAdded { device_id }
+        | WatchEvent::DeviceRemoved { device_id } => vec![("device_id", device_id.to_string())],
+        WatchEvent::DeviceStateChanged {
+            device_id,
+            from,
+            to,
+        } => vec![
+            ("device_id", device_id.to_string()),
+            ("from", format!("{:?}", from)),
+            ("to", format!("{:?}", to)),
+        ],
+        WatchEvent::FirmwareUpdated {
+            device_id,
+            from,
+            to,
+        } => vec![
+            ("device_id", device_id.to_string()),
+            ("from", from.clone()),
+            ("to", to.clone()),
+        ],
+        WatchEvent::NewClientJoined { client_id } | WatchEvent::ClientLeft { client_id } => {
+            vec![("client_id", client_id.to_string())]
+        }
+        WatchEvent::PollFailed { message } => vec![("message", message.clone())],
+    }
+}
+
+/// Substitutes a message's fields into a template and parses the result.
+fn render_template(template: &str, message: &Message) -> Result<Value, UnifiError> {
+    let mut fields = message.fields.clone();
+    fields.push(("event", message.name.to_string()));
+    fields.push(("summary", message.summary.clone()));
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rendered.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let name = after[..end].trim();
+        if let Some((_, value)) = fields.iter().find(|(key, _)| *key == name) {
+            // Escaped as the inside of a JSON string, without the quotes.
+            let quoted = Value::String(value.clone()).to_string();
+            rendered.push_str(&quoted[1..quoted.len() - 1]);
+        }
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+
+    serde_json::from_str(&rendered)
+        .map_err(|e| UnifiError::Config(format!("webhook template is not valid JSON: {}", e)))
+}
@@ -0,0 +1,95 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Controls how failed requests are retried.
+///
+/// A request is retried when the response status is in the retryable set, or when the
+/// connection could not be established or timed out. The delay before retry `n`
+/// (starting at 1) is `initial_backoff * 2^(n - 1)`, capped at `max_backoff`, and with
+/// jitter enabled is scaled by a random factor between 0.5 and 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retryable_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, backing off from 200ms up to 5s with jitter, on 429/502/503/504.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            retryable_statuses: vec![429, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request exactly once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the total number of attempts, including the first; values below 1 are treated as 1.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry and the cap on later delays.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Enables or disables randomising retry delays.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Replaces the set of response statuses that trigger a retry.
+    pub fn retry_on(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    pub(crate) fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub(crate) fn is_retryable_status(&self, status: u16) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+
+    /// Returns the delay before retry number `retry`, starting at 1.
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let delay = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            delay.mul_f64(random_factor(0.5, 1.0))
+        } else {
+            delay
+        }
+    }
+}
+
+/// Returns a random factor between `low` and `high`.
+pub(crate) fn random_factor(low: f64, high: f64) -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    low + (high - low) * (random % 1000) as f64 / 1000.0
+}
@@ -0,0 +1,88 @@
+//! A view of one site, returned by `UnifiClient::site`.
+
+use crate::client::UnifiClient;
+use crate::errors::UnifiError;
+use crate::models::client::{ClientDetails, ClientOverview};
+use crate::models::device::{DeviceDetails, DeviceOverview};
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use crate::models::statistics::DeviceStatistics;
+use crate::snapshot::SiteSnapshot;
+use std::collections::HashMap;
+
+/// The site-scoped operations of a `UnifiClient`, with the site id filled in.
+///
+/// Handles are cheap to create and borrow the client, so keep the client and create a
+/// handle where it is needed:
+///
+/// ```rust,no_run
+/// # async fn example(client: &unifi_rs::UnifiClient) -> Result<(), unifi_rs::UnifiError> {
+/// let site = client.site(client.default_site_id().await?);
+/// for device in site.devices().await? {
+///     let statistics = site.statistics(device.id).await?;
+///     println!("{}: up {}s", device.name, statistics.uptime_sec);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct SiteHandle<'a> {
+    client: &'a UnifiClient,
+    site_id: SiteId,
+}
+
+impl<'a> SiteHandle<'a> {
+    pub(crate) fn new(client: &'a UnifiClient, site_id: SiteId) -> Self {
+        Self { client, site_id }
+    }
+
+    /// Returns the id of the site.
+    pub fn id(&self) -> SiteId {
+        self.site_id
+    }
+
+    /// Returns the client the handle sends requests through.
+    pub fn client(&self) -> &'a UnifiClient {
+        self.client
+    }
+
+    /// Lists every device in the site, see `UnifiClient::list_all_devices`.
+    pub async fn devices(&self) -> Result<Vec<DeviceOverview>, UnifiError> {
+        self.client.list_all_devices(self.site_id).await
+    }
+
+    /// Lists every client in the site, see `UnifiClient::list_all_clients`.
+    pub async fn clients(&self) -> Result<Vec<ClientOverview>, UnifiError> {
+        self.client.list_all_clients(self.site_id).await
+    }
+
+    /// Retrieves the details of a device, see `UnifiClient::get_device_details`.
+    pub async fn device(&self, device_id: DeviceId) -> Result<DeviceDetails, UnifiError> {
+        self.client
+            .get_device_details(self.site_id, device_id)
+            .await
+    }
+
+    /// Retrieves the details of a connected client, see `UnifiClient::get_client_details`.
+    pub async fn connected_client(&self, client_id: ClientId) -> Result<ClientDetails, UnifiError> {
+        self.client
+            .get_client_details(self.site_id, client_id)
+            .await
+    }
+
+    /// Retrieves the latest statistics of a device, see `UnifiClient::get_device_statistics`.
+    pub async fn statistics(&self, device_id: DeviceId) -> Result<DeviceStatistics, UnifiError> {
+        self.client
+            .get_device_statistics(self.site_id, device_id)
+            .await
+    }
+
+    /// Retrieves the latest statistics of every device, see `UnifiClient::get_site_statistics`.
+    pub async fn all_statistics(&self) -> Result<HashMap<DeviceId, DeviceStatistics>, UnifiError> {
+        self.client.get_site_statistics(self.site_id).await
+    }
+
+    /// Takes a snapshot of the site, see `UnifiClient::site_snapshot`.
+    pub async fn snapshot(&self) -> Result<SiteSnapshot, UnifiError> {
+        self.client.site_snapshot(self.site_id).await
+    }
+}
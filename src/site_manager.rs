@@ -0,0 +1,208 @@
+//! Client for the cloud UniFi Site Manager API (`api.ui.com`).
+//!
+//! Where `UnifiClient` talks to a single local controller, the Site Manager gives a
+//! cross-console view of every host, site and device linked to the account that owns
+//! the API key.
+
+use crate::client::parse_body;
+use crate::errors::UnifiError;
+use crate::models::site_manager::{
+    Host, HostDevices, IspMetricInterval, IspMetricRange, IspMetrics, ManagedSite, SiteManagerPage,
+};
+use reqwest::{header, Client, ClientBuilder, Request, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+/// The default Site Manager endpoint.
+pub const SITE_MANAGER_URL: &str = "https://api.ui.com";
+
+/// A builder for creating a `SiteManagerClient`.
+pub struct SiteManagerClientBuilder {
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl Default for SiteManagerClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SiteManagerClientBuilder {
+    pub fn new() -> Self {
+        Self {
+            base_url: SITE_MANAGER_URL.to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Overrides the Site Manager endpoint, mainly for testing.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn build(self) -> Result<SiteManagerClient, UnifiError> {
+        let api_key = self
+            .api_key
+            .ok_or_else(|| UnifiError::Config("API key is required".to_string()))?;
+
+        let mut headers = header::HeaderMap::new();
+        headers.insert(
+            "X-API-KEY",
+            header::HeaderValue::from_str(&api_key)
+                .map_err(|e| UnifiError::Config(e.to_string()))?,
+        );
+
+        let client = ClientBuilder::new().default_headers(headers).build()?;
+
+        Ok(SiteManagerClient {
+            client,
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+/// A client for the cloud Site Manager API.
+#[derive(Clone)]
+pub struct SiteManagerClient {
+    client: Client,
+    base_url: String,
+}
+
+/// The error body returned by the Site Manager API.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SiteManagerErrorResponse {
+    #[serde(default)]
+    message: Option<String>,
+}
+
+impl SiteManagerClient {
+    /// Lists every console linked to the account.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing all `Host`s on success, or a `UnifiError` on failure.
+    pub async fn list_hosts(&self) -> Result<Vec<Host>, UnifiError> {
+        self.get_all("/ea/hosts", &[]).await
+    }
+
+    /// Lists every site across every console linked to the account.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing all `ManagedSite`s on success, or a `UnifiError` on failure.
+    pub async fn list_sites(&self) -> Result<Vec<ManagedSite>, UnifiError> {
+        self.get_all("/ea/sites", &[]).await
+    }
+
+    /// Lists the devices adopted by consoles, grouped by console.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_ids` - The consoles to list devices for; empty means every console.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `HostDevices` entry per console on success, or a
+    /// `UnifiError` on failure.
+    pub async fn list_devices(&self, host_ids: &[&str]) -> Result<Vec<HostDevices>, UnifiError> {
+        let query: Vec<(&str, String)> = host_ids
+            .iter()
+            .map(|id| ("hostIds[]", id.to_string()))
+            .collect();
+        self.get_all("/ea/devices", &query).await
+    }
+
+    /// Retrieves ISP metrics (latency, packet loss, throughput and uptime) for every site.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The sample interval; 5-minute samples only cover the last day.
+    /// * `range` - The time range to cover.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `IspMetrics` of each site on success,
+    /// `UnifiError::InvalidInput` if the range exceeds what `interval` retains, or another
+    /// `UnifiError` on failure.
+    pub async fn get_isp_metrics(
+        &self,
+        interval: IspMetricInterval,
+        range: IspMetricRange,
+    ) -> Result<Vec<IspMetrics>, UnifiError> {
+        if interval == IspMetricInterval::FiveMinutes
+            && matches!(range, IspMetricRange::LastWeek | IspMetricRange::LastMonth)
+        {
+            return Err(UnifiError::InvalidInput(
+                "5-minute ISP metrics only cover the last 24 hours".to_string(),
+            ));
+        }
+
+        let path = format!("/ea/isp-metrics/{}", interval.as_str());
+        self.get_all(&path, &range.query_pairs()).await
+    }
+
+    /// Fetches every page of a list endpoint by following `nextToken`.
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Vec<T>, UnifiError> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut items = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        loop {
+            let mut request = self.client.get(&url).query(query);
+            if let Some(token) = &next_token {
+                request = request.query(&[("nextToken", token)]);
+            }
+            let page: SiteManagerPage<T> = self.send(request).await?;
+            items.extend(page.data);
+
+            match page.next_token {
+                Some(token) if !token.is_empty() => next_token = Some(token),
+                _ => return Ok(items),
+            }
+        }
+    }
+
+    /// Sends a request and deserializes its JSON body.
+    ///
+    /// Errors are wrapped in `UnifiError::Request` with the request's method and path.
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, UnifiError> {
+        let request = request.build()?;
+        let (method, path) = (request.method().clone(), request.url().path().to_string());
+        self.execute(request)
+            .await
+            .map_err(|error| error.with_request(method, &path))
+    }
+
+    /// Sends a built request and deserializes its JSON body.
+    async fn execute<T: DeserializeOwned>(&self, request: Request) -> Result<T, UnifiError> {
+        let response = self.client.execute(request).await?;
+        let status = response.status();
+        let resource = response.url().path().to_string();
+        let body = response.bytes().await?;
+
+        if status.is_success() {
+            parse_body(status.as_u16(), &body)
+        } else {
+            let error: Option<SiteManagerErrorResponse> = serde_json::from_slice(&body).ok();
+            Err(UnifiError::from_status(
+                status.as_u16(),
+                &resource,
+                error
+                    .and_then(|error| error.message)
+                    .unwrap_or_else(|| status.to_string()),
+            ))
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use crate::models::client::ClientOverview;
+use crate::models::device::{DeviceDetails, DeviceState};
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use crate::models::site::SiteOverview;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+
+/// The devices and clients of one site at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteSnapshot {
+    pub site_id: SiteId,
+    pub taken_at: DateTime<Utc>,
+    pub devices: Vec<DeviceDetails>,
+    pub clients: Vec<ClientOverview>,
+}
+
+/// Every site of a controller, with its devices and clients, at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inventory {
+    pub taken_at: DateTime<Utc>,
+    pub sites: Vec<SiteInventory>,
+}
+
+/// The inventory of every controller of a `FleetClient`, keyed by controller name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub controllers: BTreeMap<String, Inventory>,
+}
+
+/// One site of an `Inventory`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteInventory {
+    pub site: SiteOverview,
+    pub snapshot: SiteSnapshot,
+}
+
+impl Inventory {
+    /// Returns the inventory of the site with id `site_id`.
+    pub fn site(&self, site_id: SiteId) -> Option<&SiteInventory> {
+        self.sites.iter().find(|site| site.site.id == site_id)
+    }
+
+    /// Returns every device of every site, with the id of its site.
+    pub fn devices(&self) -> impl Iterator<Item = (SiteId, &DeviceDetails)> {
+        self.sites.iter().flat_map(|site| {
+            let site_id = site.site.id;
+            site.snapshot
+                .devices
+                .iter()
+                .map(move |device| (site_id, device))
+        })
+    }
+
+    /// Computes what changed going from this inventory to `other`.
+    ///
+    /// Devices are compared as by `SiteSnapshot::diff`, and clients present in both are
+    /// also compared on IP address. The devices and clients of an added or removed site
+    /// are reported as added or removed too.
+    pub fn diff(&self, other: &Inventory) -> Vec<InventoryChange> {
+        let mut changes = Vec::new();
+        let empty = |site_id| SiteSnapshot {
+            site_id,
+            taken_at: other.taken_at,
+            devices: Vec::new(),
+            clients: Vec::new(),
+        };
+
+        let mut site_ids: Vec<SiteId> = self.sites.iter().map(|site| site.site.id).collect();
+        site_ids.extend(
+            other
+                .sites
+                .iter()
+                .map(|site| site.site.id)
+                .filter(|id| self.site(*id).is_none()),
+        );
+
+        for site_id in site_ids {
+            let none = empty(site_id);
+            let (before, after) = match (self.site(site_id), other.site(site_id)) {
+                (Some(before), Some(after)) => (&before.snapshot, &after.snapshot),
+                (Some(before), None) => {
+                    changes.push(InventoryChange::SiteRemoved { site_id });
+                    (&before.snapshot, &none)
+                }
+                (None, Some(after)) => {
+                    changes.push(InventoryChange::SiteAdded { site_id });
+                    (&none, &after.snapshot)
+                }
+                (None, None) => continue,
+            };
+            site_changes(site_id, before, after, &mut changes);
+        }
+        changes
+    }
+
+    /// Returns every client of every site, with the id of its site.
+    pub fn clients(&self) -> impl Iterator<Item = (SiteId, &ClientOverview)> {
+        self.sites.iter().flat_map(|site| {
+            let site_id = site.site.id;
+            site.snapshot
+                .clients
+                .iter()
+                .map(move |client| (site_id, client))
+        })
+    }
+}
+
+/// One change between two `Inventory`s, see `Inventory::diff`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+#[non_exhaustive]
+pub enum InventoryChange {
+    SiteAdded {
+        site_id: SiteId,
+    },
+    SiteRemoved {
+        site_id: SiteId,
+    },
+    DeviceAdded {
+        site_id: SiteId,
+        device_id: DeviceId,
+    },
+    DeviceRemoved {
+        site_id: SiteId,
+        device_id: DeviceId,
+    },
+    DeviceStateChanged {
+        site_id: SiteId,
+        device_id: DeviceId,
+        before: DeviceState,
+        after: DeviceState,
+    },
+    FirmwareChanged {
+        site_id: SiteId,
+        device_id: DeviceId,
+        before: String,
+        after: String,
+    },
+    DeviceIpChanged {
+        site_id: SiteId,
+        device_id: DeviceId,
+        before: Option<IpAddr>,
+        after: Option<IpAddr>,
+    },
+    ClientJoined {
+        site_id: SiteId,
+        client_id: ClientId,
+    },
+    ClientLeft {
+        site_id: SiteId,
+        client_id: ClientId,
+    },
+    ClientIpChanged {
+        site_id: SiteId,
+        client_id: ClientId,
+        before: Option<IpAddr>,
+        after: Option<IpAddr>,
+    },
+}
+
+/// The changes between two `SiteSnapshot`s.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteDiff {
+    pub added_devices: Vec<DeviceId>,
+    pub removed_devices: Vec<DeviceId>,
+    pub changed_devices: Vec<DeviceChange>,
+    pub added_clients: Vec<ClientId>,
+    pub removed_clients: Vec<ClientId>,
+}
+
+/// The tracked fields that changed on a device present in both snapshots.
+///
+/// Each field holds `(before, after)` when it changed, or `None` when it did not.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceChange {
+    pub device_id: DeviceId,
+    pub state: Option<(DeviceState, DeviceState)>,
+    pub firmware_version: Option<(String, String)>,
+    pub ip_address: Option<(Option<IpAddr>, Option<IpAddr>)>,
+}
+
+impl SiteDiff {
+    /// Returns whether the two snapshots were identical in every tracked respect.
+    pub fn is_empty(&self) -> bool {
+        self.added_devices.is_empty()
+            && self.removed_devices.is_empty()
+            && self.changed_devices.is_empty()
+            && self.added_clients.is_empty()
+            && self.removed_clients.is_empty()
+    }
+}
+
+impl SiteSnapshot {
+    /// Computes what changed going from this snapshot to `other`.
+    ///
+    /// Devices are compared on state, firmware version and IP address; clients are only
+    /// tracked for arrival and departure.
+    pub fn diff(&self, other: &SiteSnapshot) -> SiteDiff {
+        let before: HashMap<DeviceId, &DeviceDetails> = self
+            .devices
+            .iter()
+            .map(|device| (device.id, device))
+            .collect();
+        let after: HashMap<DeviceId, &DeviceDetails> = other
+            .devices
+            .iter()
+            .map(|device| (device.id, device))
+            .collect();
+
+        let mut diff = SiteDiff {
+            added_devices: other
+                .devices
+                .iter()
+                .filter(|device| !before.contains_key(&device.id))
+                .map(|device| device.id)
+                .collect(),
+            removed_devices: self
+                .devices
+                .iter()
+                .filter(|device| !after.contains_key(&device.id))
+                .map(|device| device.id)
+                .collect(),
+            ..SiteDiff::default()
+        };
+
+        for old in &self.devices {
+            if let Some(new) = after.get(&old.id) {
+                let change = DeviceChange {
+                    device_id: old.id,
+                    state: changed(&old.state, &new.state),
+                    firmware_version: changed(&old.firmware_version, &new.firmware_version),
+                    ip_address: changed(&old.ip_address, &new.ip_address),
+                };
+                if change.state.is_some()
+                    || change.firmware_version.is_some()
+                    || change.ip_address.is_some()
+                {
+                    diff.changed_devices.push(change);
+                }
+            }
+        }
+
+        let client_ids = |snapshot: &SiteSnapshot| -> Vec<ClientId> {
+            snapshot
+                .clients
+                .iter()
+                .map(|client| client.base().id)
+                .collect()
+        };
+        let (before_clients, after_clients) = (client_ids(self), client_ids(other));
+        let before_set: HashSet<&ClientId> = before_clients.iter().collect();
+        let after_set: HashSet<&ClientId> = after_clients.iter().collect();
+        diff.added_clients = after_clients
+            .iter()
+            .filter(|id| !before_set.contains(id))
+            .copied()
+            .collect();
+        diff.removed_clients = before_clients
+            .iter()
+            .filter(|id| !after_set.contains(id))
+            .copied()
+            .collect();
+
+        diff
+    }
+}
+
+/// Appends the changes between two snapshots of one site to `changes`.
+fn site_changes(
+    site_id: SiteId,
+    before: &SiteSnapshot,
+    after: &SiteSnapshot,
+    changes: &mut Vec<InventoryChange>,
+) {
+    let diff = before.diff(after);
+    changes.extend(
+        diff.added_devices
+            .into_iter()
+            .map(|device_id| InventoryChange::DeviceAdded { site_id, device_id }),
+    );
+    changes.extend(
+        diff.removed_devices
+            .into_iter()
+            .map(|device_id| InventoryChange::DeviceRemoved { site_id, device_id }),
+    );
+    for change in diff.changed_devices {
+        let device_id = change.device_id;
+        if let Some((before, after)) = change.state {
+            changes.push(InventoryChange::DeviceStateChanged {
+                site_id,
+                device_id,
+                before,
+                after,
+            });
+        }
+        if let Some((before, after)) = change.firmware_version {
+            changes.push(InventoryChange::FirmwareChanged {
+                site_id,
+                device_id,
+                before,
+                after,
+            });
+        }
+        if let Some((before, after)) = change.ip_address {
+            changes.push(InventoryChange::DeviceIpChanged {
+                site_id,
+                device_id,
+                before,
+                after,
+            });
+        }
+    }
+    changes.extend(
+        diff.added_clients
+            .into_iter()
+            .map(|client_id| InventoryChange::ClientJoined { site_id, client_id }),
+    );
+    changes.extend(
+        diff.removed_clients
+            .into_iter()
+            .map(|client_id| InventoryChange::ClientLeft { site_id, client_id }),
+    );
+
+    let after_ips: HashMap<ClientId, Option<IpAddr>> = after
+        .clients
+        .iter()
+        .map(|client| (client.base().id, client.base().ip_address))
+        .collect();
+    for client in &before.clients {
+        let client_id = client.base().id;
+        if let Some((before, after)) = after_ips
+            .get(&client_id)
+            .and_then(|after| changed(&client.base().ip_address, after))
+        {
+            changes.push(InventoryChange::ClientIpChanged {
+                site_id,
+                client_id,
+                before,
+                after,
+            });
+        }
+    }
+}
+
+/// Returns `(before, after)` if the values differ.
+fn changed<T: PartialEq + Clone>(before: &T, after: &T) -> Option<(T, T)> {
+    (before != after).then(|| (before.clone(), after.clone()))
+}
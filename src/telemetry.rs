@@ -0,0 +1,52 @@
+//! Instrumentation of `UnifiClient` through the `metrics` facade.
+//!
+//! With the `metrics` feature enabled, every request attempt is recorded under the names
+//! below; install any `metrics` recorder (e.g. a Prometheus exporter) to collect them.
+//! Without the feature nothing is recorded.
+
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+/// Counter of request attempts, labelled by `method` and `status` (the HTTP status code,
+/// or `"transport"` when no response was received).
+pub const REQUESTS_TOTAL: &str = "unifi_requests_total";
+
+/// Counter of attempts that failed, labelled like `REQUESTS_TOTAL`. Covers non-2xx
+/// responses and transport failures such as refused connections and timeouts.
+pub const REQUEST_ERRORS_TOTAL: &str = "unifi_request_errors_total";
+
+/// Counter of retries, labelled by `reason`: `"rate_limited"` for waits on HTTP 429
+/// and `"retry_policy"` for retries made by the `RetryPolicy`.
+pub const RETRIES_TOTAL: &str = "unifi_retries_total";
+
+/// Histogram of attempt latency in seconds, from sending the request until its response
+/// headers arrive, labelled by `method`.
+pub const REQUEST_DURATION_SECONDS: &str = "unifi_request_duration_seconds";
+
+/// Records a single request attempt; `status` is `None` if no response was received.
+pub(crate) fn record_request(method: &Method, status: Option<StatusCode>, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        let method = method.as_str().to_string();
+        let status_label =
+            status.map_or_else(|| "transport".to_string(), |s| s.as_u16().to_string());
+        let labels = [("method", method.clone()), ("status", status_label)];
+
+        metrics::counter!(REQUESTS_TOTAL, &labels).increment(1);
+        if !status.is_some_and(|status| status.is_success()) {
+            metrics::counter!(REQUEST_ERRORS_TOTAL, &labels).increment(1);
+        }
+        metrics::histogram!(REQUEST_DURATION_SECONDS, "method" => method)
+            .record(elapsed.as_secs_f64());
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (method, status, elapsed);
+}
+
+/// Records that a request is about to be retried.
+pub(crate) fn record_retry(reason: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(RETRIES_TOTAL, "reason" => reason).increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = reason;
+}
@@ -0,0 +1,494 @@
+//! Builders that fabricate model values for tests.
+//!
+//! Every builder starts from a realistic, fully populated value with a fresh random id,
+//! so a test only sets the fields it cares about.
+
+use crate::models::client::{
+    BaseClientOverview, ClientOverview, TeleportClientOverview, VpnClientOverview,
+    WiredClientOverview, WirelessClientOverview,
+};
+use crate::models::common::{ConnectorType, FrequencyBand, PortState, WlanStandard};
+use crate::models::device::{
+    DeviceDetails, DeviceOverview, DevicePhysicalInterfaces, DeviceState, DeviceUplinkInterface,
+    EthernetPortOverview, WirelessRadioOverview,
+};
+use crate::models::id::{ClientId, DeviceId};
+use crate::models::statistics::{
+    DeviceInterfaceStatistics, DeviceStatistics, DeviceUplinkStatistics, EthernetPortStatistics,
+    WirelessRadioStatistics,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// The timestamp used for `connected_at`, `adopted_at` and similar fields by default.
+fn reference_time() -> DateTime<Utc> {
+    "2025-01-18T12:00:00Z".parse().expect("valid timestamp")
+}
+
+/// Derives a stable MAC address from an id.
+pub(crate) fn mac_for(id: impl Into<Uuid>) -> String {
+    let id: Uuid = id.into();
+    let bytes = id.as_bytes();
+    format!(
+        "f4:e2:c6:{:02x}:{:02x}:{:02x}",
+        bytes[0], bytes[1], bytes[15]
+    )
+}
+
+/// A builder for `DeviceOverview`, defaulting to an online switch.
+#[derive(Debug, Clone)]
+pub struct DeviceOverviewBuilder {
+    overview: DeviceOverview,
+}
+
+impl Default for DeviceOverviewBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceOverviewBuilder {
+    pub fn new() -> Self {
+        let id = DeviceId::new(Uuid::new_v4());
+        Self {
+            overview: DeviceOverview {
+                id,
+                name: "Switch".to_string(),
+                model: "USW-Lite-8-PoE".to_string(),
+                mac_address: mac_for(id),
+                ip_address: Some(IpAddr::from([192, 168, 1, 10])),
+                state: DeviceState::Online,
+                features: vec!["switching".to_string()],
+                interfaces: vec!["ports".to_string()],
+                #[cfg(feature = "raw-fields")]
+                extra: Default::default(),
+            },
+        }
+    }
+
+    /// Sets the id; the MAC address is re-derived from it.
+    pub fn id(mut self, id: DeviceId) -> Self {
+        self.overview.id = id;
+        self.overview.mac_address = mac_for(id);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.overview.name = name.into();
+        self
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.overview.model = model.into();
+        self
+    }
+
+    pub fn mac_address(mut self, mac_address: impl Into<String>) -> Self {
+        self.overview.mac_address = mac_address.into();
+        self
+    }
+
+    /// Sets the IP address; pass `None` for a device without one.
+    pub fn ip_address(mut self, ip_address: impl Into<Option<IpAddr>>) -> Self {
+        self.overview.ip_address = ip_address.into();
+        self
+    }
+
+    pub fn state(mut self, state: DeviceState) -> Self {
+        self.overview.state = state;
+        self
+    }
+
+    /// Replaces the feature list, e.g. `["switching", "accessPoint"]`.
+    pub fn features<I, S>(mut self, features: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.overview.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn build(self) -> DeviceOverview {
+        self.overview
+    }
+}
+
+/// A builder for `DeviceDetails`, defaulting to an online, adopted switch with one port.
+#[derive(Debug, Clone)]
+pub struct DeviceDetailsBuilder {
+    details: DeviceDetails,
+}
+
+impl Default for DeviceDetailsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceDetailsBuilder {
+    pub fn new() -> Self {
+        let id = DeviceId::new(Uuid::new_v4());
+        Self {
+            details: DeviceDetails {
+                id,
+                name: "Switch".to_string(),
+                model: "USW-Lite-8-PoE".to_string(),
+                supported: true,
+                mac_address: mac_for(id),
+                ip_address: Some(IpAddr::from([192, 168, 1, 10])),
+                state: DeviceState::Online,
+                firmware_version: "7.1.26".to_string(),
+                firmware_updatable: false,
+                adopted_at: Some(reference_time() - Duration::days(30)),
+                provisioned_at: Some(reference_time()),
+                configuration_id: "7596498d2f367dc2".to_string(),
+                uplink: None,
+                features: None,
+                interfaces: Some(DevicePhysicalInterfaces {
+                    ports: vec![port(1)],
+                    radios: Vec::new(),
+                }),
+                #[cfg(feature = "raw-fields")]
+                extra: Default::default(),
+            },
+        }
+    }
+
+    /// Sets the id; the MAC address is re-derived from it.
+    pub fn id(mut self, id: DeviceId) -> Self {
+        self.details.id = id;
+        self.details.mac_address = mac_for(id);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.details.name = name.into();
+        self
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.details.model = model.into();
+        self
+    }
+
+    pub fn mac_address(mut self, mac_address: impl Into<String>) -> Self {
+        self.details.mac_address = mac_address.into();
+        self
+    }
+
+    /// Sets the IP address; pass `None` for a device without one.
+    pub fn ip_address(mut self, ip_address: impl Into<Option<IpAddr>>) -> Self {
+        self.details.ip_address = ip_address.into();
+        self
+    }
+
+    pub fn state(mut self, state: DeviceState) -> Self {
+        self.details.state = state;
+        self
+    }
+
+    pub fn firmware_version(mut self, version: impl Into<String>) -> Self {
+        self.details.firmware_version = version.into();
+        self
+    }
+
+    pub fn firmware_updatable(mut self, updatable: bool) -> Self {
+        self.details.firmware_updatable = updatable;
+        self
+    }
+
+    /// Connects the device's uplink to `device_id`.
+    pub fn uplink(mut self, device_id: DeviceId) -> Self {
+        self.details.uplink = Some(DeviceUplinkInterface { device_id });
+        self
+    }
+
+    /// Replaces the Ethernet ports with `count` ports that are up at 1 Gbps.
+    pub fn ports(mut self, count: i32) -> Self {
+        self.interfaces().ports = (1..=count).map(port).collect();
+        self
+    }
+
+    /// Adds a Wi-Fi 6 radio on `band`.
+    pub fn radio(mut self, band: FrequencyBand) -> Self {
+        let channel = match band {
+            FrequencyBand::Band2_4GHz => 6,
+            _ => 36,
+        };
+        self.interfaces().radios.push(WirelessRadioOverview {
+            wlan_standard: Some(WlanStandard::IEEE802_11AX),
+            frequency_ghz: Some(band),
+            channel_width_mhz: Some(if channel == 6 { 20 } else { 80 }),
+            channel: Some(channel),
+        });
+        self
+    }
+
+    pub fn build(self) -> DeviceDetails {
+        self.details
+    }
+
+    fn interfaces(&mut self) -> &mut DevicePhysicalInterfaces {
+        self.details.interfaces.get_or_insert_with(Default::default)
+    }
+}
+
+fn port(idx: i32) -> EthernetPortOverview {
+    EthernetPortOverview {
+        idx,
+        state: PortState::Up,
+        connector: ConnectorType::RJ45,
+        max_speed_mbps: 1000,
+        speed_mbps: 1000,
+    }
+}
+
+/// The kind of client a `ClientOverviewBuilder` produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientKind {
+    Wired,
+    Wireless,
+    Vpn,
+    Teleport,
+}
+
+/// A builder for `ClientOverview`.
+///
+/// Start from `wired`, `wireless`, `vpn` or `teleport`; fields that the chosen type does
+/// not have, such as `band` on a wired client, are ignored.
+#[derive(Debug, Clone)]
+pub struct ClientOverviewBuilder {
+    kind: ClientKind,
+    base: BaseClientOverview,
+    mac_address: String,
+    uplink_device_id: DeviceId,
+    band: Option<FrequencyBand>,
+    wlan_id: Option<Uuid>,
+}
+
+impl ClientOverviewBuilder {
+    fn new(kind: ClientKind, name: &str, ip_address: [u8; 4]) -> Self {
+        let id = ClientId::new(Uuid::new_v4());
+        Self {
+            kind,
+            base: BaseClientOverview {
+                id,
+                name: Some(name.to_string()),
+                connected_at: reference_time(),
+                ip_address: Some(IpAddr::from(ip_address)),
+                #[cfg(feature = "raw-fields")]
+                extra: Default::default(),
+            },
+            mac_address: mac_for(id),
+            uplink_device_id: DeviceId::new(Uuid::new_v4()),
+            band: None,
+            wlan_id: None,
+        }
+    }
+
+    /// A wired client connected to a random uplink device.
+    pub fn wired() -> Self {
+        Self::new(ClientKind::Wired, "Desktop PC", [192, 168, 1, 100])
+    }
+
+    /// A wireless client on 5 GHz, connected to a random access point.
+    pub fn wireless() -> Self {
+        let mut builder = Self::new(ClientKind::Wireless, "Phone", [192, 168, 1, 50]);
+        builder.band = Some(FrequencyBand::Band5GHz);
+        builder
+    }
+
+    /// A client connected through the site's VPN server.
+    pub fn vpn() -> Self {
+        Self::new(ClientKind::Vpn, "Remote Laptop", [192, 168, 2, 10])
+    }
+
+    /// A client connected through Teleport.
+    pub fn teleport() -> Self {
+        Self::new(ClientKind::Teleport, "Teleport Phone", [192, 168, 3, 10])
+    }
+
+    /// Sets the id; the MAC address is re-derived from it.
+    pub fn id(mut self, id: ClientId) -> Self {
+        self.base.id = id;
+        self.mac_address = mac_for(id);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.base.name = Some(name.into());
+        self
+    }
+
+    pub fn connected_at(mut self, connected_at: DateTime<Utc>) -> Self {
+        self.base.connected_at = connected_at;
+        self
+    }
+
+    /// Sets the IP address; pass `None` for a client without one.
+    pub fn ip_address(mut self, ip_address: impl Into<Option<IpAddr>>) -> Self {
+        self.base.ip_address = ip_address.into();
+        self
+    }
+
+    pub fn mac_address(mut self, mac_address: impl Into<String>) -> Self {
+        self.mac_address = mac_address.into();
+        self
+    }
+
+    pub fn uplink_device_id(mut self, device_id: DeviceId) -> Self {
+        self.uplink_device_id = device_id;
+        self
+    }
+
+    pub fn band(mut self, band: Option<FrequencyBand>) -> Self {
+        self.band = band;
+        self
+    }
+
+    pub fn wlan_id(mut self, wlan_id: Uuid) -> Self {
+        self.wlan_id = Some(wlan_id);
+        self
+    }
+
+    pub fn build(self) -> ClientOverview {
+        match self.kind {
+            ClientKind::Wired => ClientOverview::Wired(WiredClientOverview {
+                base: self.base,
+                mac_address: self.mac_address,
+                uplink_device_id: self.uplink_device_id,
+            }),
+            ClientKind::Wireless => ClientOverview::Wireless(WirelessClientOverview {
+                base: self.base,
+                mac_address: self.mac_address,
+                uplink_device_id: self.uplink_device_id,
+                band: self.band,
+                wlan_id: self.wlan_id,
+            }),
+            ClientKind::Vpn => ClientOverview::Vpn(VpnClientOverview { base: self.base }),
+            ClientKind::Teleport => {
+                ClientOverview::Teleport(TeleportClientOverview { base: self.base })
+            }
+        }
+    }
+}
+
+/// A builder for `DeviceStatistics`, defaulting to a lightly loaded device up for a week.
+#[derive(Debug, Clone)]
+pub struct DeviceStatisticsBuilder {
+    statistics: DeviceStatistics,
+}
+
+impl Default for DeviceStatisticsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceStatisticsBuilder {
+    pub fn new() -> Self {
+        Self {
+            statistics: DeviceStatistics {
+                uptime_sec: 7 * 24 * 3600,
+                last_heartbeat_at: reference_time(),
+                next_heartbeat_at: reference_time() + Duration::seconds(5),
+                load_average_1min: Some(0.42),
+                load_average_5min: Some(0.38),
+                load_average_15min: Some(0.35),
+                cpu_utilization_pct: Some(12.5),
+                memory_utilization_pct: Some(61.0),
+                uplink: Some(DeviceUplinkStatistics {
+                    tx_rate_bps: 309_720,
+                    rx_rate_bps: 32_288,
+                    interface_name: None,
+                    speed_mbps: Some(1000),
+                    full_duplex: Some(true),
+                }),
+                interfaces: None,
+                #[cfg(feature = "raw-fields")]
+                extra: Default::default(),
+            },
+        }
+    }
+
+    pub fn uptime_sec(mut self, uptime_sec: i64) -> Self {
+        self.statistics.uptime_sec = uptime_sec;
+        self
+    }
+
+    /// Sets the heartbeat timestamps, with the next heartbeat five seconds after `last`.
+    pub fn last_heartbeat_at(mut self, last: DateTime<Utc>) -> Self {
+        self.statistics.last_heartbeat_at = last;
+        self.statistics.next_heartbeat_at = last + Duration::seconds(5);
+        self
+    }
+
+    pub fn cpu_utilization_pct(mut self, pct: f64) -> Self {
+        self.statistics.cpu_utilization_pct = Some(pct);
+        self
+    }
+
+    pub fn memory_utilization_pct(mut self, pct: f64) -> Self {
+        self.statistics.memory_utilization_pct = Some(pct);
+        self
+    }
+
+    /// Sets the 1, 5 and 15 minute load averages.
+    pub fn load_averages(mut self, one: f64, five: f64, fifteen: f64) -> Self {
+        self.statistics.load_average_1min = Some(one);
+        self.statistics.load_average_5min = Some(five);
+        self.statistics.load_average_15min = Some(fifteen);
+        self
+    }
+
+    /// Sets the uplink rates, or removes the uplink when `None`.
+    pub fn uplink_rates(mut self, rates: Option<(i64, i64)>) -> Self {
+        self.statistics.uplink = rates.map(|(tx_rate_bps, rx_rate_bps)| DeviceUplinkStatistics {
+            tx_rate_bps,
+            rx_rate_bps,
+            interface_name: None,
+            speed_mbps: Some(1000),
+            full_duplex: Some(true),
+        });
+        self
+    }
+
+    /// Adds radio statistics for `band`.
+    pub fn radio(mut self, band: FrequencyBand, tx_retries_pct: f64) -> Self {
+        self.interfaces().radios.push(WirelessRadioStatistics {
+            frequency_ghz: Some(band),
+            tx_retries_pct: Some(tx_retries_pct),
+        });
+        self
+    }
+
+    /// Adds counters for port `idx`.
+    pub fn port(mut self, idx: i32, tx_bytes: i64, rx_bytes: i64) -> Self {
+        self.interfaces().ports.push(EthernetPortStatistics {
+            idx,
+            tx_bytes: Some(tx_bytes),
+            rx_bytes: Some(rx_bytes),
+            tx_packets: None,
+            rx_packets: None,
+            tx_errors: Some(0),
+            rx_errors: Some(0),
+            poe_power_w: None,
+        });
+        self
+    }
+
+    pub fn build(self) -> DeviceStatistics {
+        self.statistics
+    }
+
+    fn interfaces(&mut self) -> &mut DeviceInterfaceStatistics {
+        self.statistics
+            .interfaces
+            .get_or_insert_with(|| DeviceInterfaceStatistics {
+                radios: Vec::new(),
+                ports: Vec::new(),
+            })
+    }
+}
@@ -0,0 +1,405 @@
+//! A fake UniFi controller for hermetic tests.
+//!
+//! `FakeUnifiServer` serves the Network API on a local port from a fixed set of
+//! fixtures: one site with a gateway and an access point, a wired and a wireless client
+//! and a hotspot voucher. List endpoints honour `offset` and `limit`, and every request
+//! must carry `FAKE_API_KEY`. Actions and other writes are acknowledged but do not change
+//! the fixtures. The builders in this module fabricate individual model values.
+//! Only available with the `test-util` feature.
+
+mod builders;
+
+pub use builders::{
+    ClientOverviewBuilder, DeviceDetailsBuilder, DeviceOverviewBuilder, DeviceStatisticsBuilder,
+};
+
+use crate::client::{UnifiClient, UnifiClientBuilder};
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use builders::mac_for;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+/// The API key accepted by `FakeUnifiServer`.
+pub const FAKE_API_KEY: &str = "fake-api-key";
+
+const SITE_ID: SiteId = SiteId::new(Uuid::from_u128(0x5e5e_0000_0000_4000_8000_0000_0000_0001));
+const GATEWAY_ID: DeviceId =
+    DeviceId::new(Uuid::from_u128(0xde5e_0000_0000_4000_8000_0000_0000_0001));
+const ACCESS_POINT_ID: DeviceId =
+    DeviceId::new(Uuid::from_u128(0xde5e_0000_0000_4000_8000_0000_0000_0002));
+const WIRED_CLIENT_ID: ClientId =
+    ClientId::new(Uuid::from_u128(0xc1e0_0000_0000_4000_8000_0000_0000_0001));
+const WIRELESS_CLIENT_ID: ClientId =
+    ClientId::new(Uuid::from_u128(0xc1e0_0000_0000_4000_8000_0000_0000_0002));
+const VOUCHER_ID: Uuid = Uuid::from_u128(0x0c4e_0000_0000_4000_8000_0000_0000_0001);
+
+/// Builds a response from the incoming request.
+type Responder = Box<dyn Fn(&Request) -> ResponseTemplate + Send + Sync>;
+
+/// A local HTTP server imitating a UniFi Network controller.
+pub struct FakeUnifiServer {
+    server: MockServer,
+}
+
+impl FakeUnifiServer {
+    /// Starts the server on a random local port with all fixtures loaded.
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        mount_fixtures(&server).await;
+        Self { server }
+    }
+
+    /// Returns the base URL to pass to `UnifiClientBuilder::new`.
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Returns a client configured for this server.
+    pub fn client(&self) -> UnifiClient {
+        UnifiClientBuilder::new(self.uri())
+            .api_key(FAKE_API_KEY)
+            .build()
+            .expect("client for the fake server")
+    }
+
+    /// Returns the underlying `wiremock` server, e.g. to mount extra responses.
+    ///
+    /// Mocks mounted with `with_priority(1)` take precedence over the fixtures.
+    pub fn mock_server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// The id of the only site.
+    pub fn site_id(&self) -> SiteId {
+        SITE_ID
+    }
+
+    /// The id of the gateway, which has no uplink.
+    pub fn gateway_id(&self) -> DeviceId {
+        GATEWAY_ID
+    }
+
+    /// The id of the access point, which uplinks to the gateway.
+    pub fn access_point_id(&self) -> DeviceId {
+        ACCESS_POINT_ID
+    }
+
+    /// The id of the wired client, connected to the gateway.
+    pub fn wired_client_id(&self) -> ClientId {
+        WIRED_CLIENT_ID
+    }
+
+    /// The id of the wireless client, connected to the access point on 5 GHz.
+    pub fn wireless_client_id(&self) -> ClientId {
+        WIRELESS_CLIENT_ID
+    }
+
+    /// The UUID of the hotspot voucher.
+    pub fn voucher_id(&self) -> Uuid {
+        VOUCHER_ID
+    }
+}
+
+async fn mount_fixtures(server: &MockServer) {
+    let site = format!("/v1/sites/{}", SITE_ID);
+    let uuid = "[0-9a-f-]{36}";
+
+    // Rejects any request without the fake API key before the fixtures are consulted.
+    Mock::given(|request: &Request| {
+        request.headers.get("X-API-KEY").map(|v| v.as_bytes()) != Some(FAKE_API_KEY.as_bytes())
+    })
+    .respond_with(error(401, "Unauthorized"))
+    .with_priority(1)
+    .mount(server)
+    .await;
+
+    let routes: Vec<(&str, String, Responder)> = vec![
+        (
+            "GET",
+            "/v1/info".to_string(),
+            fixed(json!({ "applicationVersion": "9.0.108" })),
+        ),
+        (
+            "GET",
+            "/v1/health".to_string(),
+            fixed(json!({
+                "status": "OK",
+                "subsystems": [
+                    { "name": "network", "status": "OK" },
+                    { "name": "storage", "status": "OK" }
+                ]
+            })),
+        ),
+        (
+            "GET",
+            "/v1/sites".to_string(),
+            paged(vec![json!({ "id": SITE_ID, "name": "Default" })]),
+        ),
+        (
+            "GET",
+            format!("{}/devices", site),
+            paged(vec![
+                device_overview(GATEWAY_ID, "Gateway", "UCG-Ultra", "192.168.1.1"),
+                device_overview(ACCESS_POINT_ID, "Office AP", "U6-Pro", "192.168.1.20"),
+            ]),
+        ),
+        (
+            "GET",
+            format!("{}/clients", site),
+            paged(vec![wired_client(), wireless_client()]),
+        ),
+        (
+            "GET",
+            format!("{}/hotspot/vouchers", site),
+            paged(vec![voucher(VOUCHER_ID, "4861409510")]),
+        ),
+        (
+            "GET",
+            format!("{}/hotspot/vouchers/{}", site, VOUCHER_ID),
+            fixed(voucher(VOUCHER_ID, "4861409510")),
+        ),
+        (
+            "GET",
+            format!("{}/clients/{}", site, WIRED_CLIENT_ID),
+            fixed(wired_client()),
+        ),
+        (
+            "GET",
+            format!("{}/clients/{}", site, WIRELESS_CLIENT_ID),
+            fixed(wireless_client()),
+        ),
+        (
+            "GET",
+            format!("{}/devices/{}", site, GATEWAY_ID),
+            fixed(device_details(GATEWAY_ID, "Gateway", "UCG-Ultra", None)),
+        ),
+        (
+            "GET",
+            format!("{}/devices/{}", site, ACCESS_POINT_ID),
+            fixed(device_details(
+                ACCESS_POINT_ID,
+                "Office AP",
+                "U6-Pro",
+                Some(GATEWAY_ID),
+            )),
+        ),
+    ];
+    for (verb, route, responder) in routes {
+        Mock::given(method(verb))
+            .and(path(route))
+            .respond_with(responder)
+            .mount(server)
+            .await;
+    }
+
+    Mock::given(method("GET"))
+        .and(path_regex(format!(
+            "^{}/devices/({}|{})/statistics/latest$",
+            site, GATEWAY_ID, ACCESS_POINT_ID
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_json(statistics()))
+        .mount(server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(format!("^{}/clients/{}/actions$", site, uuid)))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            match body["action"].as_str() {
+                Some("AUTHORIZE_GUEST_ACCESS") => ResponseTemplate::new(200).set_body_json(json!({
+                    "action": "AUTHORIZE_GUEST_ACCESS",
+                    "grantedAuthorization": {
+                        "authorizedAt": "2025-01-18T20:00:00Z",
+                        "expiresAt": "2025-01-19T20:00:00Z"
+                    }
+                })),
+                _ => ResponseTemplate::new(200),
+            }
+        })
+        .mount(server)
+        .await;
+
+    let writes = [
+        ("POST", format!("^{}/devices/{}/actions$", site, uuid)),
+        (
+            "POST",
+            format!(
+                "^{}/devices/{}/interfaces/ports/[0-9]+/actions$",
+                site, uuid
+            ),
+        ),
+        ("PATCH", format!("^{}/devices/{}$", site, uuid)),
+        ("DELETE", format!("^{}/hotspot/vouchers/{}$", site, uuid)),
+    ];
+    for (verb, route) in writes {
+        Mock::given(method(verb))
+            .and(path_regex(route))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(server)
+            .await;
+    }
+
+    Mock::given(method("POST"))
+        .and(path(format!("{}/hotspot/vouchers", site)))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            let count = body["count"].as_u64().unwrap_or(1);
+            let vouchers: Vec<Value> = (0..count)
+                .map(|i| voucher(Uuid::new_v4(), &format!("{:010}", 1_000_000_000 + i)))
+                .collect();
+            ResponseTemplate::new(201).set_body_json(json!({ "vouchers": vouchers }))
+        })
+        .mount(server)
+        .await;
+
+    // Anything else under the API is an unknown resource.
+    Mock::given(path_regex("^/v1/"))
+        .respond_with(error(404, "Not Found"))
+        .with_priority(10)
+        .mount(server)
+        .await;
+}
+
+/// Responds with `body` to every request.
+fn fixed(body: Value) -> Responder {
+    Box::new(move |_: &Request| ResponseTemplate::new(200).set_body_json(&body))
+}
+
+/// Responds with the page of `items` selected by the `offset` and `limit` query parameters.
+fn paged(items: Vec<Value>) -> Responder {
+    Box::new(move |request: &Request| {
+        let param = |name: &str, default: usize| {
+            request
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .and_then(|(_, value)| value.parse().ok())
+                .unwrap_or(default)
+        };
+        let offset = param("offset", 0);
+        let limit = param("limit", 25);
+        let data: Vec<&Value> = items.iter().skip(offset).take(limit).collect();
+        ResponseTemplate::new(200).set_body_json(json!({
+            "offset": offset,
+            "limit": limit,
+            "count": data.len(),
+            "totalCount": items.len(),
+            "data": data
+        }))
+    })
+}
+
+fn error(status: u16, message: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(json!({
+        "statusCode": status,
+        "statusName": message.to_uppercase().replace(' ', "_"),
+        "message": message
+    }))
+}
+
+fn device_overview(id: DeviceId, name: &str, model: &str, ip: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "model": model,
+        "macAddress": mac_for(id),
+        "ipAddress": ip,
+        "state": "ONLINE",
+        "features": ["switching"],
+        "interfaces": ["ports"]
+    })
+}
+
+fn device_details(id: DeviceId, name: &str, model: &str, uplink: Option<DeviceId>) -> Value {
+    let mut details = json!({
+        "id": id,
+        "name": name,
+        "model": model,
+        "supported": true,
+        "macAddress": mac_for(id),
+        "ipAddress": "192.168.1.1",
+        "state": "ONLINE",
+        "firmwareVersion": "4.0.21",
+        "firmwareUpdatable": false,
+        "adoptedAt": "2025-01-10T09:00:00Z",
+        "provisionedAt": "2025-01-18T12:00:00Z",
+        "configurationId": "7596498d2f367dc2",
+        "features": {},
+        "interfaces": {
+            "ports": [{
+                "idx": 1,
+                "state": "UP",
+                "connector": "RJ45",
+                "maxSpeedMbps": 2500,
+                "speedMbps": 1000
+            }],
+            "radios": []
+        }
+    });
+    if let Some(uplink) = uplink {
+        details["uplink"] = json!({ "deviceId": uplink });
+        details["interfaces"]["radios"] = json!([
+            { "wlanStandard": "802.11ax", "frequencyGHz": 5, "channelWidthMHz": 80, "channel": 36 }
+        ]);
+    }
+    details
+}
+
+fn statistics() -> Value {
+    json!({
+        "uptimeSec": 737201,
+        "lastHeartbeatAt": "2025-01-18T20:26:02Z",
+        "nextHeartbeatAt": "2025-01-18T20:26:07Z",
+        "loadAverage1Min": 0.42,
+        "loadAverage5Min": 0.38,
+        "loadAverage15Min": 0.35,
+        "cpuUtilizationPct": 12.5,
+        "memoryUtilizationPct": 61.0,
+        "uplink": { "txRateBps": 309720, "rxRateBps": 32288 },
+        "interfaces": { "radios": [] }
+    })
+}
+
+fn wired_client() -> Value {
+    json!({
+        "type": "WIRED",
+        "id": WIRED_CLIENT_ID,
+        "name": "Desktop PC",
+        "connectedAt": "2025-01-18T08:00:00Z",
+        "ipAddress": "192.168.1.100",
+        "macAddress": "00:11:22:33:44:55",
+        "uplinkDeviceId": GATEWAY_ID,
+        "access": { "type": "DEFAULT" }
+    })
+}
+
+fn wireless_client() -> Value {
+    json!({
+        "type": "WIRELESS",
+        "id": WIRELESS_CLIENT_ID,
+        "name": "Phone",
+        "connectedAt": "2025-01-18T12:00:00Z",
+        "ipAddress": "192.168.1.50",
+        "macAddress": "aa:bb:cc:dd:ee:ff",
+        "uplinkDeviceId": ACCESS_POINT_ID,
+        "access": { "type": "DEFAULT" },
+        "ssid": "Office",
+        "band": "5"
+    })
+}
+
+fn voucher(id: Uuid, code: &str) -> Value {
+    json!({
+        "id": id,
+        "createdAt": "2025-01-18T20:00:00Z",
+        "name": "Conference",
+        "code": code,
+        "authorizedGuestLimit": 5,
+        "authorizedGuestCount": 0,
+        "expired": false,
+        "timeLimitMinutes": 1440,
+        "dataUsageLimitMBytes": 1024
+    })
+}
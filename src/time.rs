@@ -0,0 +1,53 @@
+//! Timer primitives that also work on `wasm32`, where tokio's timer is unavailable and
+//! `std::time::Instant::now` panics.
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use tokio::time::sleep;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+/// Waits for `duration` using the browser's `setTimeout`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: std::time::Duration) {
+    gloo_timers::future::sleep(duration).await
+}
+
+/// Returns a ticker firing every `period`, the first tick immediately. A tick that fires
+/// late delays the following ones instead of firing them in a burst.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn interval(period: Duration) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    interval
+}
+
+/// Returns a ticker behaving like tokio's `Interval` with `MissedTickBehavior::Delay`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn interval(period: Duration) -> Interval {
+    Interval {
+        period,
+        next: Instant::now(),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct Interval {
+    period: Duration,
+    next: Instant,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Interval {
+    /// Waits for the next tick.
+    pub(crate) async fn tick(&mut self) {
+        let now = Instant::now();
+        if self.next > now {
+            sleep(self.next - now).await;
+        }
+        self.next = self.next.max(now) + self.period;
+    }
+}
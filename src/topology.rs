@@ -0,0 +1,185 @@
+use crate::models::device::DeviceDetails;
+use crate::models::id::DeviceId;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// A device in a site's uplink topology.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopologyNode {
+    pub id: DeviceId,
+    pub name: String,
+    pub model: String,
+    /// The device this node uplinks to, or `None` for roots.
+    pub uplink_device_id: Option<DeviceId>,
+    /// Whether the device was identified as the site gateway, see `DeviceDetails::is_gateway`.
+    pub is_gateway: bool,
+}
+
+impl From<&DeviceDetails> for TopologyNode {
+    fn from(details: &DeviceDetails) -> Self {
+        Self {
+            id: details.id,
+            name: details.name.clone(),
+            model: details.model.clone(),
+            uplink_device_id: details.uplink.as_ref().map(|uplink| uplink.device_id),
+            is_gateway: details.is_gateway(),
+        }
+    }
+}
+
+/// A site's uplink topology: its devices and which device each uplinks to.
+///
+/// Build one from the devices of a `SiteSnapshot`, e.g.
+/// `Topology::from_devices(&client.site_snapshot(site_id).await?.devices)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Topology {
+    pub nodes: Vec<TopologyNode>,
+}
+
+impl Topology {
+    pub fn new(nodes: Vec<TopologyNode>) -> Self {
+        Self { nodes }
+    }
+
+    /// Builds the topology of `devices`.
+    pub fn from_devices(devices: &[DeviceDetails]) -> Self {
+        Self::new(devices.iter().map(TopologyNode::from).collect())
+    }
+
+    /// Returns the nodes the topology hangs from.
+    ///
+    /// These are the gateways that do not uplink to another node of the topology. A site
+    /// without such a gateway has no clear root, so every node that does not uplink to
+    /// another node is returned instead.
+    pub fn roots(&self) -> impl Iterator<Item = &TopologyNode> {
+        let gateway_rooted = self.unlinked().any(|node| node.is_gateway);
+        self.unlinked()
+            .filter(move |node| node.is_gateway || !gateway_rooted)
+    }
+
+    /// Returns the nodes that do not uplink to another node of the topology but are not
+    /// roots, such as a disconnected access point in a site with a gateway.
+    pub fn orphans(&self) -> impl Iterator<Item = &TopologyNode> {
+        let gateway_rooted = self.unlinked().any(|node| node.is_gateway);
+        self.unlinked()
+            .filter(move |node| !node.is_gateway && gateway_rooted)
+    }
+
+    /// Returns the nodes that uplink to the device with id `id`.
+    pub fn children(&self, id: DeviceId) -> impl Iterator<Item = &TopologyNode> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.uplink_device_id == Some(id))
+    }
+
+    /// Returns the nodes that do not uplink to another node of the topology.
+    fn unlinked(&self) -> impl Iterator<Item = &TopologyNode> {
+        let ids: HashSet<DeviceId> = self.nodes.iter().map(|node| node.id).collect();
+        self.nodes.iter().filter(move |node| {
+            node.uplink_device_id
+                .is_none_or(|uplink| !ids.contains(&uplink))
+        })
+    }
+
+    /// Renders the topology as a Graphviz DOT digraph, see `topology_to_dot`.
+    pub fn to_dot(&self) -> String {
+        topology_to_dot(&self.nodes)
+    }
+
+    /// Renders the topology as a Mermaid flowchart, see `topology_to_mermaid`.
+    pub fn to_mermaid(&self) -> String {
+        topology_to_mermaid(&self.nodes)
+    }
+}
+
+/// Renders the topology as a Graphviz DOT digraph.
+///
+/// Each device becomes a node labelled with its name, and each uplink relationship an
+/// edge from the uplink device to the downstream device. Edges to devices that are not
+/// part of `nodes` are omitted.
+pub fn topology_to_dot(nodes: &[TopologyNode]) -> String {
+    let ids: HashSet<DeviceId> = nodes.iter().map(|node| node.id).collect();
+    let mut dot = String::from("digraph topology {\n");
+
+    for node in nodes {
+        let _ = writeln!(
+            dot,
+            "    \"{}\" [label=\"{}\"];",
+            node.id,
+            escape_dot(&node.name)
+        );
+    }
+    for node in nodes {
+        if let Some(uplink) = node.uplink_device_id.filter(|uplink| ids.contains(uplink)) {
+            let _ = writeln!(dot, "    \"{}\" -> \"{}\";", uplink, node.id);
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Renders the topology as a top-down Mermaid flowchart.
+///
+/// Each device becomes a node labelled with its name, drawn as a stadium if it is the
+/// gateway, and each uplink relationship an arrow from the uplink device to the
+/// downstream device. Arrows to devices that are not part of `nodes` are omitted.
+pub fn topology_to_mermaid(nodes: &[TopologyNode]) -> String {
+    let ids: HashSet<DeviceId> = nodes.iter().map(|node| node.id).collect();
+    let mut mermaid = String::from("flowchart TD\n");
+
+    for node in nodes {
+        let label = escape_mermaid(&node.name);
+        let _ = if node.is_gateway {
+            writeln!(mermaid, "    {}([\"{}\"])", mermaid_id(node.id), label)
+        } else {
+            writeln!(mermaid, "    {}[\"{}\"]", mermaid_id(node.id), label)
+        };
+    }
+    for node in nodes {
+        if let Some(uplink) = node.uplink_device_id.filter(|uplink| ids.contains(uplink)) {
+            let _ = writeln!(
+                mermaid,
+                "    {} --> {}",
+                mermaid_id(uplink),
+                mermaid_id(node.id)
+            );
+        }
+    }
+    mermaid
+}
+
+/// Returns a Mermaid node id for a device; ids may not contain hyphens or start with a digit.
+fn mermaid_id(id: DeviceId) -> String {
+    format!("d{}", id.as_uuid().simple())
+}
+
+/// Escapes a string for use inside a double-quoted Mermaid label.
+fn escape_mermaid(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("#quot;"),
+            '#' => escaped.push_str("#35;"),
+            '\n' => escaped.push_str("<br/>"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a string for use inside a double-quoted DOT identifier.
+fn escape_dot(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,165 @@
+use crate::client::UnifiClient;
+use crate::models::device::DeviceState;
+use crate::models::id::{ClientId, DeviceId, SiteId};
+use crate::snapshot::{SiteDiff, SiteSnapshot};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// A change detected by a `Watcher` between two polls.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    DeviceWentOffline {
+        device_id: DeviceId,
+    },
+    /// Any state transition other than going offline, e.g. coming back online.
+    DeviceStateChanged {
+        device_id: DeviceId,
+        from: DeviceState,
+        to: DeviceState,
+    },
+    FirmwareUpdated {
+        device_id: DeviceId,
+        from: String,
+        to: String,
+    },
+    DeviceAdded {
+        device_id: DeviceId,
+    },
+    DeviceRemoved {
+        device_id: DeviceId,
+    },
+    NewClientJoined {
+        client_id: ClientId,
+    },
+    ClientLeft {
+        client_id: ClientId,
+    },
+    /// A poll failed; the watcher keeps the previous snapshot and retries on the next tick.
+    PollFailed {
+        message: String,
+    },
+}
+
+/// Converts a `SiteDiff` into the events a `Watcher` would emit for it.
+pub fn events_from_diff(diff: &SiteDiff) -> Vec<WatchEvent> {
+    let mut events = Vec::new();
+
+    for device_id in &diff.added_devices {
+        events.push(WatchEvent::DeviceAdded {
+            device_id: *device_id,
+        });
+    }
+    for device_id in &diff.removed_devices {
+        events.push(WatchEvent::DeviceRemoved {
+            device_id: *device_id,
+        });
+    }
+    for change in &diff.changed_devices {
+        let device_id = change.device_id;
+        match &change.state {
+            Some((_, DeviceState::Offline)) => {
+                events.push(WatchEvent::DeviceWentOffline { device_id })
+            }
+            Some((from, to)) => events.push(WatchEvent::DeviceStateChanged {
+                device_id,
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            None => {}
+        }
+        if let Some((from, to)) = &change.firmware_version {
+            events.push(WatchEvent::FirmwareUpdated {
+                device_id,
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+    }
+    for client_id in &diff.added_clients {
+        events.push(WatchEvent::NewClientJoined {
+            client_id: *client_id,
+        });
+    }
+    for client_id in &diff.removed_clients {
+        events.push(WatchEvent::ClientLeft {
+            client_id: *client_id,
+        });
+    }
+
+    events
+}
+
+/// Polls a site on an interval and broadcasts the changes between successive snapshots.
+///
+/// Useful when the controller's WebSocket feed is not reachable. The first poll only
+/// establishes a baseline. The background task is stopped when the `Watcher` is dropped.
+pub struct Watcher {
+    sender: broadcast::Sender<WatchEvent>,
+    task: JoinHandle<()>,
+}
+
+impl Watcher {
+    /// Starts polling `site_id` every `interval`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client used to take `SiteSnapshot`s.
+    /// * `site_id` - The id of the site to watch.
+    /// * `interval` - The delay between polls.
+    /// * `capacity` - How many events the channel buffers for slow subscribers. A capacity
+    ///   of 0 is treated as 1.
+    ///
+    /// # Returns
+    ///
+    /// The running `Watcher`; call `subscribe` to receive events.
+    pub fn spawn(
+        client: UnifiClient,
+        site_id: SiteId,
+        interval: Duration,
+        capacity: usize,
+    ) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        let events = sender.clone();
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            let mut previous: Option<SiteSnapshot> = None;
+
+            loop {
+                ticker.tick().await;
+                match client.site_snapshot(site_id).await {
+                    Ok(snapshot) => {
+                        if let Some(previous) = &previous {
+                            for event in events_from_diff(&previous.diff(&snapshot)) {
+                                // Sending only fails when nobody is subscribed.
+                                let _ = events.send(event);
+                            }
+                        }
+                        previous = Some(snapshot);
+                    }
+                    Err(error) => {
+                        let _ = events.send(WatchEvent::PollFailed {
+                            message: error.to_string(),
+                        });
+                    }
+                }
+            }
+        });
+
+        Self { sender, task }
+    }
+
+    /// Returns a receiver for events emitted from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<WatchEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}